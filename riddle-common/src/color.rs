@@ -28,6 +28,9 @@ impl ColorElement for f32 {
 ///
 /// Implemented by both individual color channel types, and compound types like
 /// [`Color`].
+///
+/// The conversion only rescales channel values, it doesn't change the color space. An sRGB
+/// color converts to an sRGB color, see [`Color::to_linear`] for changing color space.
 pub trait ColorElementConversion<T> {
 	/// Given a value that implements this trait, produce an equivalent color
 	/// element of the destination type.
@@ -80,6 +83,10 @@ impl ColorElementConversion<f32> for f32 {
 ///
 /// The two supported channel types are [`u8`] and [`f32`].
 ///
+/// Colors are in the sRGB color space, the space used by image files and color pickers,
+/// unless documented otherwise. [`Color::to_linear`] and [`Color::to_srgb`] convert the color
+/// channels of `f32` colors between sRGB and linear space. Alpha is always linear.
+///
 /// # Example
 ///
 /// ```
@@ -211,6 +218,64 @@ impl Color<u8> {
 	}
 }
 
+impl Color<f32> {
+	/// Convert an sRGB color to linear space, in which blending and interpolating colors is
+	/// physically correct. Alpha is unchanged.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use riddle_common::*;
+	/// let srgb = Color::rgba(0.5, 0.0, 1.0, 0.5);
+	/// let linear = srgb.to_linear();
+	/// assert!((linear.r - 0.214).abs() < 0.001);
+	/// assert_eq!([0.0, 1.0, 0.5], [linear.g, linear.b, linear.a]);
+	/// ```
+	pub fn to_linear(&self) -> Self {
+		Self::rgba(
+			srgb_to_linear(self.r),
+			srgb_to_linear(self.g),
+			srgb_to_linear(self.b),
+			self.a,
+		)
+	}
+
+	/// Convert a linear color to sRGB space. Alpha is unchanged.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use riddle_common::*;
+	/// let srgb = Color::rgb(0.5, 0.25, 0.75);
+	/// let round_trip = srgb.to_linear().to_srgb();
+	/// assert!((round_trip.g - srgb.g).abs() < 0.0001);
+	/// ```
+	pub fn to_srgb(&self) -> Self {
+		Self::rgba(
+			linear_to_srgb(self.r),
+			linear_to_srgb(self.g),
+			linear_to_srgb(self.b),
+			self.a,
+		)
+	}
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+	if c <= 0.04045 {
+		c / 12.92
+	} else {
+		((c + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+	if c <= 0.0031308 {
+		c * 12.92
+	} else {
+		(1.055 * c.powf(1.0 / 2.4)) - 0.055
+	}
+}
+
 /// Support converting colors between element types
 ///
 /// # Example
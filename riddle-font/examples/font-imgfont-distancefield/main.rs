@@ -0,0 +1,41 @@
+use std::{env, fs::File};
+
+use riddle_font::{FontError, ImgFontGenerator, TtFont};
+use riddle_image::ImageFormat;
+
+const IMG_OUT_FILE: &str = "font-imgfont-distancefield.out.png";
+
+const CHAR_SET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ1234567890-_=+! ";
+const TEST_STRING: &str = "Hello World!";
+const SPREAD: u32 = 4;
+
+fn main() -> Result<(), FontError> {
+	let font_bytes = include_bytes!("../../../example_assets/Roboto-Regular.ttf");
+	let ttf_font = TtFont::load(&font_bytes[..])?;
+	println!("+ TTF Loaded...");
+
+	let img_font = ImgFontGenerator::new(CHAR_SET, 32)
+		.distance_field(SPREAD)
+		.generate(&ttf_font)?;
+	println!(
+		"+ Distance Field ImgFont Generated (spread: {:?})...",
+		img_font.distance_field_spread()
+	);
+
+	let mut out_path = env::temp_dir();
+	out_path.push(IMG_OUT_FILE);
+	let out_file = File::create(out_path.clone())?;
+	img_font.image().save(out_file, ImageFormat::Png)?;
+	println!("+ ImgFont Image Saved ({:?})...", out_path);
+
+	// Glyphs are padded by the spread, so they can be placed before the start of the string
+	println!("+ Layout of {:?}", TEST_STRING);
+	println!("+ +++++++++++++++++++++++++++++");
+	img_font.layout(TEST_STRING, |character, _, location| {
+		println!("+ '{}': ({}, {})", character, location.x, location.y);
+	});
+	println!("+ +++++++++++++++++++++++++++++");
+
+	println!("+ Done.");
+	Ok(())
+}
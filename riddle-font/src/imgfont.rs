@@ -7,6 +7,10 @@ use std::collections::{HashMap, HashSet};
 use crate::rusttype_ext::*;
 use crate::*;
 
+/// The factor distance field glyphs are rendered larger by, before the field is generated and
+/// scaled back down. Must be a power of two.
+const DISTANCE_FIELD_SUPERSAMPLE: u32 = 4;
+
 /// Represents an image font, which is an Image containing glyphs, glyph information
 /// specifying which parts of the image map to which glyph, and layout information.
 #[derive(Clone)]
@@ -14,6 +18,7 @@ pub struct ImgFont {
 	img: Image,
 	glyphs: HashMap<char, ImgFontGlyph>,
 	vertical_spacing: u32,
+	distance_field_spread: Option<u32>,
 }
 
 impl ImgFont {
@@ -54,6 +59,34 @@ impl ImgFont {
 		&self.img
 	}
 
+	/// If the font's image is a distance field, the distance in pixels from the edge of each
+	/// glyph to where the field reaches its minimum or maximum value.
+	///
+	/// Distance field fonts store white glyphs, with the alpha channel storing the distance to
+	/// the edge of the glyph. An alpha of 0.5 is on the edge, larger values are inside the glyph,
+	/// and each pixel of distance changes the alpha by `1 / (2 * spread)`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use riddle_font::*;
+	/// # fn main() -> Result<(), FontError> {
+	/// # let font_bytes = include_bytes!("../../example_assets/Roboto-Regular.ttf");
+	/// let ttf_font = TtFont::load(&font_bytes[..])?;
+	///
+	/// let img_font = ImgFontGenerator::new("ABCDEF", 32).generate(&ttf_font)?;
+	/// assert_eq!(None, img_font.distance_field_spread());
+	///
+	/// let sdf_font = ImgFontGenerator::new("ABCDEF", 32)
+	///     .distance_field(4)
+	///     .generate(&ttf_font)?;
+	/// assert_eq!(Some(4), sdf_font.distance_field_spread());
+	/// # Ok(()) }
+	/// ```
+	pub fn distance_field_spread(&self) -> Option<u32> {
+		self.distance_field_spread
+	}
+
 	/// The mapping from [`char`] to glyphs registered with the font.
 	///
 	/// # Example
@@ -120,7 +153,7 @@ impl ImgFont {
 
 		self.layout(text, |_, glyph, location| {
 			if let Some(source_rect) = &glyph.rect {
-				output_image.blit_rect(&self.img, source_rect, location.convert());
+				output_image.blit_rect(&self.img, source_rect, location);
 			}
 		});
 
@@ -131,7 +164,8 @@ impl ImgFont {
 	/// layout space.
 	///
 	/// The callback fuction is invoked once for each character, providing the part of the image to
-	/// be used, and the location to draw that image.
+	/// be used, and the location to draw that image. Glyphs which extend to the left of or above
+	/// their layout position, such as distance field glyphs, may have negative locations.
 	///
 	/// # Example
 	///
@@ -160,20 +194,20 @@ impl ImgFont {
 	/// assert_eq!("AABCA", string);
 	/// # Ok(()) }
 	/// ```
-	pub fn layout<F: FnMut(char, &ImgFontGlyph, Vector2<u32>)>(&self, text: &str, mut f: F) {
-		let mut current_x = 0_u32;
+	pub fn layout<F: FnMut(char, &ImgFontGlyph, Vector2<i32>)>(&self, text: &str, mut f: F) {
+		let mut current_x = 0_i32;
 
 		for c in text.chars() {
 			if let Some(glyph) = self.glyphs.get(&c) {
 				let position = Vector2::new(current_x, 0)
 					+ if glyph.rect.is_some() {
-						glyph.placement_offset.convert()
+						glyph.placement_offset
 					} else {
 						vec2(0, 0)
 					};
 
 				f(c, &glyph, position);
-				current_x += glyph.horizontal_spacing;
+				current_x += glyph.horizontal_spacing as i32;
 			}
 		}
 	}
@@ -197,9 +231,13 @@ impl ImgFont {
 	/// ```
 	pub fn layout_dimensions(&self, text: &str) -> Vector2<u32> {
 		let mut max = vec2(0, self.vertical_spacing);
-		self.layout(text, |_, glyph, pos| {
-			max.x = max.x.max(pos.x + glyph.horizontal_spacing);
-		});
+		let mut current_x = 0;
+		for c in text.chars() {
+			if let Some(glyph) = self.glyphs.get(&c) {
+				current_x += glyph.horizontal_spacing;
+				max.x = max.x.max(current_x);
+			}
+		}
 		max
 	}
 }
@@ -254,6 +292,7 @@ pub struct ImgFontLayoutRule {
 pub struct ImgFontBuilder {
 	glyphs: Vec<ImgFontGlyph>,
 	vertical_spacing: u32,
+	distance_field_spread: Option<u32>,
 }
 
 impl ImgFontBuilder {
@@ -268,6 +307,13 @@ impl ImgFontBuilder {
 		self
 	}
 
+	/// Mark the font's image as a distance field with the given spread. See
+	/// [`ImgFont::distance_field_spread`].
+	pub fn distance_field_spread(&mut self, spread: u32) -> &mut Self {
+		self.distance_field_spread = Some(spread);
+		self
+	}
+
 	/// Register a glyph with the font. If more that one glyph is added with the same value for
 	/// [`ImgFontGlyph::character`] the last one added will be the one that is respected.
 	pub fn with_glyph(&mut self, glyph: ImgFontGlyph) -> &mut Self {
@@ -285,6 +331,7 @@ impl ImgFontBuilder {
 				.map(|g| (g.character, g.clone()))
 				.collect(),
 			vertical_spacing: self.vertical_spacing,
+			distance_field_spread: self.distance_field_spread,
 		}
 	}
 }
@@ -311,6 +358,7 @@ pub struct ImgFontGenerator {
 	pixel_height: u32,
 	fore_color: Color<u8>,
 	packing_size_policy: ImagePackerSizePolicy,
+	distance_field_spread: Option<u32>,
 }
 
 impl ImgFontGenerator {
@@ -334,6 +382,18 @@ impl ImgFontGenerator {
 		self
 	}
 
+	/// Generate a distance field font, which can be rendered crisply at any scale. See
+	/// [`ImgFont::distance_field_spread`].
+	///
+	/// Each glyph is padded by the spread on every side. A larger spread allows wider outlines
+	/// and glows, at the cost of a larger image. Glyphs are rendered at a higher resolution
+	/// before being converted, so a `pixel_height` of 32 to 64 is usually enough for any
+	/// display size.
+	pub fn distance_field(&mut self, spread: u32) -> &mut Self {
+		self.distance_field_spread = Some(spread);
+		self
+	}
+
 	/// Build the ImgFont for the given [`TtFont`] using the parameters specified in the generator.
 	pub fn generate(&self, ttfont: &TtFont) -> Result<ImgFont> {
 		let characters: Vec<char> = self.characters.iter().copied().collect();
@@ -361,35 +421,10 @@ impl ImgFontGenerator {
 					x: 0.0,
 					y: v_metrics.ascent,
 				});
-			let h_metrics = glyph.unpositioned().h_metrics();
-
-			let (image, img_glyph) = match glyph.pixel_bounding_box() {
-				Some(pixel_bounds) => {
-					let mut image =
-						Image::new(pixel_bounds.width() as u32, pixel_bounds.height() as u32);
-					glyph.draw(|x, y, v| {
-						let mut c = self.fore_color.clone();
-						c.a = (255.0 * v) as u8;
-						image.set_pixel([x as u32, y as u32], c);
-					});
-					let img_glyph = ImgFontGlyph {
-						character: *c,
-						horizontal_spacing: h_metrics.advance_width as u32,
-						placement_offset: Vector2::new(pixel_bounds.min.x, pixel_bounds.min.y),
-						rect: None,
-					};
-					(image, img_glyph)
-				}
-				_ => {
-					let image = Image::new(0, 0);
-					let img_glyph = ImgFontGlyph {
-						character: *c,
-						horizontal_spacing: h_metrics.advance_width as u32,
-						placement_offset: Vector2::new(0, 0),
-						rect: None,
-					};
-					(image, img_glyph)
-				}
+
+			let (image, img_glyph) = match self.distance_field_spread {
+				Some(spread) => self.render_distance_field_glyph(font, &glyph, *c, spread),
+				None => self.render_glyph(&glyph, *c),
 			};
 
 			images.push(image);
@@ -404,6 +439,9 @@ impl ImgFontGenerator {
 
 		let mut img_font_builder = ImgFontBuilder::new();
 		img_font_builder.vertical_spacing(vertical_spacing as u32);
+		if let Some(spread) = self.distance_field_spread {
+			img_font_builder.distance_field_spread(spread);
+		}
 
 		for (packed_rect, img_glyph) in packer.rects().iter().zip(imgfont_glyphs.iter()) {
 			let mut glyph = img_glyph.clone();
@@ -418,4 +456,129 @@ impl ImgFontGenerator {
 
 		Ok(img_font_builder.build(packer.take_image()))
 	}
+
+	fn render_glyph(&self, glyph: &rusttype::PositionedGlyph, c: char) -> (Image, ImgFontGlyph) {
+		let h_metrics = glyph.unpositioned().h_metrics();
+		match glyph.pixel_bounding_box() {
+			Some(pixel_bounds) => {
+				let mut image =
+					Image::new(pixel_bounds.width() as u32, pixel_bounds.height() as u32);
+				glyph.draw(|x, y, v| {
+					let mut c = self.fore_color.clone();
+					c.a = (255.0 * v) as u8;
+					image.set_pixel([x as u32, y as u32], c);
+				});
+				let img_glyph = ImgFontGlyph {
+					character: c,
+					horizontal_spacing: h_metrics.advance_width as u32,
+					placement_offset: Vector2::new(pixel_bounds.min.x, pixel_bounds.min.y),
+					rect: None,
+				};
+				(image, img_glyph)
+			}
+			_ => {
+				let image = Image::new(0, 0);
+				let img_glyph = ImgFontGlyph {
+					character: c,
+					horizontal_spacing: h_metrics.advance_width as u32,
+					placement_offset: Vector2::new(0, 0),
+					rect: None,
+				};
+				(image, img_glyph)
+			}
+		}
+	}
+
+	/// Render a glyph at a multiple of the font's pixel height, generate a distance field from
+	/// it, and scale the field back down. The glyph is padded by the spread, and aligned so
+	/// that each output pixel covers a whole block of rendered pixels.
+	fn render_distance_field_glyph(
+		&self,
+		font: &rusttype::Font,
+		glyph: &rusttype::PositionedGlyph,
+		c: char,
+		spread: u32,
+	) -> (Image, ImgFontGlyph) {
+		let h_metrics = glyph.unpositioned().h_metrics();
+		let glyph_scale = (self.pixel_height * DISTANCE_FIELD_SUPERSAMPLE) as f32;
+		let glyph = font
+			.glyph(c)
+			.scaled(rusttype::Scale {
+				x: glyph_scale,
+				y: glyph_scale,
+			})
+			.positioned(rusttype::Point {
+				x: 0.0,
+				y: glyph.position().y * DISTANCE_FIELD_SUPERSAMPLE as f32,
+			});
+
+		let pixel_bounds = match glyph.pixel_bounding_box() {
+			Some(pixel_bounds) => pixel_bounds,
+			None => {
+				let img_glyph = ImgFontGlyph {
+					character: c,
+					horizontal_spacing: h_metrics.advance_width as u32,
+					placement_offset: Vector2::new(0, 0),
+					rect: None,
+				};
+				return (Image::new(0, 0), img_glyph);
+			}
+		};
+
+		let supersample = DISTANCE_FIELD_SUPERSAMPLE as i32;
+		let spread = spread as i32;
+		let min = Vector2::new(
+			pixel_bounds.min.x.div_euclid(supersample),
+			pixel_bounds.min.y.div_euclid(supersample),
+		);
+		let max = Vector2::new(
+			(pixel_bounds.max.x + supersample - 1).div_euclid(supersample),
+			(pixel_bounds.max.y + supersample - 1).div_euclid(supersample),
+		);
+		let placement_offset = min - Vector2::new(spread, spread);
+		let dimensions: Vector2<u32> = (max - min + Vector2::new(spread * 2, spread * 2)).convert();
+
+		// Render the glyph as opaque white on black, offset within the padded image
+		let mut image = Image::new(
+			dimensions.x * DISTANCE_FIELD_SUPERSAMPLE,
+			dimensions.y * DISTANCE_FIELD_SUPERSAMPLE,
+		);
+		image.fill(Color::<u8>::BLACK);
+		let origin = Vector2::new(
+			pixel_bounds.min.x - placement_offset.x * supersample,
+			pixel_bounds.min.y - placement_offset.y * supersample,
+		);
+		glyph.draw(|x, y, v| {
+			let value = (255.0 * v) as u8;
+			image.set_pixel(
+				[x + origin.x as u32, y + origin.y as u32],
+				Color::rgba(value, value, value, 255),
+			);
+		});
+
+		let field_scale = (2 * spread * supersample) as f64;
+		let mut field = riddle_image::filters::distance_field(image, field_scale);
+		while field.width() > dimensions.x {
+			field = riddle_image::filters::downsample(&field);
+		}
+
+		// Move the distance in to the alpha channel, so the glyphs are white
+		let mut image = Image::new(dimensions.x, dimensions.y);
+		for y in 0..dimensions.y {
+			for x in 0..dimensions.x {
+				let distance = field.get_pixel([x, y]).r;
+				let mut color = self.fore_color.clone();
+				color.a = distance;
+				image.set_pixel([x, y], color);
+			}
+		}
+
+		let img_glyph = ImgFontGlyph {
+			character: c,
+			horizontal_spacing: h_metrics.advance_width as u32,
+			placement_offset,
+			rect: None,
+		};
+		(image, img_glyph)
+	}
 }
@@ -0,0 +1,84 @@
+use crate::*;
+
+use riddle_common::Color;
+
+/// Halve the dimensions of an image by averaging each 2x2 block of pixels. Each dimension is
+/// rounded down, to a minimum of 1. Where a dimension is odd, the last row or column is dropped.
+///
+/// Color channels are weighted by alpha, so fully transparent pixels don't darken the edges of
+/// the opaque pixels next to them.
+///
+/// # Examples
+///
+/// ```
+/// # use riddle_image::*; use riddle_math::*;
+/// let mut img = Image::new(4, 2);
+/// img.fill(Color::rgba(255, 0, 0, 255));
+///
+/// let half = filters::downsample(&img);
+/// assert_eq!(vec2(2, 1), half.dimensions());
+/// assert_eq!(Color::rgba(255, 0, 0, 255), half.get_pixel([0, 0]));
+/// ```
+pub fn downsample(source: &Image) -> Image {
+	let width = (source.width() / 2).max(1);
+	let height = (source.height() / 2).max(1);
+	let mut result = Image::new(width, height);
+
+	let max_x = source.width().saturating_sub(1);
+	let max_y = source.height().saturating_sub(1);
+	for y in 0..height {
+		for x in 0..width {
+			let mut sum = [0u32; 4];
+			for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+				let c = source.get_pixel([(x * 2 + dx).min(max_x), (y * 2 + dy).min(max_y)]);
+				let a = c.a as u32;
+				sum[0] += c.r as u32 * a;
+				sum[1] += c.g as u32 * a;
+				sum[2] += c.b as u32 * a;
+				sum[3] += a;
+			}
+
+			let color = if sum[3] == 0 {
+				Color::rgba(0, 0, 0, 0)
+			} else {
+				let channel = |v: u32| ((v + sum[3] / 2) / sum[3]) as u8;
+				Color::rgba(
+					channel(sum[0]),
+					channel(sum[1]),
+					channel(sum[2]),
+					((sum[3] + 2) / 4) as u8,
+				)
+			};
+			result.set_pixel([x, y], color);
+		}
+	}
+
+	result
+}
+
+/// Generate the mip chain for an image. The returned levels start at half the size of the
+/// source, and each following level is half the size of the previous one, down to 1x1. The
+/// source image itself isn't included.
+///
+/// # Examples
+///
+/// ```
+/// # use riddle_image::*; use riddle_math::*;
+/// let img = Image::new(8, 2);
+/// let levels = filters::mipmap_chain(&img);
+///
+/// let dimensions: Vec<Vector2<u32>> = levels.iter().map(|level| level.dimensions()).collect();
+/// assert_eq!(vec![vec2(4, 1), vec2(2, 1), vec2(1, 1)], dimensions);
+/// ```
+pub fn mipmap_chain(source: &Image) -> Vec<Image> {
+	let mut levels: Vec<Image> = vec![];
+	loop {
+		let previous = levels.last().unwrap_or(source);
+		if previous.width() <= 1 && previous.height() <= 1 {
+			break;
+		}
+		let next = downsample(previous);
+		levels.push(next);
+	}
+	levels
+}
@@ -1,3 +1,5 @@
 mod distance_field;
+mod mipmap;
 
 pub use distance_field::*;
+pub use mipmap::*;
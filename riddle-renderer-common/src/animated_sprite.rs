@@ -0,0 +1,334 @@
+use crate::*;
+
+use riddle_time::TimeSystem;
+use std::time::Duration;
+
+/// A sprite which plays animations from a [`SpriteSheet`].
+///
+/// Each frame of the sheet is taken from the source sprite with [`CommonSprite::subsprite`].
+/// The animation is advanced with [`AnimatedSprite::update`] or
+/// [`AnimatedSprite::update_with_time`], and the current frame is drawn with
+/// [`AnimatedSprite::render`].
+///
+/// Until a tag is played, all the frames of the sheet are played in order, looping.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let img = Image::new(128, 32);
+/// let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new())?;
+///
+/// let sheet = SpriteSheet::from_grid(vec2(128.0, 32.0), vec2(32.0, 32.0), Duration::from_millis(100))
+///     .with_tag("walk", 0..4);
+/// let mut player = AnimatedSprite::new(&sprite, &sheet);
+/// player.play("walk", PlaybackMode::PingPong)?;
+///
+/// // Once per frame
+/// player.update_with_time(&rdl.state().time());
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     player.render(render_ctx, &SpriteRenderArgs::new(vec2(10.0, 10.0)))
+/// })?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct AnimatedSprite<S> {
+	frames: Vec<S>,
+	sheet: SpriteSheet,
+	player: AnimationPlayer,
+}
+
+impl<S> AnimatedSprite<S> {
+	/// Build an animated sprite from a sprite containing the whole sheet, and the sheet's
+	/// frame layout.
+	pub fn new<R>(sprite: &S, sheet: &SpriteSheet) -> Self
+	where
+		R: CommonRenderer,
+		S: CommonSprite<R>,
+	{
+		let frames = sheet
+			.frames
+			.iter()
+			.map(|frame| sprite.subsprite(&frame.rect))
+			.collect();
+		let mut animated = Self {
+			frames,
+			sheet: sheet.clone(),
+			player: AnimationPlayer::new(vec![], PlaybackMode::Loop),
+		};
+		animated.play_all(PlaybackMode::Loop);
+		animated
+	}
+
+	/// Play a tag from its first frame.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use riddle_renderer_common::*;
+	/// # use riddle_math::*;
+	/// # use std::time::Duration;
+	/// # fn example<R: CommonRenderer>(sprite: &R::Sprite) -> Result<(), SpriteSheetError> {
+	/// let sheet = SpriteSheet::from_grid(vec2(64.0, 16.0), vec2(16.0, 16.0), Duration::from_millis(100))
+	///     .with_tag("attack", 0..4);
+	/// let mut animated = AnimatedSprite::new(sprite, &sheet);
+	///
+	/// animated.play("attack", PlaybackMode::Once)?;
+	/// animated.update(Duration::from_secs(1));
+	/// assert!(animated.is_finished());
+	/// # Ok(()) }
+	/// ```
+	pub fn play(&mut self, tag: &str, mode: PlaybackMode) -> Result<(), SpriteSheetError> {
+		let tag = self
+			.sheet
+			.tag(tag)
+			.ok_or_else(|| SpriteSheetError::UnknownTag(tag.to_owned()))?;
+
+		if tag.frames.iter().any(|frame| *frame >= self.frames.len()) {
+			return Err(SpriteSheetError::InvalidData(
+				"Tag refers to a frame outside of the sheet",
+			));
+		}
+
+		self.player =
+			AnimationPlayer::with_sequence(self.frame_durations(), tag.frames.clone(), mode);
+		Ok(())
+	}
+
+	/// Play all the frames in the sheet, in order, from the first frame.
+	pub fn play_all(&mut self, mode: PlaybackMode) {
+		self.player = AnimationPlayer::new(self.frame_durations(), mode);
+	}
+
+	/// Advance the animation by the given time.
+	pub fn update(&mut self, delta: Duration) {
+		self.player.update(delta);
+	}
+
+	/// Advance the animation by the time system's frame delta. Call once per frame.
+	pub fn update_with_time(&mut self, time: &TimeSystem) {
+		self.update(Duration::from_secs_f32(time.delta_secs()));
+	}
+
+	/// The index in the sheet of the frame currently displayed.
+	pub fn current_frame(&self) -> usize {
+		self.player.current_frame()
+	}
+
+	/// The sprite for the frame currently displayed.
+	pub fn current_sprite(&self) -> Option<&S> {
+		self.frames.get(self.current_frame())
+	}
+
+	/// Whether a [`PlaybackMode::Once`] animation has reached the end of its last frame. Looping
+	/// animations never finish.
+	pub fn is_finished(&self) -> bool {
+		self.player.is_finished()
+	}
+
+	/// Render the current frame.
+	pub fn render<R, Ctx>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+	) -> Result<(), R::Error>
+	where
+		R: CommonRenderer,
+		S: CommonSprite<R>,
+		Ctx: RenderContext<R> + ?Sized,
+	{
+		match self.current_sprite() {
+			Some(sprite) => sprite.render(render_ctx, args),
+			None => Ok(()),
+		}
+	}
+
+	fn frame_durations(&self) -> Vec<Duration> {
+		self.sheet
+			.frames
+			.iter()
+			.map(|frame| frame.duration)
+			.collect()
+	}
+}
+
+/// How an [`AnimatedSprite`] moves through an animation's frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+	/// Return to the first frame after the last frame.
+	#[default]
+	Loop,
+
+	/// Play the frames forwards then backwards, repeatedly. The first and last frames aren't
+	/// repeated when changing direction.
+	PingPong,
+
+	/// Play the frames once, stopping on the last frame.
+	Once,
+}
+
+/// The playback state of a sequence of frames.
+#[derive(Debug, Clone)]
+struct AnimationPlayer {
+	durations: Vec<Duration>,
+	sequence: Vec<usize>,
+	mode: PlaybackMode,
+
+	position: usize,
+	forwards: bool,
+	elapsed: Duration,
+	finished: bool,
+}
+
+impl AnimationPlayer {
+	/// Play every frame in order.
+	fn new(durations: Vec<Duration>, mode: PlaybackMode) -> Self {
+		let sequence = (0..durations.len()).collect();
+		Self::with_sequence(durations, sequence, mode)
+	}
+
+	/// Play a sequence of frame indices.
+	fn with_sequence(durations: Vec<Duration>, sequence: Vec<usize>, mode: PlaybackMode) -> Self {
+		Self {
+			durations,
+			sequence,
+			mode,
+			position: 0,
+			forwards: true,
+			elapsed: Duration::from_secs(0),
+			finished: false,
+		}
+	}
+
+	fn current_frame(&self) -> usize {
+		self.sequence.get(self.position).copied().unwrap_or(0)
+	}
+
+	fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	fn update(&mut self, delta: Duration) {
+		let total: Duration = self
+			.sequence
+			.iter()
+			.map(|frame| self.durations[*frame])
+			.sum();
+		if self.finished || total == Duration::from_secs(0) {
+			return;
+		}
+
+		self.elapsed += delta;
+		loop {
+			let duration = self.durations[self.current_frame()];
+			if self.elapsed < duration {
+				break;
+			}
+
+			self.elapsed -= duration;
+			self.advance();
+			if self.finished {
+				self.elapsed = Duration::from_secs(0);
+				break;
+			}
+		}
+	}
+
+	fn advance(&mut self) {
+		let last = self.sequence.len() - 1;
+		match self.mode {
+			PlaybackMode::Loop => {
+				self.position = if self.position == last {
+					0
+				} else {
+					self.position + 1
+				};
+			}
+			PlaybackMode::Once => {
+				if self.position == last {
+					self.finished = true;
+				} else {
+					self.position += 1;
+				}
+			}
+			PlaybackMode::PingPong => {
+				if last == 0 {
+					return;
+				}
+
+				if self.forwards && self.position == last {
+					self.forwards = false;
+				} else if !self.forwards && self.position == 0 {
+					self.forwards = true;
+				}
+
+				if self.forwards {
+					self.position += 1;
+				} else {
+					self.position -= 1;
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn ms(millis: u64) -> Duration {
+		Duration::from_millis(millis)
+	}
+
+	fn frames_over_time(player: &mut AnimationPlayer, steps: usize, step: Duration) -> Vec<usize> {
+		(0..steps)
+			.map(|_| {
+				player.update(step);
+				player.current_frame()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn loop_wraps_and_uses_frame_durations() {
+		let mut player = AnimationPlayer::new(vec![ms(10), ms(20), ms(10)], PlaybackMode::Loop);
+
+		assert_eq!(
+			vec![1, 1, 2, 0, 1, 1],
+			frames_over_time(&mut player, 6, ms(10))
+		);
+	}
+
+	#[test]
+	fn ping_pong_reverses_at_ends() {
+		let mut player = AnimationPlayer::new(vec![ms(10), ms(10), ms(10)], PlaybackMode::PingPong);
+
+		assert_eq!(
+			vec![1, 2, 1, 0, 1, 2],
+			frames_over_time(&mut player, 6, ms(10))
+		);
+	}
+
+	#[test]
+	fn once_stops_on_last_frame() {
+		let mut player =
+			AnimationPlayer::with_sequence(vec![ms(10); 4], vec![3, 1], PlaybackMode::Once);
+
+		assert_eq!(vec![1, 1], frames_over_time(&mut player, 2, ms(10)));
+		assert!(player.is_finished());
+	}
+
+	#[test]
+	fn large_delta_skips_frames() {
+		let mut player = AnimationPlayer::new(vec![ms(10); 4], PlaybackMode::Loop);
+		player.update(ms(65));
+
+		assert_eq!(2, player.current_frame());
+	}
+}
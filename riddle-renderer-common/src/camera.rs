@@ -0,0 +1,240 @@
+use riddle_math::{Rect, Vector2};
+
+/// A 2D camera, describing which part of the world is visible in a viewport.
+///
+/// The camera's `position` is the world location which is displayed at the viewport `anchor`.
+/// The anchor is relative to the viewport dimensions, so the default anchor of `(0.5, 0.5)`
+/// centers the camera position in the viewport, while `(0.0, 0.0)` places it at the top left.
+///
+/// Use [`RenderContext::set_camera`](crate::RenderContext::set_camera) to render with the
+/// camera, and [`Camera2D::screen_to_world`] to map screen locations, such as the mouse
+/// position, back in to the world.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, input::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// # let input_system = rdl.state().input();
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let mut camera = Camera2D::new(renderer.dimensions());
+/// camera.bounds = Some(Rect::new(vec2(0.0, 0.0), vec2(2000.0, 1000.0)));
+/// camera.zoom = 2.0;
+///
+/// // Keep the player in view, without showing anything outside the level bounds
+/// let player_location = vec2(100.0, 500.0);
+/// camera.follow(player_location);
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     render_ctx.set_camera(&camera)?;
+///     render_ctx.fill_rect(&Rect::new(player_location, vec2(10.0, 10.0)), Color::GREEN)
+/// })?;
+///
+/// // Find which part of the world the mouse is over
+/// let world_mouse_pos = camera.screen_to_world(input_system.mouse_pos(window.id()).into());
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera2D {
+	/// The world location displayed at the anchor.
+	pub position: Vector2<f32>,
+
+	/// The scale applied to the world. Values greater than 1 zoom in.
+	pub zoom: f32,
+
+	/// The camera rotation in radians. Rotating the camera clockwise rotates the world
+	/// anticlockwise on screen.
+	pub rotation: f32,
+
+	/// Where in the viewport the position is displayed, relative to the viewport dimensions.
+	pub anchor: Vector2<f32>,
+
+	/// The dimensions of the viewport the camera renders to, in screen units.
+	pub viewport: Vector2<f32>,
+
+	/// If set, [`Camera2D::clamp_to_bounds`] and [`Camera2D::follow`] will keep the visible
+	/// area within these world bounds.
+	pub bounds: Option<Rect<f32>>,
+}
+
+impl Camera2D {
+	/// A camera for a viewport of the given dimensions, centered on the world origin.
+	pub fn new(viewport: Vector2<f32>) -> Self {
+		Self {
+			position: Vector2::new(0.0, 0.0),
+			zoom: 1.0,
+			rotation: 0.0,
+			anchor: Vector2::new(0.5, 0.5),
+			viewport,
+			bounds: None,
+		}
+	}
+
+	/// The matrix which transforms world coordinates to screen coordinates, in the form
+	/// accepted by [`RenderContext::set_transform`](crate::RenderContext::set_transform).
+	pub fn transform(&self) -> mint::ColumnMatrix4<f32> {
+		self.matrix().into()
+	}
+
+	/// Map a location in the world to a location in the viewport.
+	pub fn world_to_screen(&self, point: Vector2<f32>) -> Vector2<f32> {
+		self.matrix()
+			.transform_point3(glam::vec3(point.x, point.y, 0.0))
+			.truncate()
+			.into()
+	}
+
+	/// Map a location in the viewport to a location in the world.
+	pub fn screen_to_world(&self, point: Vector2<f32>) -> Vector2<f32> {
+		self.matrix()
+			.inverse()
+			.transform_point3(glam::vec3(point.x, point.y, 0.0))
+			.truncate()
+			.into()
+	}
+
+	/// The smallest world space rect which contains everything visible in the viewport.
+	pub fn visible_rect(&self) -> Rect<f32> {
+		let corners = [
+			self.screen_to_world(Vector2::new(0.0, 0.0)),
+			self.screen_to_world(Vector2::new(self.viewport.x, 0.0)),
+			self.screen_to_world(Vector2::new(0.0, self.viewport.y)),
+			self.screen_to_world(self.viewport),
+		];
+
+		let mut min = corners[0];
+		let mut max = corners[0];
+		for corner in &corners[1..] {
+			min = Vector2::new(min.x.min(corner.x), min.y.min(corner.y));
+			max = Vector2::new(max.x.max(corner.x), max.y.max(corner.y));
+		}
+
+		Rect::new(min, max - min)
+	}
+
+	/// Move the camera so the target is displayed at the anchor, then clamp it to the bounds.
+	pub fn follow(&mut self, target: Vector2<f32>) {
+		self.position = target;
+		self.clamp_to_bounds();
+	}
+
+	/// Move the camera part of the way towards the target, then clamp it to the bounds.
+	///
+	/// A `factor` of 0 leaves the camera where it is, and 1 moves it all the way to the target.
+	/// Calling this every frame with a small factor gives a smooth follow.
+	pub fn follow_smoothed(&mut self, target: Vector2<f32>, factor: f32) {
+		let factor = factor.clamp(0.0, 1.0);
+		self.position = self.position + ((target - self.position) * factor);
+		self.clamp_to_bounds();
+	}
+
+	/// Move the camera so the visible area doesn't extend past the bounds. If the visible area
+	/// is larger than the bounds along an axis, the camera is centered on the bounds along that
+	/// axis.
+	pub fn clamp_to_bounds(&mut self) {
+		let bounds = match &self.bounds {
+			Some(bounds) => bounds.clone(),
+			None => return,
+		};
+
+		let visible = self.visible_rect();
+		let offset_min = self.position - visible.location;
+		let offset_max = (visible.location + visible.dimensions) - self.position;
+
+		let clamp_axis = |position: f32, min: f32, size: f32, before: f32, after: f32| {
+			if before + after >= size {
+				min + (size / 2.0) + ((before - after) / 2.0)
+			} else {
+				position.clamp(min + before, min + size - after)
+			}
+		};
+
+		self.position = Vector2::new(
+			clamp_axis(
+				self.position.x,
+				bounds.location.x,
+				bounds.dimensions.x,
+				offset_min.x,
+				offset_max.x,
+			),
+			clamp_axis(
+				self.position.y,
+				bounds.location.y,
+				bounds.dimensions.y,
+				offset_min.y,
+				offset_max.y,
+			),
+		);
+	}
+
+	fn matrix(&self) -> glam::Mat4 {
+		let anchor = glam::vec3(
+			self.anchor.x * self.viewport.x,
+			self.anchor.y * self.viewport.y,
+			0.0,
+		);
+		glam::Mat4::from_translation(anchor)
+			* glam::Mat4::from_scale(glam::vec3(self.zoom, self.zoom, 1.0))
+			* glam::Mat4::from_rotation_z(-self.rotation)
+			* glam::Mat4::from_translation(glam::vec3(-self.position.x, -self.position.y, 0.0))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn assert_near(expected: Vector2<f32>, actual: Vector2<f32>) {
+		assert!(
+			(expected.x - actual.x).abs() < 0.001 && (expected.y - actual.y).abs() < 0.001,
+			"expected {:?}, got {:?}",
+			expected,
+			actual
+		);
+	}
+
+	#[test]
+	fn world_screen_round_trip() {
+		let mut camera = Camera2D::new(Vector2::new(800.0, 600.0));
+		camera.position = Vector2::new(100.0, 50.0);
+		camera.zoom = 2.0;
+		camera.rotation = 0.5;
+
+		assert_near(
+			Vector2::new(400.0, 300.0),
+			camera.world_to_screen(camera.position),
+		);
+
+		let world = Vector2::new(-20.0, 75.0);
+		assert_near(world, camera.screen_to_world(camera.world_to_screen(world)));
+	}
+
+	#[test]
+	fn zoom_scales_around_anchor() {
+		let mut camera = Camera2D::new(Vector2::new(800.0, 600.0));
+		camera.anchor = Vector2::new(0.0, 0.0);
+		camera.zoom = 2.0;
+
+		assert_near(
+			Vector2::new(20.0, 40.0),
+			camera.world_to_screen(Vector2::new(10.0, 20.0)),
+		);
+	}
+
+	#[test]
+	fn follow_clamps_to_bounds() {
+		let mut camera = Camera2D::new(Vector2::new(100.0, 100.0));
+		camera.bounds = Some(Rect::new(
+			Vector2::new(0.0, 0.0),
+			Vector2::new(1000.0, 50.0),
+		));
+
+		camera.follow(Vector2::new(10.0, 10.0));
+
+		// Clamped horizontally, and centered vertically as the bounds are smaller than the view
+		assert_near(Vector2::new(50.0, 25.0), camera.position);
+	}
+}
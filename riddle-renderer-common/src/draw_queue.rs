@@ -0,0 +1,217 @@
+use crate::{vertex::Vertex, BlendMode};
+use riddle_math::Rect;
+
+/// A draw recorded while a render context is in sorted mode, see
+/// [`RenderContext::begin_sorted`](crate::RenderContext::begin_sorted).
+///
+/// Intended for use by renderer implementations.
+pub struct QueuedDraw<T, S> {
+	pub texture: T,
+	pub shader: S,
+	pub blend_mode: BlendMode,
+
+	/// The draw's vertices, with the world transform active at the time of the draw applied.
+	pub verts: Vec<Vertex>,
+	pub indices: Vec<u16>,
+
+	/// The clip rect active at the time of the draw.
+	pub clip: Option<Rect<f32>>,
+
+	layer: i32,
+	sort_key: f32,
+	group: usize,
+}
+
+/// The draws recorded while a render context is in sorted mode, which are drawn ordered by
+/// layer and sort key once the sorted draws are submitted.
+///
+/// Draws with the same layer and sort key are grouped by their render state, in the order
+/// each state was first seen, so that renderers can draw them with fewer state changes. Other
+/// than that, draws keep the order they were recorded in.
+///
+/// Intended for use by renderer implementations.
+pub struct DrawQueue<T, S> {
+	draws: Vec<QueuedDraw<T, S>>,
+	group_heads: Vec<usize>,
+	active: bool,
+	layer: i32,
+	sort_key: f32,
+}
+
+impl<T, S> DrawQueue<T, S> {
+	pub fn new() -> Self {
+		Self {
+			draws: vec![],
+			group_heads: vec![],
+			active: false,
+			layer: 0,
+			sort_key: 0.0,
+		}
+	}
+
+	/// Whether draws should be recorded in to the queue rather than drawn immediately.
+	pub fn is_active(&self) -> bool {
+		self.active
+	}
+
+	/// Start recording draws, with the draw order reset to layer 0 and sort key 0.
+	pub fn begin(&mut self) {
+		self.active = true;
+		self.layer = 0;
+		self.sort_key = 0.0;
+	}
+
+	/// Stop recording draws. Any draws still in the queue should be taken with
+	/// [`DrawQueue::take_sorted`] first.
+	pub fn end(&mut self) {
+		self.active = false;
+	}
+
+	/// Set the layer and sort key of subsequently recorded draws.
+	pub fn set_order(&mut self, layer: i32, sort_key: f32) {
+		self.layer = layer;
+		self.sort_key = sort_key;
+	}
+
+	/// Record a draw, transforming its vertices by the world transform.
+	///
+	/// `same_group` is used to determine whether two draws share the same render state.
+	#[allow(clippy::too_many_arguments)]
+	pub fn push<F>(
+		&mut self,
+		texture: T,
+		shader: S,
+		blend_mode: BlendMode,
+		verts: &[Vertex],
+		indices: &[u16],
+		transform: glam::Mat4,
+		clip: Option<Rect<f32>>,
+		same_group: F,
+	) where
+		F: Fn(&QueuedDraw<T, S>, &QueuedDraw<T, S>) -> bool,
+	{
+		let verts = verts
+			.iter()
+			.map(|vertex| {
+				let pos = transform.transform_point3(glam::vec3(vertex.pos[0], vertex.pos[1], 0.0));
+				Vertex {
+					pos: [pos.x, pos.y],
+					..*vertex
+				}
+			})
+			.collect();
+
+		let mut draw = QueuedDraw {
+			texture,
+			shader,
+			blend_mode,
+			verts,
+			indices: indices.to_vec(),
+			clip,
+			layer: self.layer,
+			sort_key: self.sort_key,
+			group: 0,
+		};
+
+		let draws = &self.draws;
+		draw.group = match self
+			.group_heads
+			.iter()
+			.position(|head| same_group(&draws[*head], &draw))
+		{
+			Some(group) => group,
+			None => {
+				self.group_heads.push(self.draws.len());
+				self.group_heads.len() - 1
+			}
+		};
+
+		self.draws.push(draw);
+	}
+
+	/// Take all the recorded draws, in the order they should be drawn.
+	pub fn take_sorted(&mut self) -> Vec<QueuedDraw<T, S>> {
+		self.group_heads.clear();
+		let mut draws = std::mem::take(&mut self.draws);
+		draws.sort_by(|a, b| {
+			a.layer
+				.cmp(&b.layer)
+				.then(a.sort_key.total_cmp(&b.sort_key))
+				.then(a.group.cmp(&b.group))
+		});
+		draws
+	}
+}
+
+impl<T, S> Default for DrawQueue<T, S> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn push(queue: &mut DrawQueue<u32, ()>, texture: u32, layer: i32, sort_key: f32) {
+		queue.set_order(layer, sort_key);
+		queue.push(
+			texture,
+			(),
+			BlendMode::Alpha,
+			&[],
+			&[],
+			glam::Mat4::IDENTITY,
+			None,
+			|a, b| a.texture == b.texture,
+		);
+	}
+
+	fn textures(draws: &[QueuedDraw<u32, ()>]) -> Vec<u32> {
+		draws.iter().map(|draw| draw.texture).collect()
+	}
+
+	#[test]
+	fn draws_sorted_by_layer_then_key() {
+		let mut queue = DrawQueue::new();
+		queue.begin();
+		push(&mut queue, 0, 1, 0.0);
+		push(&mut queue, 1, 0, 5.0);
+		push(&mut queue, 2, 0, -1.0);
+		push(&mut queue, 3, 1, -10.0);
+
+		assert_eq!(vec![2, 1, 3, 0], textures(&queue.take_sorted()));
+	}
+
+	#[test]
+	fn equal_keys_grouped_by_state_in_first_seen_order() {
+		let mut queue = DrawQueue::new();
+		queue.begin();
+		push(&mut queue, 7, 0, 0.0);
+		push(&mut queue, 3, 0, 0.0);
+		push(&mut queue, 7, 0, 0.0);
+		push(&mut queue, 3, 0, 0.0);
+		push(&mut queue, 3, 0, 1.0);
+		push(&mut queue, 7, 0, 1.0);
+
+		assert_eq!(vec![7, 7, 3, 3, 7, 3], textures(&queue.take_sorted()));
+	}
+
+	#[test]
+	fn vertices_transformed_when_recorded() {
+		let mut queue: DrawQueue<(), ()> = DrawQueue::new();
+		queue.begin();
+		queue.push(
+			(),
+			(),
+			BlendMode::Alpha,
+			&[Vertex::ptc([1.0, 2.0], [0.0, 0.0], &[1.0; 4])],
+			&[0],
+			glam::Mat4::from_translation(glam::vec3(10.0, 20.0, 0.0)),
+			None,
+			|_, _| true,
+		);
+
+		assert_eq!([11.0, 22.0], queue.take_sorted()[0].verts[0].pos);
+	}
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SpriteSheetError {
+	#[error("Failed to parse sprite sheet JSON: {0}")]
+	Json(#[from] serde_json::Error),
+
+	#[error("Invalid sprite sheet: {0}")]
+	InvalidData(&'static str),
+
+	#[error("Sprite sheet animation refers to unknown frame: {0}")]
+	UnknownFrame(String),
+
+	#[error("Unknown animation tag: {0}")]
+	UnknownTag(String),
+}
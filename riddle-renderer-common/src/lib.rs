@@ -2,14 +2,31 @@
 //! secondary libraries to be defined in terms of the traits and structs defined in this crate
 //! without needing to encode knowledge of any specific renderers.
 
+mod animated_sprite;
+mod camera;
+mod draw_queue;
+mod error;
+mod nine_slice;
+mod particles;
 mod renderer;
 mod sprite;
 mod sprite_font;
+mod sprite_sheet;
+mod tessellation;
 pub mod vertex;
+mod virtual_resolution;
 
+pub use animated_sprite::*;
+pub use camera::*;
+pub use draw_queue::*;
+pub use error::*;
+pub use nine_slice::*;
+pub use particles::*;
 pub use renderer::*;
 pub use sprite::*;
 pub use sprite_font::*;
+pub use sprite_sheet::*;
+pub use virtual_resolution::*;
 
 use riddle_common::Color;
 use riddle_image::Image;
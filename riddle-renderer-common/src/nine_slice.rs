@@ -0,0 +1,344 @@
+use crate::*;
+
+use riddle_math::{Rect, Vector2};
+
+/// A sprite which can be rendered at any size while keeping its borders intact, for example
+/// UI panels and buttons.
+///
+/// The sprite is split in to nine regions by the border insets. The corners are always drawn
+/// at their original size, the edges are stretched or tiled along their length, and the center
+/// is stretched or tiled to fill the remaining space. If the target is smaller than the borders,
+/// the borders are scaled down to fit.
+///
+/// All nine regions are drawn with a single [`Renderable`], unless tiling produces more than
+/// [`MAX_QUADS_PER_RENDERABLE`] parts.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let img = Image::new(48, 48);
+/// let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new())?;
+///
+/// // A panel with 16 texel borders, with its center tiled rather than stretched
+/// let panel = NineSliceSprite::new(sprite, NineSliceInsets::uniform(16.0))
+///     .with_center_mode(NineSliceMode::Tile);
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     panel.render_rect(render_ctx, &Rect::new(vec2(10.0, 10.0), vec2(200.0, 120.0)))
+/// })?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct NineSliceSprite<S> {
+	sprite: S,
+	insets: NineSliceInsets,
+	edge_mode: NineSliceMode,
+	center_mode: NineSliceMode,
+}
+
+impl<S> NineSliceSprite<S> {
+	/// Build a nine slice sprite from a sprite and the size of its borders, in texels.
+	///
+	/// The edges and center are stretched by default.
+	pub fn new(sprite: S, insets: NineSliceInsets) -> Self {
+		Self {
+			sprite,
+			insets,
+			edge_mode: NineSliceMode::Stretch,
+			center_mode: NineSliceMode::Stretch,
+		}
+	}
+
+	/// Set how the edges are scaled along their length.
+	pub fn with_edge_mode(mut self, mode: NineSliceMode) -> Self {
+		self.edge_mode = mode;
+		self
+	}
+
+	/// Set how the center is scaled.
+	pub fn with_center_mode(mut self, mode: NineSliceMode) -> Self {
+		self.center_mode = mode;
+		self
+	}
+
+	/// The sprite the nine slice sprite was built from.
+	pub fn sprite(&self) -> &S {
+		&self.sprite
+	}
+
+	/// The size of the sprite's borders.
+	pub fn insets(&self) -> &NineSliceInsets {
+		&self.insets
+	}
+
+	/// Render the sprite with the given dimensions.
+	///
+	/// The location, pivot, angle, scale, color and blend mode in the render args are applied
+	/// to the sprite as a whole.
+	pub fn render<R, Ctx>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		dimensions: Vector2<f32>,
+	) -> Result<(), R::Error>
+	where
+		R: CommonRenderer,
+		S: CommonSprite<R>,
+		Ctx: RenderContext<R> + ?Sized,
+	{
+		let parts = self.regions(self.sprite.dimensions(), dimensions);
+		self.sprite
+			.render_stretched_regions(render_ctx, args, &parts[..])
+	}
+
+	/// Render the sprite filling the given rect.
+	pub fn render_rect<R, Ctx>(
+		&self,
+		render_ctx: &mut Ctx,
+		rect: &Rect<f32>,
+	) -> Result<(), R::Error>
+	where
+		R: CommonRenderer,
+		S: CommonSprite<R>,
+		Ctx: RenderContext<R> + ?Sized,
+	{
+		self.render(
+			render_ctx,
+			&SpriteRenderArgs::new(rect.location),
+			rect.dimensions,
+		)
+	}
+
+	/// The pairs of source regions and destination rects which draw the sprite with the given
+	/// dimensions, in the form accepted by [`CommonSprite::render_stretched_regions`].
+	fn regions(
+		&self,
+		sprite_dimensions: Vector2<f32>,
+		dimensions: Vector2<f32>,
+	) -> Vec<(Rect<f32>, Rect<f32>)> {
+		let columns = slice_axis(
+			sprite_dimensions.x,
+			dimensions.x,
+			self.insets.left,
+			self.insets.right,
+		);
+		let rows = slice_axis(
+			sprite_dimensions.y,
+			dimensions.y,
+			self.insets.top,
+			self.insets.bottom,
+		);
+
+		let mut parts = vec![];
+		for (row_index, row) in rows.iter().enumerate() {
+			for (column_index, column) in columns.iter().enumerate() {
+				let (tile_x, tile_y) = match (column_index == 1, row_index == 1) {
+					(true, true) => {
+						let tile = self.center_mode == NineSliceMode::Tile;
+						(tile, tile)
+					}
+					(true, false) => (self.edge_mode == NineSliceMode::Tile, false),
+					(false, true) => (false, self.edge_mode == NineSliceMode::Tile),
+					(false, false) => (false, false),
+				};
+
+				for x in column.segments(tile_x) {
+					for y in row.segments(tile_y) {
+						parts.push((
+							Rect::new(
+								Vector2::new(x.src, y.src),
+								Vector2::new(x.src_len, y.src_len),
+							),
+							Rect::new(
+								Vector2::new(x.dest, y.dest),
+								Vector2::new(x.dest_len, y.dest_len),
+							),
+						));
+					}
+				}
+			}
+		}
+		parts
+	}
+}
+
+/// The size of each of a [`NineSliceSprite`]'s borders, in texels.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NineSliceInsets {
+	pub left: f32,
+	pub top: f32,
+	pub right: f32,
+	pub bottom: f32,
+}
+
+impl NineSliceInsets {
+	pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+		Self {
+			left,
+			top,
+			right,
+			bottom,
+		}
+	}
+
+	/// Insets with every border the same size.
+	pub fn uniform(inset: f32) -> Self {
+		Self::new(inset, inset, inset, inset)
+	}
+}
+
+/// How the edges or center of a [`NineSliceSprite`] fill their space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NineSliceMode {
+	/// Stretch the region to fill the space.
+	#[default]
+	Stretch,
+
+	/// Repeat the region at its original size to fill the space, cutting off the last repeat
+	/// at the end of the space.
+	Tile,
+}
+
+/// A span of a source sprite, and the span of the target it is drawn to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Span {
+	src: f32,
+	src_len: f32,
+	dest: f32,
+	dest_len: f32,
+}
+
+impl Span {
+	/// Split the span in to the spans which draw it, either stretched or tiled.
+	fn segments(&self, tile: bool) -> Vec<Span> {
+		if self.src_len <= 0.0 || self.dest_len <= 0.0 {
+			return vec![];
+		}
+
+		if !tile {
+			return vec![*self];
+		}
+
+		let mut segments = vec![];
+		let mut offset = 0.0;
+		while offset < self.dest_len {
+			let len = self.src_len.min(self.dest_len - offset);
+			segments.push(Span {
+				src: self.src,
+				src_len: len,
+				dest: self.dest + offset,
+				dest_len: len,
+			});
+			offset += self.src_len;
+		}
+		segments
+	}
+}
+
+/// Split one axis of the sprite and the target in to the start border, middle and end border
+/// spans. The borders are scaled down if they don't fit in the target.
+fn slice_axis(src_len: f32, dest_len: f32, start: f32, end: f32) -> [Span; 3] {
+	let border_scale = if start + end > dest_len && start + end > 0.0 {
+		dest_len / (start + end)
+	} else {
+		1.0
+	};
+	let dest_start = start * border_scale;
+	let dest_end = end * border_scale;
+
+	[
+		Span {
+			src: 0.0,
+			src_len: start,
+			dest: 0.0,
+			dest_len: dest_start,
+		},
+		Span {
+			src: start,
+			src_len: src_len - start - end,
+			dest: dest_start,
+			dest_len: dest_len - dest_start - dest_end,
+		},
+		Span {
+			src: src_len - end,
+			src_len: end,
+			dest: dest_len - dest_end,
+			dest_len: dest_end,
+		},
+	]
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect<f32> {
+		Rect::new(Vector2::new(x, y), Vector2::new(w, h))
+	}
+
+	#[test]
+	fn stretch_keeps_corners_and_fills_target() {
+		let nine_slice = NineSliceSprite::new((), NineSliceInsets::uniform(4.0));
+		let parts = nine_slice.regions(Vector2::new(12.0, 12.0), Vector2::new(100.0, 50.0));
+
+		assert_eq!(9, parts.len());
+		assert_eq!(
+			(rect(0.0, 0.0, 4.0, 4.0), rect(0.0, 0.0, 4.0, 4.0)),
+			parts[0]
+		);
+		assert_eq!(
+			(rect(4.0, 0.0, 4.0, 4.0), rect(4.0, 0.0, 92.0, 4.0)),
+			parts[1]
+		);
+		assert_eq!(
+			(rect(4.0, 4.0, 4.0, 4.0), rect(4.0, 4.0, 92.0, 42.0)),
+			parts[4]
+		);
+		assert_eq!(
+			(rect(8.0, 8.0, 4.0, 4.0), rect(96.0, 46.0, 4.0, 4.0)),
+			parts[8]
+		);
+	}
+
+	#[test]
+	fn tiled_edges_repeat_and_crop() {
+		let nine_slice = NineSliceSprite::new((), NineSliceInsets::new(0.0, 2.0, 0.0, 2.0))
+			.with_edge_mode(NineSliceMode::Tile);
+		let parts = nine_slice.regions(Vector2::new(4.0, 8.0), Vector2::new(10.0, 8.0));
+
+		// The top edge is tiled as 4 + 4 + 2 texels
+		let top_edge: Vec<_> = parts
+			.iter()
+			.filter(|(_, dest)| dest.location.y == 0.0)
+			.cloned()
+			.collect();
+		assert_eq!(
+			vec![
+				(rect(0.0, 0.0, 4.0, 2.0), rect(0.0, 0.0, 4.0, 2.0)),
+				(rect(0.0, 0.0, 4.0, 2.0), rect(4.0, 0.0, 4.0, 2.0)),
+				(rect(0.0, 0.0, 2.0, 2.0), rect(8.0, 0.0, 2.0, 2.0)),
+			],
+			top_edge
+		);
+	}
+
+	#[test]
+	fn borders_shrink_to_fit_target() {
+		let nine_slice = NineSliceSprite::new((), NineSliceInsets::new(6.0, 0.0, 2.0, 0.0));
+		let parts = nine_slice.regions(Vector2::new(10.0, 4.0), Vector2::new(4.0, 4.0));
+
+		assert_eq!(
+			vec![
+				(rect(0.0, 0.0, 6.0, 4.0), rect(0.0, 0.0, 3.0, 4.0)),
+				(rect(8.0, 0.0, 2.0, 4.0), rect(3.0, 0.0, 1.0, 4.0)),
+			],
+			parts
+		);
+	}
+}
@@ -0,0 +1,608 @@
+use crate::*;
+
+use riddle_math::{Rect, Vector2};
+use riddle_time::TimeSystem;
+use std::time::Duration;
+
+/// The most particles a single emitter can hold, so that they can be drawn with a single
+/// [`Renderable`].
+pub const MAX_PARTICLES: usize = (u16::MAX as usize + 1) / 4;
+
+/// Emits, simulates and draws particles using regions of a sprite.
+///
+/// Particles are simulated on the CPU. Given the same seed, settings and sequence of updates,
+/// an emitter always produces the same particles, so effects can be tested without a renderer.
+/// Particles are simulated in world space, so moving the emitter doesn't move particles which
+/// have already been emitted.
+///
+/// All of an emitter's particles are drawn with a single [`Renderable`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let img = Image::new(16, 16);
+/// let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new())?;
+///
+/// // Sparks which fall and fade out
+/// let mut sparks = ParticleEmitter::new(sprite, 1234)
+///     .with_shape(EmissionShape::Circle { radius: 4.0 })
+///     .with_emission_rate(200.0)
+///     .with_lifetime(0.5, 1.0)
+///     .with_direction(-std::f32::consts::FRAC_PI_2, 0.5)
+///     .with_speed(100.0, 200.0)
+///     .with_gravity(vec2(0.0, 300.0))
+///     .with_color_over_lifetime(vec![Color::WHITE, Color::rgba(1.0, 0.5, 0.0, 0.0)])
+///     .with_blend_mode(BlendMode::Additive);
+/// sparks.set_location(vec2(100.0, 100.0));
+///
+/// // Once per frame
+/// sparks.update_with_time(&rdl.state().time());
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     sparks.render(render_ctx)
+/// })?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct ParticleEmitter<S> {
+	sprite: S,
+
+	shape: EmissionShape,
+	emission_rate: f32,
+	lifetime: (f32, f32),
+	direction: f32,
+	spread: f32,
+	speed: (f32, f32),
+	angle: (f32, f32),
+	angular_velocity: (f32, f32),
+	gravity: Vector2<f32>,
+	damping: f32,
+	color_over_lifetime: Vec<Color<f32>>,
+	scale_over_lifetime: Vec<f32>,
+	frames: Vec<Rect<f32>>,
+	frame_mode: ParticleFrameMode,
+	blend_mode: BlendMode,
+	max_particles: usize,
+
+	location: Vector2<f32>,
+	emitting: bool,
+	emit_accumulator: f32,
+	rng: ParticleRng,
+	particles: Vec<Particle>,
+}
+
+impl<S> ParticleEmitter<S> {
+	/// Build an emitter which draws particles using a sprite, with a seed for the random values
+	/// used when emitting particles.
+	///
+	/// By default the emitter emits 10 white particles a second from a point, each living for
+	/// 1 second and moving right at 50 units per second.
+	pub fn new(sprite: S, seed: u64) -> Self {
+		Self {
+			sprite,
+			shape: EmissionShape::Point,
+			emission_rate: 10.0,
+			lifetime: (1.0, 1.0),
+			direction: 0.0,
+			spread: 0.0,
+			speed: (50.0, 50.0),
+			angle: (0.0, 0.0),
+			angular_velocity: (0.0, 0.0),
+			gravity: Vector2::new(0.0, 0.0),
+			damping: 0.0,
+			color_over_lifetime: vec![Color::WHITE],
+			scale_over_lifetime: vec![1.0],
+			frames: vec![],
+			frame_mode: ParticleFrameMode::Random,
+			blend_mode: BlendMode::Alpha,
+			max_particles: 1000,
+
+			location: Vector2::new(0.0, 0.0),
+			emitting: true,
+			emit_accumulator: 0.0,
+			rng: ParticleRng::new(seed),
+			particles: vec![],
+		}
+	}
+
+	/// Set the area new particles are emitted from, relative to the emitter's location.
+	pub fn with_shape(mut self, shape: EmissionShape) -> Self {
+		self.shape = shape;
+		self
+	}
+
+	/// Set how many particles are emitted per second while the emitter is emitting.
+	pub fn with_emission_rate(mut self, particles_per_sec: f32) -> Self {
+		self.emission_rate = particles_per_sec.max(0.0);
+		self
+	}
+
+	/// Set the range of lifetimes of new particles, in seconds.
+	pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+		self.lifetime = (min, max);
+		self
+	}
+
+	/// Set the direction new particles move in, in radians, and the angle either side of the
+	/// direction they may be randomly rotated by.
+	pub fn with_direction(mut self, direction: f32, spread: f32) -> Self {
+		self.direction = direction;
+		self.spread = spread;
+		self
+	}
+
+	/// Set the range of speeds of new particles, in units per second.
+	pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+		self.speed = (min, max);
+		self
+	}
+
+	/// Set the range of initial angles of new particles, in radians.
+	pub fn with_angle(mut self, min: f32, max: f32) -> Self {
+		self.angle = (min, max);
+		self
+	}
+
+	/// Set the range of angular velocities of new particles, in radians per second.
+	pub fn with_angular_velocity(mut self, min: f32, max: f32) -> Self {
+		self.angular_velocity = (min, max);
+		self
+	}
+
+	/// Set the acceleration applied to all particles, in units per second per second.
+	pub fn with_gravity(mut self, gravity: Vector2<f32>) -> Self {
+		self.gravity = gravity;
+		self
+	}
+
+	/// Set how quickly particles slow down. A particle's velocity is scaled by
+	/// `e^(-damping * t)` over `t` seconds.
+	pub fn with_damping(mut self, damping: f32) -> Self {
+		self.damping = damping.max(0.0);
+		self
+	}
+
+	/// Set the colors particles fade between over their lifetime. The colors are spread evenly
+	/// across the lifetime, the first at birth and the last at death.
+	pub fn with_color_over_lifetime(mut self, colors: Vec<Color<f32>>) -> Self {
+		self.color_over_lifetime = colors;
+		self
+	}
+
+	/// Set the scales particles change between over their lifetime. The scales are spread
+	/// evenly across the lifetime, the first at birth and the last at death.
+	pub fn with_scale_over_lifetime(mut self, scales: Vec<f32>) -> Self {
+		self.scale_over_lifetime = scales;
+		self
+	}
+
+	/// Draw particles using regions of the sprite, in texels, instead of the whole sprite.
+	pub fn with_frames(mut self, frames: Vec<Rect<f32>>, mode: ParticleFrameMode) -> Self {
+		self.frames = frames;
+		self.frame_mode = mode;
+		self
+	}
+
+	/// Set the blend mode particles are drawn with.
+	pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+		self.blend_mode = blend_mode;
+		self
+	}
+
+	/// Set the most particles which can be alive at once. Particles aren't emitted while the
+	/// emitter is full. Limited to [`MAX_PARTICLES`].
+	pub fn with_max_particles(mut self, max_particles: usize) -> Self {
+		self.max_particles = max_particles.min(MAX_PARTICLES);
+		self
+	}
+
+	/// The sprite particles are drawn with.
+	pub fn sprite(&self) -> &S {
+		&self.sprite
+	}
+
+	/// The location new particles are emitted around.
+	pub fn location(&self) -> Vector2<f32> {
+		self.location
+	}
+
+	/// Move the emitter. Particles which have already been emitted aren't moved.
+	pub fn set_location(&mut self, location: Vector2<f32>) {
+		self.location = location;
+	}
+
+	pub fn is_emitting(&self) -> bool {
+		self.emitting
+	}
+
+	/// Start or stop emitting particles at the emission rate. Particles which have already been
+	/// emitted continue to be simulated.
+	pub fn set_emitting(&mut self, emitting: bool) {
+		self.emitting = emitting;
+		if !emitting {
+			self.emit_accumulator = 0.0;
+		}
+	}
+
+	/// Immediately emit a number of particles, whether or not the emitter is emitting.
+	pub fn burst(&mut self, count: usize) {
+		for _ in 0..count {
+			if !self.emit() {
+				break;
+			}
+		}
+	}
+
+	/// The particles which are alive, oldest first.
+	pub fn particles(&self) -> &[Particle] {
+		&self.particles[..]
+	}
+
+	/// Remove all particles.
+	pub fn clear(&mut self) {
+		self.particles.clear();
+		self.emit_accumulator = 0.0;
+	}
+
+	/// Advance the simulation by the given time, removing expired particles and emitting new
+	/// ones.
+	pub fn update(&mut self, delta: Duration) {
+		let dt = delta.as_secs_f32();
+
+		for particle in &mut self.particles {
+			particle.age += dt;
+		}
+		self.particles
+			.retain(|particle| particle.age < particle.lifetime);
+
+		let gravity: glam::Vec2 = self.gravity.into();
+		let damping = (-self.damping * dt).exp();
+		for particle in &mut self.particles {
+			let mut velocity: glam::Vec2 = particle.velocity.into();
+			velocity = (velocity + (gravity * dt)) * damping;
+
+			particle.velocity = velocity.into();
+			particle.location = (glam::Vec2::from(particle.location) + (velocity * dt)).into();
+			particle.angle += particle.angular_velocity * dt;
+		}
+
+		if self.emitting {
+			self.emit_accumulator += self.emission_rate * dt;
+			while self.emit_accumulator >= 1.0 {
+				self.emit_accumulator -= 1.0;
+				if !self.emit() {
+					self.emit_accumulator = 0.0;
+				}
+			}
+		}
+
+		for index in 0..self.particles.len() {
+			self.apply_lifetime_curves(index);
+		}
+	}
+
+	/// Advance the simulation by the time system's frame delta. Call once per frame.
+	pub fn update_with_time(&mut self, time: &TimeSystem) {
+		self.update(Duration::from_secs_f32(time.delta_secs()));
+	}
+
+	/// Render all the particles with a single [`Renderable`].
+	pub fn render<R, Ctx>(&self, render_ctx: &mut Ctx) -> Result<(), R::Error>
+	where
+		R: CommonRenderer,
+		S: CommonSprite<R>,
+		Ctx: RenderContext<R> + ?Sized,
+	{
+		if self.particles.is_empty() {
+			return Ok(());
+		}
+
+		let sprite_rect = Rect::new(Vector2::new(0.0, 0.0), self.sprite.dimensions());
+		let quads: Vec<SpriteQuad> = self
+			.particles
+			.iter()
+			.map(|particle| {
+				let source_rect = self
+					.frames
+					.get(particle.frame)
+					.cloned()
+					.unwrap_or_else(|| sprite_rect.clone());
+				SpriteQuad {
+					pivot: source_rect.dimensions * 0.5,
+					source_rect,
+					location: particle.location,
+					scale: Vector2::new(particle.scale, particle.scale),
+					angle: particle.angle,
+					color: particle.color.clone(),
+				}
+			})
+			.collect();
+
+		self.sprite
+			.render_quads(render_ctx, self.blend_mode, &quads[..])
+	}
+
+	/// Emit a single particle, returning false if the emitter is full.
+	fn emit(&mut self) -> bool {
+		if self.particles.len() >= self.max_particles {
+			return false;
+		}
+
+		let rng = &mut self.rng;
+		let offset = self.shape.sample(rng);
+		let direction = self.direction + rng.range(-self.spread, self.spread);
+		let speed = rng.range(self.speed.0, self.speed.1);
+		let frame = match self.frame_mode {
+			ParticleFrameMode::Random if !self.frames.is_empty() => {
+				((rng.next_f32() * self.frames.len() as f32) as usize).min(self.frames.len() - 1)
+			}
+			_ => 0,
+		};
+
+		let particle = Particle {
+			location: self.location + offset,
+			velocity: Vector2::new(direction.cos() * speed, direction.sin() * speed),
+			angle: rng.range(self.angle.0, self.angle.1),
+			angular_velocity: rng.range(self.angular_velocity.0, self.angular_velocity.1),
+			age: 0.0,
+			lifetime: rng.range(self.lifetime.0, self.lifetime.1),
+			frame,
+			color: Color::WHITE,
+			scale: 1.0,
+		};
+		self.particles.push(particle);
+
+		let index = self.particles.len() - 1;
+		self.apply_lifetime_curves(index);
+		true
+	}
+
+	/// Update the color, scale and frame of a particle to match its age.
+	fn apply_lifetime_curves(&mut self, index: usize) {
+		let particle = &mut self.particles[index];
+		let t = if particle.lifetime > 0.0 {
+			(particle.age / particle.lifetime).min(1.0)
+		} else {
+			1.0
+		};
+
+		particle.color = sample_curve(&self.color_over_lifetime, t, Color::WHITE, |a, b, t| {
+			Color::rgba(
+				lerp(a.r, b.r, t),
+				lerp(a.g, b.g, t),
+				lerp(a.b, b.b, t),
+				lerp(a.a, b.a, t),
+			)
+		});
+		particle.scale = sample_curve(&self.scale_over_lifetime, t, 1.0, |a, b, t| lerp(*a, *b, t));
+
+		if self.frame_mode == ParticleFrameMode::OverLifetime && !self.frames.is_empty() {
+			particle.frame = ((t * self.frames.len() as f32) as usize).min(self.frames.len() - 1);
+		}
+	}
+}
+
+/// A single particle simulated by a [`ParticleEmitter`].
+#[derive(Clone, Debug)]
+pub struct Particle {
+	pub location: Vector2<f32>,
+
+	/// Units per second.
+	pub velocity: Vector2<f32>,
+
+	/// Radians.
+	pub angle: f32,
+
+	/// Radians per second.
+	pub angular_velocity: f32,
+
+	/// Seconds since the particle was emitted.
+	pub age: f32,
+
+	/// Seconds the particle lives for.
+	pub lifetime: f32,
+
+	/// The index of the emitter frame the particle is drawn with.
+	pub frame: usize,
+
+	/// The color from the emitter's color over lifetime.
+	pub color: Color<f32>,
+
+	/// The scale from the emitter's scale over lifetime.
+	pub scale: f32,
+}
+
+/// The area around a [`ParticleEmitter`]'s location which particles are emitted from.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub enum EmissionShape {
+	/// Emit all particles from the emitter's location.
+	#[default]
+	Point,
+
+	/// Emit particles anywhere within a circle centered on the emitter's location.
+	Circle { radius: f32 },
+
+	/// Emit particles on the edge of a circle centered on the emitter's location.
+	Ring { radius: f32 },
+
+	/// Emit particles anywhere within a rect centered on the emitter's location.
+	Rect { dimensions: Vector2<f32> },
+
+	/// Emit particles along a line from the emitter's location to an offset from it.
+	Line { offset: Vector2<f32> },
+}
+
+impl EmissionShape {
+	/// A random offset from the emitter's location within the shape.
+	fn sample(&self, rng: &mut ParticleRng) -> Vector2<f32> {
+		match self {
+			EmissionShape::Point => Vector2::new(0.0, 0.0),
+			EmissionShape::Circle { radius } => {
+				let angle = rng.range(0.0, std::f32::consts::PI * 2.0);
+				let distance = radius * rng.next_f32().sqrt();
+				Vector2::new(angle.cos() * distance, angle.sin() * distance)
+			}
+			EmissionShape::Ring { radius } => {
+				let angle = rng.range(0.0, std::f32::consts::PI * 2.0);
+				Vector2::new(angle.cos() * radius, angle.sin() * radius)
+			}
+			EmissionShape::Rect { dimensions } => Vector2::new(
+				rng.range(-0.5, 0.5) * dimensions.x,
+				rng.range(-0.5, 0.5) * dimensions.y,
+			),
+			EmissionShape::Line { offset } => *offset * rng.next_f32(),
+		}
+	}
+}
+
+/// How a [`ParticleEmitter`] picks which of its frames each particle is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticleFrameMode {
+	/// Each particle is drawn with a frame picked when it is emitted.
+	#[default]
+	Random,
+
+	/// Particles move through the frames in order over their lifetime.
+	OverLifetime,
+}
+
+/// A small, fast, seedable random number generator (SplitMix64), so that emitters produce the
+/// same particles on every platform.
+#[derive(Clone, Debug)]
+struct ParticleRng {
+	state: u64,
+}
+
+impl ParticleRng {
+	fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/// A value in the range [0, 1).
+	fn next_f32(&mut self) -> f32 {
+		(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+	}
+
+	fn range(&mut self, min: f32, max: f32) -> f32 {
+		min + ((max - min) * self.next_f32())
+	}
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+	a + ((b - a) * t)
+}
+
+/// Sample a list of values spread evenly over the range [0, 1].
+fn sample_curve<T: Clone, F: Fn(&T, &T, f32) -> T>(values: &[T], t: f32, default: T, f: F) -> T {
+	match values {
+		[] => default,
+		[value] => value.clone(),
+		_ => {
+			let position = t * (values.len() - 1) as f32;
+			let index = (position.floor() as usize).min(values.len() - 2);
+			f(&values[index], &values[index + 1], position - index as f32)
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn step(emitter: &mut ParticleEmitter<()>, steps: usize) {
+		for _ in 0..steps {
+			emitter.update(Duration::from_millis(100));
+		}
+	}
+
+	#[test]
+	fn same_seed_emits_same_particles() {
+		let build = |seed| {
+			ParticleEmitter::new((), seed)
+				.with_shape(EmissionShape::Circle { radius: 10.0 })
+				.with_direction(0.0, 1.0)
+				.with_speed(10.0, 20.0)
+				.with_emission_rate(50.0)
+		};
+
+		let mut a = build(7);
+		let mut b = build(7);
+		let mut c = build(8);
+		step(&mut a, 5);
+		step(&mut b, 5);
+		step(&mut c, 5);
+
+		let locations = |e: &ParticleEmitter<()>| -> Vec<Vector2<f32>> {
+			e.particles().iter().map(|p| p.location).collect()
+		};
+		assert_eq!(25, a.particles().len());
+		assert_eq!(locations(&a), locations(&b));
+		assert_ne!(locations(&a), locations(&c));
+	}
+
+	#[test]
+	fn particles_expire_and_respect_max() {
+		let mut emitter = ParticleEmitter::new((), 0)
+			.with_emission_rate(10.0)
+			.with_lifetime(0.25, 0.25)
+			.with_max_particles(100);
+
+		// One particle is emitted per step, and each lives for 2.5 steps
+		step(&mut emitter, 10);
+		assert_eq!(3, emitter.particles().len());
+
+		emitter.set_emitting(false);
+		emitter.burst(500);
+		assert_eq!(100, emitter.particles().len());
+	}
+
+	#[test]
+	fn gravity_and_damping_integrate_velocity() {
+		let mut emitter = ParticleEmitter::new((), 0)
+			.with_speed(0.0, 0.0)
+			.with_gravity(Vector2::new(0.0, 10.0))
+			.with_damping(1.0)
+			.with_lifetime(10.0, 10.0);
+		emitter.set_emitting(false);
+		emitter.burst(1);
+		emitter.update(Duration::from_secs(1));
+
+		let particle = &emitter.particles()[0];
+		let expected_velocity = 10.0 * (-1.0f32).exp();
+		assert!((particle.velocity.y - expected_velocity).abs() < 0.0001);
+		assert!((particle.location.y - expected_velocity).abs() < 0.0001);
+	}
+
+	#[test]
+	fn lifetime_curves_follow_age() {
+		let mut emitter = ParticleEmitter::new((), 0)
+			.with_lifetime(1.0, 1.0)
+			.with_color_over_lifetime(vec![Color::WHITE, Color::TRANSPARENT_BLACK])
+			.with_scale_over_lifetime(vec![1.0, 3.0, 2.0])
+			.with_frames(
+				vec![Rect::new(Vector2::new(0.0, 0.0), Vector2::new(8.0, 8.0)); 4],
+				ParticleFrameMode::OverLifetime,
+			);
+		emitter.set_emitting(false);
+		emitter.burst(1);
+		emitter.update(Duration::from_millis(750));
+
+		let particle = &emitter.particles()[0];
+		assert_eq!(Color::rgba(0.25, 0.25, 0.25, 0.25), particle.color);
+		assert_eq!(2.5, particle.scale);
+		assert_eq!(3, particle.frame);
+	}
+}
@@ -1,4 +1,4 @@
-use crate::{vertex::Vertex, CommonSprite};
+use crate::{tessellation, vertex::Vertex, Camera2D, CommonSprite, DistanceFieldParams};
 use riddle_common::Color;
 use riddle_image::ImageError;
 use riddle_math::{Rect, Vector2};
@@ -19,6 +19,14 @@ pub trait CommonRenderer: Sized {
 	fn render<R, F>(&self, f: F) -> Result<R, Self::Error>
 	where
 		F: FnOnce(&mut Self::RenderContext) -> Result<R, Self::Error>;
+
+	/// Build a shader which renders sprites whose alpha channel stores a distance field, such
+	/// as distance field [`crate::SpriteFont`]s. See [`DistanceFieldParams`] for how the
+	/// shader draws.
+	fn distance_field_shader(
+		&self,
+		params: &DistanceFieldParams,
+	) -> Result<Self::Shader, Self::Error>;
 }
 
 /// The context provided to render callbacks
@@ -26,22 +34,388 @@ pub trait RenderContext<R: CommonRenderer> {
 	/// Replace the current world transform.
 	fn set_transform(&mut self, transform: mint::ColumnMatrix4<f32>) -> Result<(), R::Error>;
 
+	/// Replace the current world transform with the camera's world to screen transform.
+	fn set_camera(&mut self, camera: &Camera2D) -> Result<(), R::Error> {
+		self.set_transform(camera.transform())
+	}
+
 	/// Fill the target with a flat color.
+	///
+	/// The clear isn't restricted by the current clip rect.
 	fn clear(&mut self, color: Color<f32>) -> Result<(), R::Error>;
 
+	/// Restrict drawing to a rect of the target, until the matching
+	/// [`RenderContext::pop_clip_rect`].
+	///
+	/// The rect is in target coordinates, and isn't affected by the current transform. When
+	/// clip rects are nested, drawing is restricted to the intersection of all of them.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// renderer.render(|render_ctx| {
+	///     render_ctx.clear(Color::BLACK)?;
+	///
+	///     // Only the 50x50 region of the rect within the clip rect will be drawn
+	///     render_ctx.push_clip_rect(&Rect::new(vec2(0.0, 0.0), vec2(50.0, 50.0)))?;
+	///     render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(100.0, 100.0)), Color::GREEN)?;
+	///     render_ctx.pop_clip_rect()
+	/// })?;
+	/// # Ok(()) }
+	/// ```
+	fn push_clip_rect(&mut self, rect: &Rect<f32>) -> Result<(), R::Error>;
+
+	/// Restore the clip rect which was active before the last [`RenderContext::push_clip_rect`].
+	///
+	/// Popping when no clip rect is active has no effect.
+	fn pop_clip_rect(&mut self) -> Result<(), R::Error>;
+
+	/// Clear the mask, and draw to the mask instead of the target until the next call to
+	/// [`RenderContext::set_mask_mode`].
+	///
+	/// While drawing to the mask, pixels with an alpha of at least 0.5 are added to the mask.
+	/// Renderables are drawn to the mask with their textures and colors, but custom shaders
+	/// aren't used.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// renderer.render(|render_ctx| {
+	///     render_ctx.clear(Color::BLACK)?;
+	///
+	///     // Build a circular mask
+	///     render_ctx.begin_mask()?;
+	///     render_ctx.fill_circle(vec2(50.0, 50.0), 25.0, Color::WHITE)?;
+	///
+	///     // Only the part of the rect within the circle will be drawn
+	///     render_ctx.set_mask_mode(MaskMode::Inside)?;
+	///     render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(100.0, 100.0)), Color::GREEN)?;
+	///
+	///     render_ctx.set_mask_mode(MaskMode::Disabled)
+	/// })?;
+	/// # Ok(()) }
+	/// ```
+	fn begin_mask(&mut self) -> Result<(), R::Error>;
+
+	/// Stop drawing to the mask, and restrict drawing using the current mask.
+	///
+	/// [`MaskMode::Disabled`] stops masking, leaving the mask unchanged so it can be enabled
+	/// again later in the render.
+	fn set_mask_mode(&mut self, mode: MaskMode) -> Result<(), R::Error>;
+
+	/// Start buffering draws, so that they are drawn ordered by layer and sort key rather than
+	/// in the order they were submitted. Use [`RenderContext::set_draw_order`] to set the layer
+	/// and sort key of subsequent draws.
+	///
+	/// The buffered draws are drawn by [`RenderContext::end_sorted`] or
+	/// [`RenderContext::present`]. Lower layers are drawn first, and within a layer lower sort
+	/// keys are drawn first. Draws with the same layer and sort key may be reordered so that
+	/// draws sharing a texture and shader are drawn together, but are otherwise drawn in the
+	/// order they were submitted.
+	///
+	/// Each draw uses the transform and clip rect which were active when it was submitted.
+	/// Mask state and clears are not recorded per draw. Calling [`RenderContext::clear`],
+	/// [`RenderContext::begin_mask`] or [`RenderContext::set_mask_mode`] draws the buffered draws
+	/// before the change takes effect, which splits the sorted sequence in two: draws submitted
+	/// after the call are sorted separately, and are always drawn over the draws submitted
+	/// before it, whatever their layer and sort key.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	/// let characters = vec![vec2(10.0, 40.0), vec2(20.0, 10.0), vec2(30.0, 25.0)];
+	///
+	/// renderer.render(|render_ctx| {
+	///     render_ctx.clear(Color::BLACK)?;
+	///     render_ctx.begin_sorted()?;
+	///
+	///     // The HUD is drawn over the world, even though it is submitted first
+	///     render_ctx.set_draw_order(1, 0.0)?;
+	///     render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(100.0, 10.0)), Color::WHITE)?;
+	///
+	///     // Characters lower down the screen are drawn in front
+	///     for location in &characters {
+	///         render_ctx.set_draw_order(0, location.y)?;
+	///         render_ctx.fill_rect(&Rect::new(*location, vec2(10.0, 20.0)), Color::GREEN)?;
+	///     }
+	///
+	///     render_ctx.end_sorted()
+	/// })?;
+	/// # Ok(()) }
+	/// ```
+	fn begin_sorted(&mut self) -> Result<(), R::Error>;
+
+	/// Set the layer and sort key of subsequent draws while buffering sorted draws.
+	///
+	/// Has no effect outside of [`RenderContext::begin_sorted`].
+	fn set_draw_order(&mut self, layer: i32, sort_key: f32) -> Result<(), R::Error>;
+
+	/// Draw all the draws buffered since [`RenderContext::begin_sorted`], and go back to drawing
+	/// in the order draws are submitted.
+	fn end_sorted(&mut self) -> Result<(), R::Error>;
+
 	/// Draw a `Renderable` to the target with the current world transform.
 	fn draw(&mut self, renderable: &Renderable<'_, R>) -> Result<(), R::Error>;
 
 	/// Draw a solid rect with the given color.
-	fn fill_rect(&mut self, rect: &Rect<f32>, color: Color<f32>) -> Result<(), R::Error>;
+	fn fill_rect(&mut self, rect: &Rect<f32>, color: Color<f32>) -> Result<(), R::Error> {
+		self.fill_rect_with_blend_mode(rect, color, BlendMode::Alpha)
+	}
+
+	/// Draw a solid rect with the given color, blending it with the target using the given
+	/// blend mode.
+	fn fill_rect_with_blend_mode(
+		&mut self,
+		rect: &Rect<f32>,
+		color: Color<f32>,
+		blend_mode: BlendMode,
+	) -> Result<(), R::Error> {
+		let geometry = tessellation::rect(rect, &color.into());
+		fill_tessellated(self, &geometry, blend_mode)
+	}
+
+	/// Draw untextured triangles with the current world transform. The vertex colors are used
+	/// as is, and the vertex uvs are ignored.
+	///
+	/// All the shape drawing functions are built on top of this.
+	fn fill_geometry(
+		&mut self,
+		verts: &[Vertex],
+		indices: &[u16],
+		blend_mode: BlendMode,
+	) -> Result<(), R::Error>;
+
+	/// Draw a line segment of the given thickness.
+	fn draw_line(
+		&mut self,
+		start: Vector2<f32>,
+		end: Vector2<f32>,
+		thickness: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let geometry = tessellation::line(start, end, thickness, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw the outline of a rect. The stroke is centered on the rect's edges.
+	fn stroke_rect(
+		&mut self,
+		rect: &Rect<f32>,
+		thickness: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points = tessellation::rect_points(rect);
+		let geometry = tessellation::closed_stroke(&points, thickness, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw a solid rect with rounded corners. The radius is clamped to half the rect's
+	/// smallest dimension.
+	fn fill_rounded_rect(
+		&mut self,
+		rect: &Rect<f32>,
+		radius: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points = tessellation::rounded_rect_points(rect, radius);
+		let geometry = tessellation::convex_polygon(&points, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw the outline of a rect with rounded corners.
+	fn stroke_rounded_rect(
+		&mut self,
+		rect: &Rect<f32>,
+		radius: f32,
+		thickness: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points = tessellation::rounded_rect_points(rect, radius);
+		let geometry = tessellation::closed_stroke(&points, thickness, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw a solid circle.
+	fn fill_circle(
+		&mut self,
+		center: Vector2<f32>,
+		radius: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points = tessellation::circle_points(center, radius);
+		let geometry = tessellation::convex_polygon(&points, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw the outline of a circle. The stroke is centered on the circle's radius.
+	fn stroke_circle(
+		&mut self,
+		center: Vector2<f32>,
+		radius: f32,
+		thickness: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points = tessellation::circle_points(center, radius);
+		let geometry = tessellation::closed_stroke(&points, thickness, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw a solid polygon. The polygon may be concave, but must not self intersect.
+	fn fill_polygon(&mut self, points: &[Vector2<f32>], color: Color<f32>) -> Result<(), R::Error> {
+		let points: Vec<glam::Vec2> = points.iter().map(|p| (*p).into()).collect();
+		let geometry = tessellation::polygon(&points, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
+
+	/// Draw the closed outline of a polygon.
+	fn stroke_polygon(
+		&mut self,
+		points: &[Vector2<f32>],
+		thickness: f32,
+		color: Color<f32>,
+	) -> Result<(), R::Error> {
+		let points: Vec<glam::Vec2> = points.iter().map(|p| (*p).into()).collect();
+		let geometry = tessellation::closed_stroke(&points, thickness, &color.into());
+		fill_tessellated(self, &geometry, BlendMode::Alpha)
+	}
 
 	/// Consume the context and present any outstanding draw calls.
 	fn present(self) -> Result<(), R::Error>;
 }
 
+/// Draw tessellated geometry, split in to parts which can each be indexed with `u16`s.
+fn fill_tessellated<R, Ctx>(
+	ctx: &mut Ctx,
+	geometry: &tessellation::Geometry,
+	blend_mode: BlendMode,
+) -> Result<(), R::Error>
+where
+	R: CommonRenderer,
+	Ctx: RenderContext<R> + ?Sized,
+{
+	for (verts, indices) in geometry.parts() {
+		ctx.fill_geometry(&verts, &indices, blend_mode)?;
+	}
+	Ok(())
+}
+
+/// Intersect a new clip rect with the currently active clip rect, if any, to get the clip rect
+/// which should be active after [`RenderContext::push_clip_rect`].
+///
+/// If the rects don't overlap the result has zero dimensions. Intended for use by renderer
+/// implementations.
+pub fn nested_clip_rect(current: Option<&Rect<f32>>, rect: &Rect<f32>) -> Rect<f32> {
+	match current {
+		Some(current) => current
+			.intersect(rect)
+			.unwrap_or_else(|| Rect::new(rect.location, Vector2::new(0.0, 0.0))),
+		None => rect.clone(),
+	}
+}
+
 pub struct Renderable<'a, R: CommonRenderer> {
 	pub texture: R::Texture,
 	pub shader: R::Shader,
 	pub verts: &'a [Vertex],
 	pub indices: &'a [u16],
+	pub blend_mode: BlendMode,
+}
+
+/// How drawing is restricted by the mask built with [`RenderContext::begin_mask`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MaskMode {
+	/// Drawing isn't affected by the mask.
+	#[default]
+	Disabled,
+
+	/// Only draw within the mask.
+	Inside,
+
+	/// Only draw outside of the mask.
+	Outside,
+}
+
+/// How source colors are combined with the colors already in the render target.
+///
+/// Unless stated otherwise, source colors are expected to have straight (not premultiplied)
+/// alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+	/// Standard alpha blending: `src * src.a + dst * (1 - src.a)`.
+	#[default]
+	Alpha,
+
+	/// Alpha blending for sources with premultiplied alpha: `src + dst * (1 - src.a)`.
+	PremultipliedAlpha,
+
+	/// Add the source to the target: `src * src.a + dst`.
+	Additive,
+
+	/// Multiply the target by the source: `src * dst`.
+	Multiply,
+
+	/// Inverse multiply, which brightens the target: `src + dst * (1 - src)`.
+	Screen,
+
+	/// Overwrite the target with the source, ignoring alpha.
+	Replace,
+}
+
+impl BlendMode {
+	/// All supported blend modes.
+	pub const ALL: [BlendMode; 6] = [
+		BlendMode::Alpha,
+		BlendMode::PremultipliedAlpha,
+		BlendMode::Additive,
+		BlendMode::Multiply,
+		BlendMode::Screen,
+		BlendMode::Replace,
+	];
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect<f32> {
+		Rect::new(Vector2::new(x, y), Vector2::new(w, h))
+	}
+
+	#[test]
+	fn nested_clip_rect_intersects_current() {
+		assert_eq!(
+			rect(10.0, 10.0, 20.0, 20.0),
+			nested_clip_rect(None, &rect(10.0, 10.0, 20.0, 20.0))
+		);
+		assert_eq!(
+			rect(10.0, 15.0, 10.0, 15.0),
+			nested_clip_rect(
+				Some(&rect(0.0, 15.0, 20.0, 40.0)),
+				&rect(10.0, 10.0, 20.0, 20.0)
+			)
+		);
+	}
+
+	#[test]
+	fn disjoint_nested_clip_rect_is_empty() {
+		let clip = nested_clip_rect(
+			Some(&rect(0.0, 0.0, 10.0, 10.0)),
+			&rect(20.0, 20.0, 5.0, 5.0),
+		);
+		assert_eq!(Vector2::new(0.0, 0.0), clip.dimensions);
+	}
 }
@@ -1,4 +1,6 @@
-use crate::*;
+use crate::{vertex::Vertex, *};
+
+use std::convert::TryFrom;
 
 /// Sprites are conceptually both a reference to an image, and the sub region of the image
 /// which represents the logical sprite.
@@ -77,6 +79,42 @@ pub trait CommonSprite<R: CommonRenderer>: Sized + Clone {
 		parts: &[(Rect<f32>, Vector2<f32>)],
 	) -> Result<(), R::Error>;
 
+	/// Render multiple sub regions of the sprite at once, using a custom shader.
+	///
+	/// See [`CommonSprite::render_regions`] for how the regions are interpreted.
+	fn render_regions_with_shader<Ctx: RenderContext<R> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		shader: &R::Shader,
+		parts: &[(Rect<f32>, Vector2<f32>)],
+	) -> Result<(), R::Error>;
+
+	/// Render multiple sub regions of the sprite at once, each stretched to fill a rect.
+	///
+	/// The regions are defined by pairs of the region of the sprite to draw in texels, and the
+	/// rect to draw the region in to relative to the [`SpriteRenderArgs::location`]. All the
+	/// regions are drawn with a single [`Renderable`].
+	///
+	/// The pivot and rotation are relative to the location arg, as with
+	/// [`CommonSprite::render_regions`].
+	fn render_stretched_regions<Ctx: RenderContext<R> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		parts: &[(Rect<f32>, Rect<f32>)],
+	) -> Result<(), R::Error>;
+
+	/// Render many regions of the sprite at once, each with its own transform and color.
+	///
+	/// All the quads are drawn with a single [`Renderable`], using the same blend mode.
+	fn render_quads<Ctx: RenderContext<R> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		blend_mode: BlendMode,
+		quads: &[SpriteQuad],
+	) -> Result<(), R::Error>;
+
 	/// Render the entire sprite.
 	fn render<Ctx: RenderContext<R> + ?Sized>(
 		&self,
@@ -93,6 +131,82 @@ pub trait CommonSprite<R: CommonRenderer>: Sized + Clone {
 		)
 	}
 
+	/// Render the entire sprite, using a custom shader.
+	fn render_with_shader<Ctx: RenderContext<R> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		shader: &R::Shader,
+	) -> Result<(), R::Error> {
+		self.render_regions_with_shader(
+			render_ctx,
+			args,
+			shader,
+			&[(
+				Rect::new([0.0, 0.0], self.dimensions().into()),
+				Vector2::new(0.0, 0.0),
+			)],
+		)
+	}
+
+	/// Render the sprite stretched over a rect, with the texture coordinates offset and scaled.
+	///
+	/// The uv offset and scale are in units of the sprite's dimensions. An offset of `(0.5, 0.0)`
+	/// starts drawing from the middle of the sprite, and a scale of `(4.0, 2.0)` draws the sprite
+	/// 4 times across and twice down the rect. Texture coordinates outside of the sprite
+	/// are resolved by the [`AddressMode`]s the sprite was created with, so only sprites which
+	/// cover their entire texture will repeat or mirror correctly.
+	///
+	/// The rect is relative to the [`SpriteRenderArgs::location`], and the pivot and rotation
+	/// are applied as with [`CommonSprite::render_stretched_regions`].
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// let img = Image::new(64, 64);
+	/// let stars = Sprite::new_from_image(
+	///     &renderer,
+	///     &img,
+	///     &SpriteInitArgs::new().with_address_modes(AddressMode::Repeat, AddressMode::Repeat),
+	/// )?;
+	///
+	/// // Once per frame, scroll the starfield. Wrapping the offset keeps it precise over time.
+	/// let mut scroll = 0.0;
+	/// scroll = (scroll + rdl.state().time().delta_secs() * 0.1) % 1.0;
+	///
+	/// renderer.render(|render_ctx| {
+	///     let screen = Rect::new(vec2(0.0, 0.0), vec2(800.0, 600.0));
+	///     stars.render_tiled(
+	///         render_ctx,
+	///         &SpriteRenderArgs::new(vec2(0.0, 0.0)),
+	///         &screen,
+	///         vec2(scroll, 0.0),
+	///         vec2(800.0 / 64.0, 600.0 / 64.0),
+	///     )
+	/// })?;
+	/// # Ok(()) }
+	/// ```
+	fn render_tiled<Ctx: RenderContext<R> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		rect: &Rect<f32>,
+		uv_offset: Vector2<f32>,
+		uv_scale: Vector2<f32>,
+	) -> Result<(), R::Error> {
+		let dimensions = self.dimensions();
+		let source = Rect::new(
+			Vector2::new(uv_offset.x * dimensions.x, uv_offset.y * dimensions.y),
+			Vector2::new(uv_scale.x * dimensions.x, uv_scale.y * dimensions.y),
+		);
+		self.render_stretched_regions(render_ctx, args, &[(source, rect.clone())])
+	}
+
 	/// Utility function to simply render the sprite at a given location
 	///
 	/// See [`SpriteRenderArgs`] for how to render the sprite with more control.
@@ -111,6 +225,226 @@ pub trait CommonSprite<R: CommonRenderer>: Sized + Clone {
 	}
 }
 
+/// Build the vertices and indices which render regions of a sprite, as described by
+/// [`CommonSprite::render_regions`].
+///
+/// Intended for use by renderer implementations.
+///
+/// # Arguments
+///
+/// * **texture_dimensions** - The dimensions of the texture the sprite references, in texels.
+/// * **source_rect** - The region of the texture the sprite represents.
+///
+/// # Panics
+///
+/// Panics if there are more than [`MAX_QUADS_PER_RENDERABLE`] parts.
+pub fn sprite_regions_geometry(
+	texture_dimensions: Vector2<f32>,
+	source_rect: &Rect<f32>,
+	args: &SpriteRenderArgs,
+	parts: &[(Rect<f32>, Vector2<f32>)],
+) -> (Vec<Vertex>, Vec<u16>) {
+	let parts: Vec<(Rect<f32>, Rect<f32>)> = parts
+		.iter()
+		.map(|(src_rect, location)| (src_rect.clone(), Rect::new(*location, src_rect.dimensions)))
+		.collect();
+	sprite_stretched_regions_geometry(texture_dimensions, source_rect, args, &parts[..])
+}
+
+/// Build the vertices and indices which render regions of a sprite, as described by
+/// [`CommonSprite::render_stretched_regions`].
+///
+/// Intended for use by renderer implementations.
+///
+/// # Arguments
+///
+/// * **texture_dimensions** - The dimensions of the texture the sprite references, in texels.
+/// * **source_rect** - The region of the texture the sprite represents.
+///
+/// # Panics
+///
+/// Panics if there are more than [`MAX_QUADS_PER_RENDERABLE`] parts.
+pub fn sprite_stretched_regions_geometry(
+	texture_dimensions: Vector2<f32>,
+	source_rect: &Rect<f32>,
+	args: &SpriteRenderArgs,
+	parts: &[(Rect<f32>, Rect<f32>)],
+) -> (Vec<Vertex>, Vec<u16>) {
+	let rot: glam::Mat2 = glam::Mat2::from_angle(args.angle);
+	let scale: glam::Mat2 = glam::Mat2::from_diagonal(args.scale.into());
+	let origin: glam::Vec2 = args.location.into();
+	let pivot: glam::Vec2 = args.pivot.into();
+
+	let Vector2::<f32> {
+		x: tex_width,
+		y: tex_height,
+	} = texture_dimensions;
+
+	let vertex_data: Vec<Vertex> = parts
+		.iter()
+		.flat_map(|(src_rect, dest_rect)| {
+			let location = glam::Vec2::from(dest_rect.location);
+			let src_rect = Rect::new(
+				source_rect.location + src_rect.location,
+				src_rect.dimensions,
+			);
+
+			let pos_topleft: glam::Vec2 = location - pivot;
+			let pos_topright: glam::Vec2 = pos_topleft + glam::vec2(dest_rect.dimensions.x, 0.0);
+			let pos_bottomleft: glam::Vec2 = pos_topleft + glam::vec2(0.0, dest_rect.dimensions.y);
+			let pos_bottomright: glam::Vec2 =
+				pos_bottomleft + glam::vec2(dest_rect.dimensions.x, 0.0);
+
+			let uv_top = src_rect.location.y / tex_height;
+			let uv_left = src_rect.location.x / tex_width;
+			let uv_bottom = uv_top + (src_rect.dimensions.y / tex_height);
+			let uv_right = uv_left + (src_rect.dimensions.x / tex_width);
+
+			let color_arr: [f32; 4] = args.diffuse_color.clone().into();
+
+			vec![
+				Vertex::ptc(
+					origin + (rot * (scale * pos_topleft)),
+					[uv_left, uv_top],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_bottomleft)),
+					[uv_left, uv_bottom],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_bottomright)),
+					[uv_right, uv_bottom],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_topright)),
+					[uv_right, uv_top],
+					&color_arr,
+				),
+			]
+		})
+		.collect();
+
+	(vertex_data, quad_indices(parts.len()))
+}
+
+/// Build the vertices and indices which render independently transformed quads, as described
+/// by [`CommonSprite::render_quads`].
+///
+/// Intended for use by renderer implementations.
+///
+/// # Arguments
+///
+/// * **texture_dimensions** - The dimensions of the texture the sprite references, in texels.
+/// * **source_rect** - The region of the texture the sprite represents.
+///
+/// # Panics
+///
+/// Panics if there are more than [`MAX_QUADS_PER_RENDERABLE`] parts.
+pub fn sprite_quads_geometry(
+	texture_dimensions: Vector2<f32>,
+	source_rect: &Rect<f32>,
+	quads: &[SpriteQuad],
+) -> (Vec<Vertex>, Vec<u16>) {
+	let Vector2::<f32> {
+		x: tex_width,
+		y: tex_height,
+	} = texture_dimensions;
+
+	let vertex_data: Vec<Vertex> = quads
+		.iter()
+		.flat_map(|quad| {
+			let rot: glam::Mat2 = glam::Mat2::from_angle(quad.angle);
+			let scale: glam::Mat2 = glam::Mat2::from_diagonal(quad.scale.into());
+			let origin: glam::Vec2 = quad.location.into();
+			let pivot: glam::Vec2 = quad.pivot.into();
+			let dimensions = quad.source_rect.dimensions;
+
+			let pos_topleft: glam::Vec2 = -pivot;
+			let pos_topright: glam::Vec2 = pos_topleft + glam::vec2(dimensions.x, 0.0);
+			let pos_bottomleft: glam::Vec2 = pos_topleft + glam::vec2(0.0, dimensions.y);
+			let pos_bottomright: glam::Vec2 = pos_bottomleft + glam::vec2(dimensions.x, 0.0);
+
+			let src_location = source_rect.location + quad.source_rect.location;
+			let uv_top = src_location.y / tex_height;
+			let uv_left = src_location.x / tex_width;
+			let uv_bottom = uv_top + (dimensions.y / tex_height);
+			let uv_right = uv_left + (dimensions.x / tex_width);
+
+			let color_arr: [f32; 4] = quad.color.clone().into();
+
+			vec![
+				Vertex::ptc(
+					origin + (rot * (scale * pos_topleft)),
+					[uv_left, uv_top],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_bottomleft)),
+					[uv_left, uv_bottom],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_bottomright)),
+					[uv_right, uv_bottom],
+					&color_arr,
+				),
+				Vertex::ptc(
+					origin + (rot * (scale * pos_topright)),
+					[uv_right, uv_top],
+					&color_arr,
+				),
+			]
+		})
+		.collect();
+
+	(vertex_data, quad_indices(quads.len()))
+}
+
+/// The most quads which can be drawn by a single [`Renderable`], since its vertices are
+/// referenced by `u16` indices.
+///
+/// Renderer implementations should split larger sets of regions or quads in to multiple
+/// renderables, each built by [`sprite_stretched_regions_geometry`] or
+/// [`sprite_quads_geometry`].
+pub const MAX_QUADS_PER_RENDERABLE: usize = (u16::MAX as usize + 1) / 4;
+
+/// The indices for a sequence of quads, each made up of 4 consecutive vertices.
+fn quad_indices(count: usize) -> Vec<u16> {
+	(0..count)
+		.flat_map(|i| {
+			let base = u16::try_from(i)
+				.ok()
+				.and_then(|i| i.checked_mul(4))
+				.expect("more quads than MAX_QUADS_PER_RENDERABLE");
+			vec![1 + base, 2 + base, base, 2 + base, base, 3 + base]
+		})
+		.collect()
+}
+
+/// A region of a sprite drawn with its own transform and color, see
+/// [`CommonSprite::render_quads`].
+#[derive(Clone, Debug)]
+pub struct SpriteQuad {
+	/// The region of the sprite to draw, in texels.
+	pub source_rect: Rect<f32>,
+
+	/// Where the quad's pivot is placed.
+	pub location: Vector2<f32>,
+
+	/// The point the quad is scaled and rotated around, relative to the top left of the quad.
+	pub pivot: Vector2<f32>,
+	pub scale: Vector2<f32>,
+
+	/// The angle the quad is rotated by, in radians.
+	pub angle: f32,
+
+	/// The diffuse color of the quad, which will be multiplied by the sprite colors.
+	pub color: Color<f32>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterMode {
 	Nearest,
@@ -123,10 +457,54 @@ impl Default for FilterMode {
 	}
 }
 
+/// How texture coordinates outside of a texture are resolved when sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressMode {
+	/// Use the color of the nearest texel on the texture's edge.
+	#[default]
+	ClampToEdge,
+
+	/// Repeat the texture.
+	Repeat,
+
+	/// Repeat the texture, flipping it on every other repeat.
+	MirrorRepeat,
+}
+
+/// The color space a sprite's image is encoded in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+	/// The image holds colors, as authored in image editors. Renderers which blend in linear
+	/// space decode the colors to linear space when sampling.
+	#[default]
+	Srgb,
+
+	/// The image holds data which is used as is, such as lookup tables or distance fields.
+	Linear,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct SpriteInitArgs {
 	pub mag_filter: FilterMode,
 	pub min_filter: FilterMode,
+
+	/// Whether a chain of mip levels is generated from the image when the sprite is created.
+	/// Mipmaps avoid shimmering when sprites are drawn scaled down, at the cost of a third more
+	/// texture memory.
+	pub mipmaps: bool,
+
+	/// How the two nearest mip levels are combined when sampling. Linear filtering, along with
+	/// a linear min filter, gives trilinear filtering. Ignored without [`SpriteInitArgs::mipmaps`].
+	pub mipmap_filter: FilterMode,
+
+	/// How horizontal texture coordinates outside of the texture are resolved.
+	pub address_mode_u: AddressMode,
+
+	/// How vertical texture coordinates outside of the texture are resolved.
+	pub address_mode_v: AddressMode,
+
+	/// The color space the image is encoded in.
+	pub color_space: ColorSpace,
 }
 
 impl SpriteInitArgs {
@@ -135,6 +513,11 @@ impl SpriteInitArgs {
 		Self {
 			mag_filter: Default::default(),
 			min_filter: Default::default(),
+			mipmaps: false,
+			mipmap_filter: Default::default(),
+			address_mode_u: Default::default(),
+			address_mode_v: Default::default(),
+			color_space: Default::default(),
 		}
 	}
 
@@ -144,6 +527,57 @@ impl SpriteInitArgs {
 		self.min_filter = min_filter;
 		self
 	}
+
+	/// Generate mipmaps for the sprite, and specify the filter used between mip levels.
+	///
+	/// The mip levels are generated from the image when the sprite is created. Renderers don't
+	/// allow sprites with mipmaps to be updated after they are created.
+	///
+	/// Sprites packed in to an atlas shouldn't use mipmaps, since the smaller levels blend
+	/// neighbouring sprites together.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// // Trilinear filtering
+	/// let init_args = SpriteInitArgs::new()
+	///     .with_filter_modes(FilterMode::Linear, FilterMode::Linear)
+	///     .with_mipmaps(FilterMode::Linear);
+	///
+	/// let img = Image::new(256, 256);
+	/// let sprite = Sprite::new_from_image(&renderer, &img, &init_args)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn with_mipmaps(mut self, mipmap_filter: FilterMode) -> Self {
+		self.mipmaps = true;
+		self.mipmap_filter = mipmap_filter;
+		self
+	}
+
+	/// Specify how horizontal and vertical texture coordinates outside of the texture are
+	/// resolved. Use with [`CommonSprite::render_tiled`] to repeat a sprite across a rect.
+	pub fn with_address_modes(
+		mut self,
+		address_mode_u: AddressMode,
+		address_mode_v: AddressMode,
+	) -> Self {
+		self.address_mode_u = address_mode_u;
+		self.address_mode_v = address_mode_v;
+		self
+	}
+
+	/// Specify the color space the image is encoded in. Images holding data rather than
+	/// colors should use [`ColorSpace::Linear`], so they aren't decoded by renderers which
+	/// blend in linear space.
+	pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+		self.color_space = color_space;
+		self
+	}
 }
 
 #[derive(Clone, Debug)]
@@ -153,6 +587,7 @@ pub struct SpriteRenderArgs {
 	pub scale: Vector2<f32>,
 	pub angle: f32,
 	pub diffuse_color: Color<f32>,
+	pub blend_mode: BlendMode,
 }
 
 impl SpriteRenderArgs {
@@ -196,6 +631,12 @@ impl SpriteRenderArgs {
 		self.diffuse_color = color;
 		self
 	}
+
+	/// Set the blend mode used to combine the sprite with the render target.
+	pub fn with_blend_mode(&mut self, blend_mode: BlendMode) -> &mut Self {
+		self.blend_mode = blend_mode;
+		self
+	}
 }
 
 impl Default for SpriteRenderArgs {
@@ -206,6 +647,7 @@ impl Default for SpriteRenderArgs {
 			angle: 0.0,
 			scale: [1.0, 1.0].into(),
 			diffuse_color: Color::WHITE,
+			blend_mode: BlendMode::Alpha,
 		}
 	}
 }
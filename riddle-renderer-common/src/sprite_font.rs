@@ -39,30 +39,107 @@ use riddle_math::{Rect, SpacialNumericConversion, Vector2};
 pub struct SpriteFont<R: CommonRenderer> {
 	sprite: R::Sprite,
 	font: ImgFont,
+	distance_field: Option<DistanceFieldShaders<R>>,
 }
 
 impl<R: CommonRenderer> SpriteFont<R> {
 	/// Build a SpriteFont from the ImgFont given. The ImgFont's image will get loaded in to a
 	/// texture, and its glyph information will be used for layout.
+	///
+	/// Distance field fonts are rendered with the default [`DistanceFieldStyle`].
 	pub fn new(renderer: &R, font: ImgFont) -> Result<Self, R::Error> {
 		let sprite = R::Sprite::new_from_image(
 			renderer,
 			font.image(),
-			&SpriteInitArgs {
-				min_filter: FilterMode::Linear,
-				mag_filter: FilterMode::Linear,
-			},
+			&SpriteInitArgs::new().with_filter_modes(FilterMode::Linear, FilterMode::Linear),
 		)?;
 
-		Ok(Self { sprite, font })
+		Self::new_from_sprite(renderer, font, sprite)
 	}
 
 	/// Build a SpriteFont from the ImgFont given and a sprite which contains the ImgFont atlas.
 	///
 	/// This method is to allow the use of SpriteFonts when the font atlas is stored in a larger
 	/// atlas texture.
-	pub fn new_from_sprite(font: ImgFont, sprite: R::Sprite) -> Self {
-		Self { sprite, font }
+	pub fn new_from_sprite(
+		renderer: &R,
+		font: ImgFont,
+		sprite: R::Sprite,
+	) -> Result<Self, R::Error> {
+		let mut sprite_font = Self {
+			sprite,
+			font,
+			distance_field: None,
+		};
+		sprite_font.set_distance_field_style(renderer, DistanceFieldStyle::new())?;
+		Ok(sprite_font)
+	}
+
+	/// Set the outline, glow and shadow used to render a distance field font.
+	///
+	/// Has no effect for fonts which aren't distance fields.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, font::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// let font_bytes = include_bytes!("../../example_assets/Roboto-Regular.ttf");
+	/// let ttfont = TtFont::load(&font_bytes[..])?;
+	/// let img_font = ImgFontGenerator::new("abcdefghijklmnopqrstuvwxyz ", 48)
+	///     .distance_field(6)
+	///     .generate(&ttfont)?;
+	///
+	/// let mut sprite_font = SpriteFont::new(&renderer, img_font)?;
+	/// sprite_font.set_distance_field_style(
+	///     &renderer,
+	///     DistanceFieldStyle::new()
+	///         .with_outline(2.0, Color::BLACK)
+	///         .with_shadow(vec2(3.0, 3.0), Color::rgba(0.0, 0.0, 0.0, 0.5), 2.0),
+	/// )?;
+	///
+	/// // The same font renders crisply at any scale
+	/// renderer.render(|render_ctx| {
+	///     render_ctx.clear(Color::WHITE)?;
+	///     sprite_font.render(render_ctx, &SpriteRenderArgs::new([0.0, 0.0]), "small")?;
+	///     sprite_font.render(
+	///         render_ctx,
+	///         SpriteRenderArgs::new([0.0, 50.0]).with_scale([4.0, 4.0]),
+	///         "large",
+	///     )
+	/// })?;
+	/// # Ok(()) }
+	/// ```
+	pub fn set_distance_field_style(
+		&mut self,
+		renderer: &R,
+		style: DistanceFieldStyle,
+	) -> Result<(), R::Error> {
+		let spread = match self.font.distance_field_spread() {
+			Some(spread) => spread,
+			None => return Ok(()),
+		};
+
+		let text = renderer.distance_field_shader(&style.text_params(spread))?;
+		let shadow = if style.shadow_color.a > 0.0 {
+			Some(renderer.distance_field_shader(&style.shadow_params(spread))?)
+		} else {
+			None
+		};
+		self.distance_field = Some(DistanceFieldShaders {
+			style,
+			text,
+			shadow,
+		});
+		Ok(())
+	}
+
+	/// The style used to render the font, if it is a distance field font.
+	pub fn distance_field_style(&self) -> Option<&DistanceFieldStyle> {
+		self.distance_field.as_ref().map(|shaders| &shaders.style)
 	}
 
 	pub fn render<Ctx: RenderContext<R> + ?Sized>(
@@ -77,7 +154,182 @@ impl<R: CommonRenderer> SpriteFont<R> {
 				parts.push((rect.clone().convert(), location.convert()));
 			}
 		});
-		self.sprite
-			.render_regions(render_ctx, render_args, &parts[..])
+
+		match &self.distance_field {
+			Some(shaders) => {
+				if let Some(shadow_shader) = &shaders.shadow {
+					let style = &shaders.style;
+					let mut shadow_color = style.shadow_color.clone();
+					shadow_color.a *= render_args.diffuse_color.a;
+
+					// The offset is in font pixels, so it follows the text's scale and angle
+					let offset = glam::Mat2::from_angle(render_args.angle)
+						* (glam::Vec2::from(render_args.scale)
+							* glam::Vec2::from(style.shadow_offset));
+					let mut shadow_args = render_args.clone();
+					shadow_args
+						.at(render_args.location + Vector2::from(offset))
+						.with_color(shadow_color);
+					self.sprite.render_regions_with_shader(
+						render_ctx,
+						&shadow_args,
+						shadow_shader,
+						&parts[..],
+					)?;
+				}
+
+				self.sprite.render_regions_with_shader(
+					render_ctx,
+					render_args,
+					&shaders.text,
+					&parts[..],
+				)
+			}
+			None => self
+				.sprite
+				.render_regions(render_ctx, render_args, &parts[..]),
+		}
+	}
+}
+
+struct DistanceFieldShaders<R: CommonRenderer> {
+	style: DistanceFieldStyle,
+	text: R::Shader,
+	shadow: Option<R::Shader>,
+}
+
+/// How a distance field [`SpriteFont`] is drawn. The text itself is drawn with the
+/// [`SpriteRenderArgs::diffuse_color`].
+///
+/// Widths and offsets are in pixels of the font at its generated size, so they scale with the
+/// text. The shadow offset also rotates with the text.
+/// The outline and glow together, and the shadow softness, should be no wider than the font's
+/// [`riddle_font::ImgFont::distance_field_spread`].
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_common::*; use riddle_common::Color; use riddle_math::*;
+/// let style = DistanceFieldStyle::new()
+///     .with_outline(1.5, Color::BLACK)
+///     .with_glow(3.0, Color::rgba(1.0, 0.8, 0.0, 0.75));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceFieldStyle {
+	pub outline_width: f32,
+	pub outline_color: Color<f32>,
+
+	/// The width of the glow beyond the outline. The glow fades out over its width.
+	pub glow_width: f32,
+	pub glow_color: Color<f32>,
+
+	/// The offset of the shadow, in pixels of the font at its generated size.
+	pub shadow_offset: Vector2<f32>,
+
+	/// The color of the shadow. No shadow is drawn if the color is fully transparent.
+	pub shadow_color: Color<f32>,
+
+	/// The width of the shadow's blurred edge.
+	pub shadow_softness: f32,
+}
+
+impl DistanceFieldStyle {
+	/// A style with no outline, glow or shadow.
+	pub fn new() -> Self {
+		Self {
+			outline_width: 0.0,
+			outline_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+			glow_width: 0.0,
+			glow_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+			shadow_offset: Vector2::new(0.0, 0.0),
+			shadow_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+			shadow_softness: 0.0,
+		}
+	}
+
+	/// Draw an outline around the text.
+	pub fn with_outline(mut self, width: f32, color: Color<f32>) -> Self {
+		self.outline_width = width;
+		self.outline_color = color;
+		self
+	}
+
+	/// Draw a glow around the text, beyond any outline.
+	pub fn with_glow(mut self, width: f32, color: Color<f32>) -> Self {
+		self.glow_width = width;
+		self.glow_color = color;
+		self
+	}
+
+	/// Draw a drop shadow behind the text. The shadow includes the outline, but not the glow.
+	pub fn with_shadow(mut self, offset: Vector2<f32>, color: Color<f32>, softness: f32) -> Self {
+		self.shadow_offset = offset;
+		self.shadow_color = color;
+		self.shadow_softness = softness;
+		self
+	}
+
+	fn text_params(&self, spread: u32) -> DistanceFieldParams {
+		let pixel_distance = pixel_distance(spread);
+		DistanceFieldParams {
+			outline_color: self.outline_color.clone(),
+			outline_width: self.outline_width * pixel_distance,
+			glow_color: self.glow_color.clone(),
+			glow_width: self.glow_width * pixel_distance,
+			softness: 0.0,
+			pixel_distance,
+		}
 	}
+
+	fn shadow_params(&self, spread: u32) -> DistanceFieldParams {
+		let pixel_distance = pixel_distance(spread);
+		// The shadow is drawn with the shadow color as the vertex color, so the outline only
+		// needs to match its rgb
+		let shadow_color = &self.shadow_color;
+		DistanceFieldParams {
+			outline_color: Color::rgba(shadow_color.r, shadow_color.g, shadow_color.b, 1.0),
+			outline_width: self.outline_width * pixel_distance,
+			glow_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+			glow_width: 0.0,
+			softness: self.shadow_softness * pixel_distance,
+			pixel_distance,
+		}
+	}
+}
+
+impl Default for DistanceFieldStyle {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The values used by a renderer's distance field shader, built by [`CommonRenderer::distance_field_shader`].
+///
+/// Intended for use by renderer implementations.
+///
+/// Widths are in units of the distance stored in the texture's alpha channel, where 0.5 is the
+/// edge of a glyph. The shader draws, from top to bottom:
+///
+/// * The fill, where the distance is above 0.5, colored by the vertex color.
+/// * The outline, where the distance is above `0.5 - outline_width`.
+/// * The glow, fading out from the outline's edge over `glow_width`.
+///
+/// The edges of the fill and outline are blurred inwards by `softness`, and anti aliased.
+/// The outline and glow colors are multiplied by the vertex color's alpha.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceFieldParams {
+	pub outline_color: Color<f32>,
+	pub outline_width: f32,
+	pub glow_color: Color<f32>,
+	pub glow_width: f32,
+	pub softness: f32,
+
+	/// The change in distance across one pixel of the font at its generated size. Renderers
+	/// which can't measure the change in distance across a target pixel can use it as the anti
+	/// aliasing width.
+	pub pixel_distance: f32,
+}
+
+fn pixel_distance(spread: u32) -> f32 {
+	1.0 / (2.0 * spread.max(1) as f32)
 }
@@ -0,0 +1,413 @@
+use crate::*;
+
+use riddle_math::{Rect, Vector2};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+
+/// The layout and timing of the frames of animations in a sprite sheet, used to build
+/// [`AnimatedSprite`]s.
+///
+/// Sprite sheets can be built from a uniform grid of frames, or loaded from the JSON data
+/// exported by Aseprite or TexturePacker.
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_common::*;
+/// # use riddle_math::*;
+/// # use std::time::Duration;
+/// // A 4x2 grid of 32x32 frames, with the first row used for walking
+/// let sheet = SpriteSheet::from_grid(
+///     vec2(128.0, 64.0),
+///     vec2(32.0, 32.0),
+///     Duration::from_millis(100),
+/// )
+/// .with_tag("walk", 0..4)
+/// .with_tag("jump", 4..8);
+///
+/// assert_eq!(8, sheet.frames.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpriteSheet {
+	/// All the frames in the sheet.
+	pub frames: Vec<SpriteSheetFrame>,
+
+	/// Named sequences of frames.
+	pub tags: Vec<SpriteSheetTag>,
+}
+
+/// The tool which exported the JSON data loaded by [`SpriteSheet::load_json`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpriteSheetFormat {
+	Aseprite,
+	TexturePacker,
+}
+
+/// A single frame of a [`SpriteSheet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteSheetFrame {
+	/// The region of the sheet's sprite which the frame is drawn from.
+	pub rect: Rect<f32>,
+
+	/// How long the frame is displayed for.
+	pub duration: Duration,
+}
+
+/// A named sequence of frames in a [`SpriteSheet`], such as a single animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteSheetTag {
+	pub name: String,
+
+	/// The indices of the frames in the sheet, in the order they are played.
+	pub frames: Vec<usize>,
+}
+
+impl SpriteSheet {
+	/// Build a sheet from a grid of equally sized frames, in row major order, each displayed for
+	/// the same duration.
+	///
+	/// Partial frames at the right and bottom edges of the sheet are ignored.
+	pub fn from_grid(
+		sheet_dimensions: Vector2<f32>,
+		frame_dimensions: Vector2<f32>,
+		frame_duration: Duration,
+	) -> Self {
+		let columns = (sheet_dimensions.x / frame_dimensions.x).floor() as u32;
+		let rows = (sheet_dimensions.y / frame_dimensions.y).floor() as u32;
+
+		let frames = (0..rows)
+			.flat_map(|row| (0..columns).map(move |column| (column, row)))
+			.map(|(column, row)| SpriteSheetFrame {
+				rect: Rect::new(
+					Vector2::new(
+						column as f32 * frame_dimensions.x,
+						row as f32 * frame_dimensions.y,
+					),
+					frame_dimensions,
+				),
+				duration: frame_duration,
+			})
+			.collect();
+
+		Self {
+			frames,
+			tags: vec![],
+		}
+	}
+
+	/// Load a sheet from the JSON data exported by Aseprite or TexturePacker, as given by the
+	/// format.
+	///
+	/// Both the hash and array forms of the `frames` data are supported. For Aseprite data,
+	/// frame durations are read from each frame's `duration`, and tags from `meta.frameTags`,
+	/// where a `reverse` direction reverses the tag's frames. For TexturePacker data, tags are
+	/// read from the `animations` lists of frame names.
+	///
+	/// Frames without a duration are displayed for `default_frame_duration`. Rotated frames
+	/// aren't supported.
+	///
+	/// # Example
+	///
+	/// ```
+	/// # use riddle_renderer_common::*;
+	/// # use std::time::Duration;
+	/// # fn main() -> Result<(), SpriteSheetError> {
+	/// let json = br#"{
+	///     "frames": [
+	///         { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+	///         { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 250 }
+	///     ],
+	///     "meta": {
+	///         "frameTags": [ { "name": "blink", "from": 0, "to": 1, "direction": "forward" } ]
+	///     }
+	/// }"#;
+	///
+	/// let sheet = SpriteSheet::load_json(
+	///     &json[..],
+	///     SpriteSheetFormat::Aseprite,
+	///     Duration::from_millis(100),
+	/// )?;
+	/// assert_eq!(Duration::from_millis(250), sheet.frames[1].duration);
+	/// assert_eq!("blink", sheet.tags[0].name);
+	/// # Ok(()) }
+	/// ```
+	pub fn load_json<R: std::io::Read>(
+		reader: R,
+		format: SpriteSheetFormat,
+		default_frame_duration: Duration,
+	) -> Result<Self, SpriteSheetError> {
+		let json: JsonSheet = serde_json::from_reader(reader)?;
+
+		let named_frames: Vec<(Option<String>, JsonFrame)> = match json.frames {
+			JsonFrames::Array(frames) => frames
+				.into_iter()
+				.map(|frame| (frame.filename.clone(), frame))
+				.collect(),
+			JsonFrames::Hash(OrderedFrames(frames)) => frames
+				.into_iter()
+				.map(|(name, frame)| (Some(name), frame))
+				.collect(),
+		};
+
+		let mut frames = Vec::with_capacity(named_frames.len());
+		for (_, frame) in &named_frames {
+			if frame.rotated {
+				return Err(SpriteSheetError::InvalidData(
+					"Rotated frames are not supported",
+				));
+			}
+
+			frames.push(SpriteSheetFrame {
+				rect: Rect::new(
+					Vector2::new(frame.frame.x, frame.frame.y),
+					Vector2::new(frame.frame.w, frame.frame.h),
+				),
+				duration: match format {
+					SpriteSheetFormat::Aseprite => frame.duration.map(Duration::from_millis),
+					SpriteSheetFormat::TexturePacker => None,
+				}
+				.unwrap_or(default_frame_duration),
+			});
+		}
+
+		let tags = match format {
+			SpriteSheetFormat::Aseprite => Self::aseprite_tags(json.meta.frame_tags, frames.len())?,
+			SpriteSheetFormat::TexturePacker => {
+				Self::texture_packer_tags(json.animations, &named_frames)?
+			}
+		};
+
+		Ok(Self { frames, tags })
+	}
+
+	fn aseprite_tags(
+		frame_tags: Vec<JsonTag>,
+		frame_count: usize,
+	) -> Result<Vec<SpriteSheetTag>, SpriteSheetError> {
+		let mut tags = vec![];
+		for tag in frame_tags {
+			if tag.from > tag.to || tag.to >= frame_count {
+				return Err(SpriteSheetError::InvalidData(
+					"Frame tag range is outside of the frames",
+				));
+			}
+
+			let mut tag_frames: Vec<usize> = (tag.from..=tag.to).collect();
+			if tag.direction == "reverse" {
+				tag_frames.reverse();
+			}
+			tags.push(SpriteSheetTag {
+				name: tag.name,
+				frames: tag_frames,
+			});
+		}
+		Ok(tags)
+	}
+
+	fn texture_packer_tags(
+		animations: BTreeMap<String, Vec<String>>,
+		named_frames: &[(Option<String>, JsonFrame)],
+	) -> Result<Vec<SpriteSheetTag>, SpriteSheetError> {
+		let mut tags = vec![];
+		for (name, frame_names) in animations {
+			let tag_frames = frame_names
+				.into_iter()
+				.map(|frame_name| {
+					named_frames
+						.iter()
+						.position(|(name, _)| name.as_deref() == Some(&frame_name[..]))
+						.ok_or(SpriteSheetError::UnknownFrame(frame_name))
+				})
+				.collect::<Result<Vec<usize>, SpriteSheetError>>()?;
+			tags.push(SpriteSheetTag {
+				name,
+				frames: tag_frames,
+			});
+		}
+		Ok(tags)
+	}
+
+	/// Add a named sequence of frames to the sheet.
+	pub fn with_tag<I: IntoIterator<Item = usize>>(mut self, name: &str, frames: I) -> Self {
+		self.tags.push(SpriteSheetTag {
+			name: name.to_owned(),
+			frames: frames.into_iter().collect(),
+		});
+		self
+	}
+
+	/// Find a tag by name.
+	pub fn tag(&self, name: &str) -> Option<&SpriteSheetTag> {
+		self.tags.iter().find(|tag| tag.name == name)
+	}
+}
+
+#[derive(Deserialize)]
+struct JsonSheet {
+	frames: JsonFrames,
+
+	#[serde(default)]
+	meta: JsonMeta,
+
+	#[serde(default)]
+	animations: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFrames {
+	Array(Vec<JsonFrame>),
+	Hash(OrderedFrames),
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+	#[serde(default)]
+	filename: Option<String>,
+	frame: JsonRect,
+	#[serde(default)]
+	rotated: bool,
+	#[serde(default)]
+	duration: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct JsonRect {
+	x: f32,
+	y: f32,
+	w: f32,
+	h: f32,
+}
+
+#[derive(Deserialize, Default)]
+struct JsonMeta {
+	#[serde(default, rename = "frameTags")]
+	frame_tags: Vec<JsonTag>,
+}
+
+#[derive(Deserialize)]
+struct JsonTag {
+	name: String,
+	from: usize,
+	to: usize,
+	#[serde(default)]
+	direction: String,
+}
+
+/// The hash form of the frames data, which keeps the frames in the order they appear in the
+/// JSON data, as that is the order the exporters number the frames in.
+struct OrderedFrames(Vec<(String, JsonFrame)>);
+
+impl<'de> Deserialize<'de> for OrderedFrames {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		struct OrderedFramesVisitor;
+
+		impl<'de> serde::de::Visitor<'de> for OrderedFramesVisitor {
+			type Value = OrderedFrames;
+
+			fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+				formatter.write_str("a map of frame names to frames")
+			}
+
+			fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+			where
+				A: serde::de::MapAccess<'de>,
+			{
+				let mut frames = vec![];
+				while let Some(entry) = map.next_entry()? {
+					frames.push(entry);
+				}
+				Ok(OrderedFrames(frames))
+			}
+		}
+
+		deserializer.deserialize_map(OrderedFramesVisitor)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn grid_frames_are_row_major() {
+		let sheet = SpriteSheet::from_grid(
+			Vector2::new(70.0, 40.0),
+			Vector2::new(32.0, 16.0),
+			Duration::from_millis(50),
+		);
+
+		let locations: Vec<_> = sheet.frames.iter().map(|f| f.rect.location).collect();
+		assert_eq!(
+			vec![
+				Vector2::new(0.0, 0.0),
+				Vector2::new(32.0, 0.0),
+				Vector2::new(0.0, 16.0),
+				Vector2::new(32.0, 16.0),
+			],
+			locations
+		);
+	}
+
+	#[test]
+	fn aseprite_hash_keeps_frame_order() {
+		let json = br#"{
+			"frames": {
+				"run 10.aseprite": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 80 },
+				"run 2.aseprite": { "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 120 },
+				"run 3.aseprite": { "frame": { "x": 16, "y": 0, "w": 8, "h": 8 }, "duration": 80 }
+			},
+			"meta": {
+				"frameTags": [
+					{ "name": "run", "from": 0, "to": 2, "direction": "forward" },
+					{ "name": "back", "from": 1, "to": 2, "direction": "reverse" }
+				]
+			}
+		}"#;
+		let sheet = SpriteSheet::load_json(
+			&json[..],
+			SpriteSheetFormat::Aseprite,
+			Duration::from_millis(100),
+		)
+		.unwrap();
+
+		assert_eq!(0.0, sheet.frames[0].rect.location.x);
+		assert_eq!(Duration::from_millis(120), sheet.frames[1].duration);
+		assert_eq!(vec![0, 1, 2], sheet.tag("run").unwrap().frames);
+		assert_eq!(vec![2, 1], sheet.tag("back").unwrap().frames);
+	}
+
+	#[test]
+	fn texture_packer_animations_use_frame_names() {
+		let json = br#"{
+			"frames": [
+				{ "filename": "idle.png", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": false },
+				{ "filename": "walk_1.png", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "rotated": false },
+				{ "filename": "walk_2.png", "frame": { "x": 16, "y": 0, "w": 8, "h": 8 }, "rotated": false }
+			],
+			"animations": { "walk": ["walk_1.png", "walk_2.png", "walk_1.png"] }
+		}"#;
+		let sheet = SpriteSheet::load_json(
+			&json[..],
+			SpriteSheetFormat::TexturePacker,
+			Duration::from_millis(100),
+		)
+		.unwrap();
+
+		assert_eq!(Duration::from_millis(100), sheet.frames[2].duration);
+		assert_eq!(vec![1, 2, 1], sheet.tag("walk").unwrap().frames);
+
+		let missing = br#"{ "frames": [], "animations": { "walk": ["walk_1.png"] } }"#;
+		assert!(matches!(
+			SpriteSheet::load_json(
+				&missing[..],
+				SpriteSheetFormat::TexturePacker,
+				Duration::from_millis(100)
+			),
+			Err(SpriteSheetError::UnknownFrame(_))
+		));
+	}
+}
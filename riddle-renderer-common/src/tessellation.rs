@@ -0,0 +1,424 @@
+use crate::vertex::Vertex;
+
+use glam::Vec2;
+use riddle_math::{Rect, Vector2};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// Untextured triangles produced by tessellating a shape.
+///
+/// Indices are stored as `u32`, since large shapes can have more vertices than a `u16` index
+/// can reference. Use [`Geometry::parts`] to split the geometry in to pieces which can be drawn
+/// with `u16` indices.
+pub(crate) struct Geometry {
+	pub verts: Vec<Vertex>,
+	pub indices: Vec<u32>,
+}
+
+/// The maximum number of vertices a single part of a [`Geometry`] can reference.
+const MAX_PART_VERTICES: usize = u16::MAX as usize + 1;
+
+impl Geometry {
+	fn new(points: &[Vec2], indices: Vec<u32>, color: &[f32; 4]) -> Self {
+		Self {
+			verts: points
+				.iter()
+				.map(|p| Vertex::ptc(*p, [0.0, 0.0], color))
+				.collect(),
+			indices,
+		}
+	}
+
+	/// Split the geometry in to parts which each reference few enough vertices to be indexed
+	/// with `u16`s. Geometry which is already small enough is returned as a single part.
+	pub fn parts(&self) -> Vec<(Vec<Vertex>, Vec<u16>)> {
+		if self.verts.len() <= MAX_PART_VERTICES {
+			let indices = self
+				.indices
+				.iter()
+				.map(|&i| u16::try_from(i).unwrap())
+				.collect();
+			return vec![(self.verts.clone(), indices)];
+		}
+
+		let mut parts = vec![];
+		let mut verts: Vec<Vertex> = vec![];
+		let mut indices: Vec<u16> = vec![];
+		let mut remap: HashMap<u32, u16> = Default::default();
+		for triangle in self.indices.chunks_exact(3) {
+			let new_verts = triangle.iter().filter(|i| !remap.contains_key(i)).count();
+			if verts.len() + new_verts > MAX_PART_VERTICES {
+				parts.push((std::mem::take(&mut verts), std::mem::take(&mut indices)));
+				remap.clear();
+			}
+
+			for &i in triangle {
+				let index = *remap.entry(i).or_insert_with(|| {
+					verts.push(self.verts[i as usize]);
+					u16::try_from(verts.len() - 1).unwrap()
+				});
+				indices.push(index);
+			}
+		}
+		if !indices.is_empty() {
+			parts.push((verts, indices));
+		}
+		parts
+	}
+}
+
+/// The maximum length of a miter join, as a multiple of half the stroke thickness. Sharper
+/// joins are clamped to avoid long spikes.
+const MITER_LIMIT: f32 = 4.0;
+
+pub(crate) fn line(
+	start: Vector2<f32>,
+	end: Vector2<f32>,
+	thickness: f32,
+	color: &[f32; 4],
+) -> Geometry {
+	let start = Vec2::from(start);
+	let end = Vec2::from(end);
+	let dir = end - start;
+	if dir.length_squared() == 0.0 {
+		return Geometry::new(&[], vec![], color);
+	}
+
+	let offset = dir.normalize().perp() * (thickness / 2.0);
+	let points = [start + offset, start - offset, end - offset, end + offset];
+	Geometry::new(&points, vec![0, 1, 2, 0, 2, 3], color)
+}
+
+pub(crate) fn rect(rect: &Rect<f32>, color: &[f32; 4]) -> Geometry {
+	Geometry::new(&rect_points(rect), vec![1, 2, 0, 2, 0, 3], color)
+}
+
+/// Triangulate a simple polygon, which may be concave, using ear clipping.
+pub(crate) fn polygon(points: &[Vec2], color: &[f32; 4]) -> Geometry {
+	Geometry::new(points, triangulate(points), color)
+}
+
+/// Triangulate a convex polygon as a fan around its first point.
+pub(crate) fn convex_polygon(points: &[Vec2], color: &[f32; 4]) -> Geometry {
+	let indices = (1..points.len().saturating_sub(1))
+		.flat_map(|i| vec![0, i as u32, (i + 1) as u32])
+		.collect();
+	Geometry::new(points, indices, color)
+}
+
+/// Build a stroke of the given thickness along the closed outline described by the points,
+/// using mitered joins.
+pub(crate) fn closed_stroke(points: &[Vec2], thickness: f32, color: &[f32; 4]) -> Geometry {
+	let count = points.len();
+	if count < 2 {
+		return Geometry::new(&[], vec![], color);
+	}
+
+	let half = thickness / 2.0;
+	let mut stroke_points = Vec::with_capacity(count * 2);
+	for i in 0..count {
+		let prev = points[(i + count - 1) % count];
+		let current = points[i];
+		let next = points[(i + 1) % count];
+
+		let normal_in = (current - prev).normalize_or_zero().perp();
+		let normal_out = (next - current).normalize_or_zero().perp();
+
+		let miter = (normal_in + normal_out).normalize_or_zero();
+		let miter = if miter == Vec2::ZERO {
+			normal_out
+		} else {
+			let cos = miter.dot(normal_out).max(1.0 / MITER_LIMIT);
+			miter / cos
+		};
+
+		stroke_points.push(current + miter * half);
+		stroke_points.push(current - miter * half);
+	}
+
+	let indices = (0..count)
+		.flat_map(|i| {
+			let outer = (i * 2) as u32;
+			let inner = outer + 1;
+			let next_outer = (((i + 1) % count) * 2) as u32;
+			let next_inner = next_outer + 1;
+			vec![outer, inner, next_inner, outer, next_inner, next_outer]
+		})
+		.collect();
+
+	Geometry::new(&stroke_points, indices, color)
+}
+
+pub(crate) fn rect_points(rect: &Rect<f32>) -> [Vec2; 4] {
+	let top_left = Vec2::from(rect.location);
+	let top_right = top_left + glam::vec2(rect.dimensions.x, 0.0);
+	let bottom_left = top_left + glam::vec2(0.0, rect.dimensions.y);
+	let bottom_right = bottom_left + glam::vec2(rect.dimensions.x, 0.0);
+	[top_left, bottom_left, bottom_right, top_right]
+}
+
+pub(crate) fn circle_points(center: Vector2<f32>, radius: f32) -> Vec<Vec2> {
+	let center = Vec2::from(center);
+	arc_points(
+		center,
+		radius,
+		0.0,
+		std::f32::consts::TAU,
+		segment_count(radius, 1.0),
+	)
+}
+
+pub(crate) fn rounded_rect_points(rect: &Rect<f32>, radius: f32) -> Vec<Vec2> {
+	let radius = radius
+		.min(rect.dimensions.x / 2.0)
+		.min(rect.dimensions.y / 2.0)
+		.max(0.0);
+	if radius == 0.0 {
+		return rect_points(rect).to_vec();
+	}
+
+	let min = Vec2::from(rect.location) + Vec2::splat(radius);
+	let max = Vec2::from(rect.location) + Vec2::from(rect.dimensions) - Vec2::splat(radius);
+	let segments = segment_count(radius, 0.25);
+
+	use std::f32::consts::{FRAC_PI_2, PI};
+	let mut points = vec![];
+	points.extend(arc_points(
+		glam::vec2(max.x, max.y),
+		radius,
+		0.0,
+		FRAC_PI_2,
+		segments,
+	));
+	points.extend(arc_points(
+		glam::vec2(min.x, max.y),
+		radius,
+		FRAC_PI_2,
+		FRAC_PI_2,
+		segments,
+	));
+	points.extend(arc_points(
+		glam::vec2(min.x, min.y),
+		radius,
+		PI,
+		FRAC_PI_2,
+		segments,
+	));
+	points.extend(arc_points(
+		glam::vec2(max.x, min.y),
+		radius,
+		PI + FRAC_PI_2,
+		FRAC_PI_2,
+		segments,
+	));
+	points
+}
+
+/// Points along an arc, including the start point but excluding the end point unless the arc
+/// is not a full circle.
+fn arc_points(center: Vec2, radius: f32, start: f32, sweep: f32, segments: u32) -> Vec<Vec2> {
+	let closed = sweep >= std::f32::consts::TAU;
+	let point_count = if closed { segments } else { segments + 1 };
+	(0..point_count)
+		.map(|i| {
+			let angle = start + sweep * (i as f32 / segments as f32);
+			center + glam::vec2(angle.cos(), angle.sin()) * radius
+		})
+		.collect()
+}
+
+/// Number of segments used to approximate a fraction of a circle, targeting segments roughly
+/// 4 pixels long.
+fn segment_count(radius: f32, fraction: f32) -> u32 {
+	let circumference = std::f32::consts::TAU * radius.abs() * fraction;
+	((circumference / 4.0).ceil() as u32).clamp(4, 256)
+}
+
+fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+	(b - a).perp_dot(c - a)
+}
+
+fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+	let d1 = cross(a, b, p);
+	let d2 = cross(b, c, p);
+	let d3 = cross(c, a, p);
+	let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+	let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+	!(has_neg && has_pos)
+}
+
+fn triangulate(points: &[Vec2]) -> Vec<u32> {
+	if points.len() < 3 {
+		return vec![];
+	}
+
+	let signed_area: f32 = (0..points.len())
+		.map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+		.sum();
+
+	// Work with a consistent winding, so convexity can be tested by sign.
+	let mut remaining: Vec<usize> = (0..points.len()).collect();
+	if signed_area < 0.0 {
+		remaining.reverse();
+	}
+
+	let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+	while remaining.len() > 3 {
+		let len = remaining.len();
+		let ear = (0..len).find(|&i| {
+			let a = points[remaining[(i + len - 1) % len]];
+			let b = points[remaining[i]];
+			let c = points[remaining[(i + 1) % len]];
+			if cross(a, b, c) <= 0.0 {
+				return false;
+			}
+
+			remaining.iter().all(|&idx| {
+				let p = points[idx];
+				p == a || p == b || p == c || !point_in_triangle(p, a, b, c)
+			})
+		});
+
+		match ear {
+			Some(i) => {
+				indices.push(remaining[(i + len - 1) % len] as u32);
+				indices.push(remaining[i] as u32);
+				indices.push(remaining[(i + 1) % len] as u32);
+				remaining.remove(i);
+			}
+			None => {
+				// Degenerate or self intersecting polygon, fan the remaining points.
+				for i in 1..len - 1 {
+					indices.push(remaining[0] as u32);
+					indices.push(remaining[i] as u32);
+					indices.push(remaining[i + 1] as u32);
+				}
+				return indices;
+			}
+		}
+	}
+
+	indices.extend(remaining.iter().map(|&i| i as u32));
+	indices
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn triangle_area(points: &[Vec2], indices: &[u32]) -> f32 {
+		indices
+			.chunks(3)
+			.map(|tri| {
+				let (a, b, c) = (
+					points[tri[0] as usize],
+					points[tri[1] as usize],
+					points[tri[2] as usize],
+				);
+				cross(a, b, c).abs() / 2.0
+			})
+			.sum()
+	}
+
+	#[test]
+	fn triangulate_convex_square() {
+		let points = [
+			glam::vec2(0.0, 0.0),
+			glam::vec2(10.0, 0.0),
+			glam::vec2(10.0, 10.0),
+			glam::vec2(0.0, 10.0),
+		];
+		let indices = triangulate(&points);
+		assert_eq!(6, indices.len());
+		assert_eq!(100.0, triangle_area(&points, &indices));
+	}
+
+	#[test]
+	fn triangulate_concave_l_shape() {
+		// Both windings should produce the same covered area
+		let mut points = vec![
+			glam::vec2(0.0, 0.0),
+			glam::vec2(20.0, 0.0),
+			glam::vec2(20.0, 10.0),
+			glam::vec2(10.0, 10.0),
+			glam::vec2(10.0, 20.0),
+			glam::vec2(0.0, 20.0),
+		];
+		for _ in 0..2 {
+			let indices = triangulate(&points);
+			assert_eq!(12, indices.len());
+			assert_eq!(300.0, triangle_area(&points, &indices));
+			points.reverse();
+		}
+	}
+
+	#[test]
+	fn line_has_requested_thickness() {
+		let geometry = line(
+			Vector2::new(0.0, 0.0),
+			Vector2::new(10.0, 0.0),
+			4.0,
+			&[1.0; 4],
+		);
+		let points: Vec<Vec2> = geometry.verts.iter().map(|v| Vec2::from(v.pos)).collect();
+		assert_eq!(40.0, triangle_area(&points, &geometry.indices));
+	}
+
+	#[test]
+	fn stroke_rect_covers_border() {
+		let rect = Rect::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 10.0));
+		let geometry = closed_stroke(&rect_points(&rect), 2.0, &[1.0; 4]);
+		let points: Vec<Vec2> = geometry.verts.iter().map(|v| Vec2::from(v.pos)).collect();
+
+		// Outer 12x12 minus inner 8x8
+		let area = triangle_area(&points, &geometry.indices);
+		assert!((area - 80.0).abs() < 0.001);
+	}
+
+	#[test]
+	fn large_geometry_splits_in_to_u16_parts() {
+		let points: Vec<Vec2> = (0..40_000)
+			.map(|i| {
+				let angle = std::f32::consts::TAU * (i as f32 / 40_000.0);
+				glam::vec2(angle.cos(), angle.sin()) * 1000.0
+			})
+			.collect();
+		let geometry = closed_stroke(&points, 2.0, &[1.0; 4]);
+		assert_eq!(80_000, geometry.verts.len());
+
+		let parts = geometry.parts();
+		assert!(parts.len() > 1);
+
+		let mut triangles = 0;
+		for (verts, indices) in &parts {
+			assert!(verts.len() <= MAX_PART_VERTICES);
+			assert!(indices.iter().all(|&i| (i as usize) < verts.len()));
+			triangles += indices.len() / 3;
+		}
+		assert_eq!(geometry.indices.len() / 3, triangles);
+
+		// The parts cover the same area as the original geometry
+		let area = |verts: &[Vertex], indices: &[u32]| {
+			let points: Vec<Vec2> = verts.iter().map(|v| Vec2::from(v.pos)).collect();
+			triangle_area(&points, indices)
+		};
+		let parts_area: f32 = parts
+			.iter()
+			.map(|(verts, indices)| {
+				let indices: Vec<u32> = indices.iter().map(|&i| i as u32).collect();
+				area(verts, &indices)
+			})
+			.sum();
+		let total_area = area(&geometry.verts, &geometry.indices);
+		assert!((parts_area - total_area).abs() < total_area * 0.0001);
+	}
+
+	#[test]
+	fn rounded_rect_clamps_radius() {
+		let rect = Rect::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 4.0));
+		let points = rounded_rect_points(&rect, 100.0);
+		for p in points {
+			assert!(p.x >= -0.001 && p.x <= 10.001);
+			assert!(p.y >= -0.001 && p.y <= 4.001);
+		}
+	}
+}
@@ -0,0 +1,154 @@
+use riddle_math::{Rect, Vector2};
+
+/// How a fixed size virtual screen is scaled to fill an output, such as a window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ScalingMode {
+	/// Scale by the largest whole number which fits the output, so every virtual pixel covers
+	/// the same number of output pixels. The scaled screen is centered, with bars filling the
+	/// rest of the output. If the output is smaller than the virtual screen it is drawn
+	/// unscaled and cropped.
+	Integer,
+
+	/// Scale as large as possible while keeping the aspect ratio, with bars filling the rest
+	/// of the output along one axis.
+	#[default]
+	AspectFit,
+
+	/// Stretch to fill the output, ignoring the aspect ratio.
+	Stretch,
+}
+
+/// A fixed size virtual screen, and how it is scaled to fill an output.
+///
+/// Maps between virtual coordinates and output coordinates, such as the mouse position within
+/// a window. For [`ScalingMode::Integer`] to keep virtual pixels aligned to display pixels, the
+/// output dimensions and locations should be in physical pixels.
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_common::*; use riddle_math::*;
+/// let resolution = VirtualResolution::new(vec2(320, 180), ScalingMode::Integer);
+///
+/// // 320x180 fits in to 1000x600 three times, leaving bars around the scaled screen
+/// let output = vec2(1000.0, 600.0);
+/// assert_eq!(
+///     Rect::new(vec2(20.0, 30.0), vec2(960.0, 540.0)),
+///     resolution.viewport(output)
+/// );
+///
+/// assert_eq!(vec2(10.0, 5.0), resolution.output_to_virtual(output, vec2(50.0, 45.0)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualResolution {
+	/// The dimensions of the virtual screen, in virtual pixels.
+	pub dimensions: Vector2<u32>,
+
+	pub mode: ScalingMode,
+}
+
+impl VirtualResolution {
+	pub fn new(dimensions: Vector2<u32>, mode: ScalingMode) -> Self {
+		Self { dimensions, mode }
+	}
+
+	/// The scale applied to each axis of the virtual screen to draw it to an output of the
+	/// given dimensions.
+	pub fn scale(&self, output: Vector2<f32>) -> Vector2<f32> {
+		let ratio = Vector2::new(
+			output.x / self.dimensions.x as f32,
+			output.y / self.dimensions.y as f32,
+		);
+		match self.mode {
+			ScalingMode::Integer => {
+				let scale = ratio.x.min(ratio.y).floor().max(1.0);
+				Vector2::new(scale, scale)
+			}
+			ScalingMode::AspectFit => {
+				let scale = ratio.x.min(ratio.y);
+				Vector2::new(scale, scale)
+			}
+			ScalingMode::Stretch => ratio,
+		}
+	}
+
+	/// The rect, in output coordinates, the virtual screen is drawn in to. Anything in the
+	/// output outside of the rect is letterboxing.
+	pub fn viewport(&self, output: Vector2<f32>) -> Rect<f32> {
+		let scale = self.scale(output);
+		let dimensions = Vector2::new(
+			self.dimensions.x as f32 * scale.x,
+			self.dimensions.y as f32 * scale.y,
+		);
+		let mut location = (output - dimensions) * 0.5;
+		if self.mode == ScalingMode::Integer {
+			// Keep virtual pixels aligned to output pixels
+			location = Vector2::new(location.x.floor(), location.y.floor());
+		}
+		Rect::new(location, dimensions)
+	}
+
+	/// Map a location in the output, such as the mouse position, to a location in the virtual
+	/// screen. Locations in the letterbox bars map to locations outside of the virtual screen.
+	pub fn output_to_virtual(&self, output: Vector2<f32>, point: Vector2<f32>) -> Vector2<f32> {
+		let viewport = self.viewport(output);
+		let scale = self.scale(output);
+		let relative = point - viewport.location;
+		Vector2::new(relative.x / scale.x, relative.y / scale.y)
+	}
+
+	/// Map a location in the virtual screen to a location in the output.
+	pub fn virtual_to_output(&self, output: Vector2<f32>, point: Vector2<f32>) -> Vector2<f32> {
+		let viewport = self.viewport(output);
+		let scale = self.scale(output);
+		viewport.location + Vector2::new(point.x * scale.x, point.y * scale.y)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn aspect_fit_letterboxes_along_one_axis() {
+		let resolution = VirtualResolution::new(Vector2::new(320, 180), ScalingMode::AspectFit);
+
+		assert_eq!(
+			Rect::new(Vector2::new(0.0, 75.0), Vector2::new(800.0, 450.0)),
+			resolution.viewport(Vector2::new(800.0, 600.0))
+		);
+	}
+
+	#[test]
+	fn integer_scale_is_at_least_one() {
+		let resolution = VirtualResolution::new(Vector2::new(320, 180), ScalingMode::Integer);
+
+		// Smaller than the virtual screen, so drawn unscaled and cropped
+		assert_eq!(
+			Rect::new(Vector2::new(-10.0, -10.0), Vector2::new(320.0, 180.0)),
+			resolution.viewport(Vector2::new(300.0, 160.0))
+		);
+	}
+
+	#[test]
+	fn output_virtual_round_trip() {
+		let output = Vector2::new(1000.0, 700.0);
+		let point = Vector2::new(100.0, 50.0);
+		for mode in [
+			ScalingMode::Integer,
+			ScalingMode::AspectFit,
+			ScalingMode::Stretch,
+		] {
+			let resolution = VirtualResolution::new(Vector2::new(320, 180), mode);
+			let round_trip =
+				resolution.output_to_virtual(output, resolution.virtual_to_output(output, point));
+			assert!(
+				(round_trip.x - point.x).abs() < 0.001 && (round_trip.y - point.y).abs() < 0.001,
+				"{:?}: expected {:?}, got {:?}",
+				mode,
+				point,
+				round_trip
+			);
+		}
+	}
+}
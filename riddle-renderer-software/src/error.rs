@@ -0,0 +1,12 @@
+use crate::*;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SoftwareRendererError {
+	#[error(transparent)]
+	Image(#[from] image::ImageError),
+
+	#[error(transparent)]
+	Common(#[from] CommonError),
+}
@@ -0,0 +1,54 @@
+#![deny(clippy::all)]
+
+//! Riddle CPU renderer, which rasterizes to a `riddle_image::Image`.
+//!
+//! The renderer implements the traits defined in `riddle-renderer-common`, with the same
+//! transform, filtering, blending and color multiply semantics as `riddle-renderer-wgpu`.
+//! It requires no GPU or window, so code written against the common renderer traits can be
+//! exercised in unit tests.
+//!
+//! # Example
+//!
+//! ```
+//! use riddle_renderer_software::{common::Color, math::*, *};
+//!
+//! # fn main() -> Result<(), SoftwareRendererError> {
+//! let renderer = Renderer::new(vec2(32, 32));
+//!
+//! renderer.render(|render_ctx| {
+//!     render_ctx.clear(Color::BLACK)?;
+//!     render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(16.0, 16.0)), Color::RED)
+//! })?;
+//!
+//! let img = renderer.image();
+//! assert_eq!(Color::<u8>::RED, img.get_pixel([8, 8]));
+//! assert_eq!(Color::<u8>::BLACK, img.get_pixel([24, 24]));
+//! # Ok(()) }
+//! ```
+
+mod error;
+mod rasterizer;
+mod render_context;
+mod renderer;
+mod shader;
+mod sprite;
+mod texture;
+
+pub use riddle_common as common;
+pub use riddle_image as image;
+pub use riddle_math as math;
+
+type Result<R> = std::result::Result<R, SoftwareRendererError>;
+
+pub use error::*;
+pub use render_context::*;
+pub use renderer::*;
+pub use shader::*;
+pub use sprite::*;
+pub use texture::*;
+
+pub use riddle_renderer_common::*;
+
+use rasterizer::*;
+use riddle_common::*;
+use riddle_renderer_common::vertex::*;
@@ -0,0 +1,448 @@
+use crate::*;
+
+use math::Vector2;
+
+/// The image being rendered to, along with the render context's mask.
+pub(crate) struct RenderTarget {
+	pub image: image::Image,
+	pub mask: Vec<bool>,
+}
+
+impl RenderTarget {
+	pub fn new(dimensions: Vector2<u32>) -> Self {
+		Self {
+			image: image::Image::new(dimensions.x, dimensions.y),
+			mask: vec![false; (dimensions.x * dimensions.y) as usize],
+		}
+	}
+}
+
+/// How a draw interacts with the target's mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MaskState {
+	/// The mask is ignored.
+	Disabled,
+
+	/// Fragments with an alpha of at least 0.5 are added to the mask, instead of being drawn.
+	Write,
+
+	/// Fragments are only drawn within the mask.
+	Inside,
+
+	/// Fragments are only drawn outside of the mask.
+	Outside,
+}
+
+impl From<MaskMode> for MaskState {
+	fn from(mode: MaskMode) -> Self {
+		match mode {
+			MaskMode::Disabled => MaskState::Disabled,
+			MaskMode::Inside => MaskState::Inside,
+			MaskMode::Outside => MaskState::Outside,
+		}
+	}
+}
+
+/// The state used to rasterize a batch of triangles in to a target image.
+pub(crate) struct Rasterizer<'a> {
+	pub transform: glam::Mat4,
+	pub texture: &'a Texture,
+	pub shader: &'a Shader,
+	pub blend_mode: BlendMode,
+	pub clip: Option<math::Rect<f32>>,
+	pub mask_state: MaskState,
+}
+
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+	pos: glam::Vec2,
+	uv: glam::Vec2,
+	color: glam::Vec4,
+}
+
+impl<'a> Rasterizer<'a> {
+	/// Transform and rasterize indexed triangles in to the target.
+	pub fn draw(&self, target: &mut RenderTarget, verts: &[Vertex], indices: &[u16]) {
+		let screen_verts: Vec<ScreenVertex> = verts
+			.iter()
+			.map(|v| ScreenVertex {
+				pos: self
+					.transform
+					.transform_point3(glam::vec3(v.pos[0], v.pos[1], 0.0))
+					.truncate(),
+				uv: glam::Vec2::from(v.uv),
+				color: glam::Vec4::from(v.color),
+			})
+			.collect();
+
+		for tri in indices.chunks_exact(3) {
+			let a = screen_verts[tri[0] as usize];
+			let b = screen_verts[tri[1] as usize];
+			let c = screen_verts[tri[2] as usize];
+			self.draw_triangle(target, a, b, c);
+		}
+	}
+
+	fn draw_triangle(
+		&self,
+		target: &mut RenderTarget,
+		a: ScreenVertex,
+		b: ScreenVertex,
+		c: ScreenVertex,
+	) {
+		// Orient all triangles the same way, so the top left fill rule is consistent
+		let area = edge(a.pos, b.pos, c.pos);
+		let (b, c, area) = if area < 0.0 {
+			(c, b, -area)
+		} else {
+			(b, c, area)
+		};
+		if area == 0.0 {
+			return;
+		}
+
+		let (clip_min, clip_max) = self.clip_bounds(&target.image);
+		let min = a.pos.min(b.pos).min(c.pos).max(clip_min);
+		let max = a.pos.max(b.pos).max(c.pos).min(clip_max);
+		if min.x >= max.x || min.y >= max.y {
+			return;
+		}
+
+		let (filter, lod) = self.select_sampling(&a, &b, &c, area);
+
+		let bias = [
+			fill_bias(b.pos, c.pos),
+			fill_bias(c.pos, a.pos),
+			fill_bias(a.pos, b.pos),
+		];
+
+		for y in (min.y.floor() as u32)..(max.y.ceil() as u32) {
+			for x in (min.x.floor() as u32)..(max.x.ceil() as u32) {
+				let p = glam::vec2(x as f32 + 0.5, y as f32 + 0.5);
+				let w = [
+					edge(b.pos, c.pos, p),
+					edge(c.pos, a.pos, p),
+					edge(a.pos, b.pos, p),
+				];
+				if w.iter().zip(bias.iter()).any(
+					|(w, inclusive)| {
+						if *inclusive {
+							*w < 0.0
+						} else {
+							*w <= 0.0
+						}
+					},
+				) {
+					continue;
+				}
+
+				let mask_index = (y * target.image.width() + x) as usize;
+				match self.mask_state {
+					MaskState::Inside if !target.mask[mask_index] => continue,
+					MaskState::Outside if target.mask[mask_index] => continue,
+					_ => (),
+				}
+
+				let (wa, wb, wc) = (w[0] / area, w[1] / area, w[2] / area);
+				let uv = a.uv * wa + b.uv * wb + c.uv * wc;
+				let color = a.color * wa + b.color * wb + c.color * wc;
+
+				let fragment = Fragment {
+					position: Vector2::new(p.x, p.y),
+					uv: Vector2::new(uv.x, uv.y),
+					color: Color::rgba(color.x, color.y, color.z, color.w),
+					texel: self.texture.sample(Vector2::new(uv.x, uv.y), filter, lod),
+				};
+				let src = self.shader.shade(&fragment);
+
+				if self.mask_state == MaskState::Write {
+					if src.a >= 0.5 {
+						target.mask[mask_index] = true;
+					}
+					continue;
+				}
+
+				let dst: Color<f32> = target.image.get_pixel([x, y]).convert();
+				target
+					.image
+					.set_pixel([x, y], blend(self.blend_mode, src, dst));
+			}
+		}
+	}
+
+	/// The region of the target which can be drawn to, snapped to whole pixels.
+	fn clip_bounds(&self, target: &image::Image) -> (glam::Vec2, glam::Vec2) {
+		let target_max = glam::vec2(target.width() as f32, target.height() as f32);
+		match &self.clip {
+			Some(clip) => {
+				let min = glam::Vec2::from(clip.location);
+				let max = min + glam::Vec2::from(clip.dimensions);
+				(
+					min.round().max(glam::Vec2::ZERO),
+					max.round().min(target_max),
+				)
+			}
+			None => (glam::Vec2::ZERO, target_max),
+		}
+	}
+
+	/// Select the min or mag filter, and the mip level of detail, by comparing the texel area
+	/// covered by the triangle with the pixel area it covers.
+	fn select_sampling(
+		&self,
+		a: &ScreenVertex,
+		b: &ScreenVertex,
+		c: &ScreenVertex,
+		pixel_area: f32,
+	) -> (FilterMode, f32) {
+		let tex_dimensions = self.texture.dimensions();
+		let tex_scale = glam::vec2(tex_dimensions.x as f32, tex_dimensions.y as f32);
+		let texel_area = edge(a.uv * tex_scale, b.uv * tex_scale, c.uv * tex_scale).abs();
+		if texel_area > pixel_area {
+			// Each mip level has a quarter of the texels of the level before it
+			let lod = 0.5 * (texel_area / pixel_area).log2();
+			(self.texture.internal.min_filter, lod)
+		} else {
+			(self.texture.internal.mag_filter, 0.0)
+		}
+	}
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`.
+#[inline]
+fn edge(a: glam::Vec2, b: glam::Vec2, p: glam::Vec2) -> f32 {
+	(b - a).perp_dot(p - a)
+}
+
+/// Whether pixels exactly on the edge from `a` to `b` are covered, following the top left fill
+/// rule, so pixels on edges shared between triangles are only drawn once.
+#[inline]
+fn fill_bias(a: glam::Vec2, b: glam::Vec2) -> bool {
+	let d = b - a;
+	(d.y == 0.0 && d.x > 0.0) || d.y < 0.0
+}
+
+/// Combine a source color with the target color, matching the blend states used by
+/// `riddle-renderer-wgpu`.
+pub(crate) fn blend(mode: BlendMode, src: Color<f32>, dst: Color<f32>) -> Color<f32> {
+	let mix = |f: &dyn Fn(f32, f32) -> f32| {
+		Color::rgba(
+			f(src.r, dst.r).clamp(0.0, 1.0),
+			f(src.g, dst.g).clamp(0.0, 1.0),
+			f(src.b, dst.b).clamp(0.0, 1.0),
+			dst.a,
+		)
+	};
+
+	match mode {
+		BlendMode::Alpha => {
+			let mut out = mix(&|s, d| s * src.a + d * (1.0 - src.a));
+			out.a = (src.a + dst.a).clamp(0.0, 1.0);
+			out
+		}
+		BlendMode::PremultipliedAlpha => {
+			let mut out = mix(&|s, d| s + d * (1.0 - src.a));
+			out.a = (src.a + dst.a * (1.0 - src.a)).clamp(0.0, 1.0);
+			out
+		}
+		BlendMode::Additive => mix(&|s, d| s * src.a + d),
+		BlendMode::Multiply => mix(&|s, d| s * d),
+		BlendMode::Screen => mix(&|s, d| s + d * (1.0 - s)),
+		BlendMode::Replace => Color::rgba(
+			src.r.clamp(0.0, 1.0),
+			src.g.clamp(0.0, 1.0),
+			src.b.clamp(0.0, 1.0),
+			src.a.clamp(0.0, 1.0),
+		),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn draw_quad(
+		blend_mode: BlendMode,
+		color: [f32; 4],
+		clip: Option<math::Rect<f32>>,
+	) -> image::Image {
+		let mut white = image::Image::new(1, 1);
+		white.fill(Color::<u8>::WHITE);
+		let texture = Texture::from_image(&white, &SpriteInitArgs::new());
+		let shader = Shader::default();
+
+		let verts = [
+			Vertex::ptc([0.0, 0.0], [0.0, 0.0], &color),
+			Vertex::ptc([0.0, 4.0], [0.0, 1.0], &color),
+			Vertex::ptc([4.0, 4.0], [1.0, 1.0], &color),
+			Vertex::ptc([4.0, 0.0], [1.0, 0.0], &color),
+		];
+
+		let mut target = RenderTarget::new(Vector2::new(4, 4));
+		Rasterizer {
+			transform: glam::Mat4::IDENTITY,
+			texture: &texture,
+			shader: &shader,
+			blend_mode,
+			clip,
+			mask_state: MaskState::Disabled,
+		}
+		.draw(&mut target, &verts, &[1, 2, 0, 2, 0, 3]);
+		target.image
+	}
+
+	#[test]
+	fn shared_edges_are_drawn_once() {
+		let img = draw_quad(BlendMode::Additive, [0.25, 0.25, 0.25, 1.0], None);
+		let expected: Color<u8> = Color::rgba(0.25, 0.25, 0.25, 0.0).convert();
+		for y in 0..4 {
+			for x in 0..4 {
+				assert_eq!(expected, img.get_pixel([x, y]));
+			}
+		}
+	}
+
+	#[test]
+	fn alpha_blend_over_transparent() {
+		let img = draw_quad(BlendMode::Alpha, [1.0, 0.0, 0.0, 0.5], None);
+		let expected: Color<u8> = Color::rgba(0.5, 0.0, 0.0, 0.5).convert();
+		assert_eq!(expected, img.get_pixel([1, 1]));
+	}
+
+	#[test]
+	fn clip_restricts_drawing() {
+		let clip = math::Rect::new(Vector2::new(1.0, 1.0), Vector2::new(2.0, 2.0));
+		let img = draw_quad(BlendMode::Replace, [1.0, 1.0, 1.0, 1.0], Some(clip));
+		for y in 0..4 {
+			for x in 0..4 {
+				let inside = (1..3).contains(&x) && (1..3).contains(&y);
+				assert_eq!(inside, img.get_pixel([x, y]) == Color::<u8>::WHITE);
+			}
+		}
+	}
+
+	#[test]
+	fn mask_restricts_drawing() {
+		let mut white = image::Image::new(1, 1);
+		white.fill(Color::<u8>::WHITE);
+		let texture = Texture::from_image(&white, &SpriteInitArgs::new());
+		let shader = Shader::default();
+		let quad = |x: f32, width: f32| {
+			let color = [1.0; 4];
+			[
+				Vertex::ptc([x, 0.0], [0.0, 0.0], &color),
+				Vertex::ptc([x, 4.0], [0.0, 1.0], &color),
+				Vertex::ptc([x + width, 4.0], [1.0, 1.0], &color),
+				Vertex::ptc([x + width, 0.0], [1.0, 0.0], &color),
+			]
+		};
+		let draw = |target: &mut RenderTarget, verts: &[Vertex], mask_state| {
+			Rasterizer {
+				transform: glam::Mat4::IDENTITY,
+				texture: &texture,
+				shader: &shader,
+				blend_mode: BlendMode::Replace,
+				clip: None,
+				mask_state,
+			}
+			.draw(target, verts, &[1, 2, 0, 2, 0, 3]);
+		};
+
+		let mut target = RenderTarget::new(Vector2::new(4, 4));
+		draw(&mut target, &quad(0.0, 2.0), MaskState::Write);
+		assert_eq!(Color::<u8>::ZERO, target.image.get_pixel([0, 0]));
+
+		draw(&mut target, &quad(0.0, 4.0), MaskState::Outside);
+		assert_eq!(Color::<u8>::ZERO, target.image.get_pixel([1, 0]));
+		assert_eq!(Color::<u8>::WHITE, target.image.get_pixel([2, 0]));
+	}
+
+	#[test]
+	fn minified_mipmapped_texture_uses_smaller_level() {
+		let mut checker = image::Image::new(4, 4);
+		for y in 0..4 {
+			for x in 0..4 {
+				if (x + y) % 2 == 0 {
+					checker.set_pixel([x, y], Color::<u8>::WHITE);
+				} else {
+					checker.set_pixel([x, y], Color::<u8>::BLACK);
+				}
+			}
+		}
+		let shader = Shader::default();
+		let color = [1.0; 4];
+		let verts = [
+			Vertex::ptc([0.0, 0.0], [0.0, 0.0], &color),
+			Vertex::ptc([0.0, 1.0], [0.0, 1.0], &color),
+			Vertex::ptc([1.0, 1.0], [1.0, 1.0], &color),
+			Vertex::ptc([1.0, 0.0], [1.0, 0.0], &color),
+		];
+		let draw = |init_args: &SpriteInitArgs| {
+			let texture = Texture::from_image(&checker, init_args);
+			let mut target = RenderTarget::new(Vector2::new(1, 1));
+			Rasterizer {
+				transform: glam::Mat4::IDENTITY,
+				texture: &texture,
+				shader: &shader,
+				blend_mode: BlendMode::Replace,
+				clip: None,
+				mask_state: MaskState::Disabled,
+			}
+			.draw(&mut target, &verts, &[1, 2, 0, 2, 0, 3]);
+			target.image.get_pixel([0, 0])
+		};
+
+		let plain = draw(&SpriteInitArgs::new());
+		assert!(plain == Color::<u8>::WHITE || plain == Color::<u8>::BLACK);
+
+		let mipmapped = draw(&SpriteInitArgs::new().with_mipmaps(FilterMode::Nearest));
+		assert!((127..=128).contains(&mipmapped.r));
+	}
+
+	#[test]
+	fn address_modes_resolve_coordinates_outside_texture() {
+		let mut strip = image::Image::new(2, 1);
+		strip.set_pixel([0, 0], Color::<u8>::RED);
+		strip.set_pixel([1, 0], Color::<u8>::BLUE);
+		let shader = Shader::default();
+		let color = [1.0; 4];
+		let verts = [
+			Vertex::ptc([0.0, 0.0], [0.0, 0.0], &color),
+			Vertex::ptc([0.0, 1.0], [0.0, 1.0], &color),
+			Vertex::ptc([6.0, 1.0], [3.0, 1.0], &color),
+			Vertex::ptc([6.0, 0.0], [3.0, 0.0], &color),
+		];
+		let draw = |address_mode| {
+			let texture = Texture::from_image(
+				&strip,
+				&SpriteInitArgs::new().with_address_modes(address_mode, AddressMode::ClampToEdge),
+			);
+			let mut target = RenderTarget::new(Vector2::new(6, 1));
+			Rasterizer {
+				transform: glam::Mat4::IDENTITY,
+				texture: &texture,
+				shader: &shader,
+				blend_mode: BlendMode::Replace,
+				clip: None,
+				mask_state: MaskState::Disabled,
+			}
+			.draw(&mut target, &verts, &[1, 2, 0, 2, 0, 3]);
+			(0..6)
+				.map(|x| target.image.get_pixel([x, 0]).r > 127)
+				.collect::<Vec<bool>>()
+		};
+
+		assert_eq!(
+			vec![true, false, false, false, false, false],
+			draw(AddressMode::ClampToEdge)
+		);
+		assert_eq!(
+			vec![true, false, true, false, true, false],
+			draw(AddressMode::Repeat)
+		);
+		assert_eq!(
+			vec![true, false, false, true, true, false],
+			draw(AddressMode::MirrorRepeat)
+		);
+	}
+}
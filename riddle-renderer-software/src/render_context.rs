@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use crate::*;
+
+/// The [`RenderContext`] used by the software [`Renderer`].
+///
+/// Draws are rasterized immediately in to the renderer's target image.
+pub struct SoftwareRenderContext {
+	renderer: Renderer,
+	transform: glam::Mat4,
+	clip_stack: Vec<math::Rect<f32>>,
+	mask_state: MaskState,
+	draw_queue: DrawQueue<Texture, Shader>,
+}
+
+impl SoftwareRenderContext {
+	pub(crate) fn new(renderer: &Renderer) -> Self {
+		Self {
+			renderer: renderer.clone(),
+			transform: glam::Mat4::IDENTITY,
+			clip_stack: vec![],
+			mask_state: MaskState::Disabled,
+			draw_queue: DrawQueue::new(),
+		}
+	}
+
+	fn render(
+		&mut self,
+		texture: &Texture,
+		shader: &Shader,
+		verts: &[Vertex],
+		indices: &[u16],
+		blend_mode: BlendMode,
+	) {
+		if self.draw_queue.is_active() {
+			self.draw_queue.push(
+				texture.clone(),
+				shader.clone(),
+				blend_mode,
+				verts,
+				indices,
+				self.transform,
+				self.clip_stack.last().cloned(),
+				|a, b| {
+					Arc::ptr_eq(&a.texture.internal, &b.texture.internal)
+						&& a.shader.same_shader(&b.shader)
+						&& a.blend_mode == b.blend_mode
+				},
+			);
+		} else {
+			self.rasterize(texture, shader, verts, indices, blend_mode);
+		}
+	}
+
+	/// Rasterize any draws queued while in sorted mode, in sorted order.
+	fn draw_queued(&mut self) {
+		let draws = self.draw_queue.take_sorted();
+		let transform = std::mem::replace(&mut self.transform, glam::Mat4::IDENTITY);
+		let clip_stack = std::mem::take(&mut self.clip_stack);
+
+		for draw in draws {
+			self.clip_stack = draw.clip.into_iter().collect();
+			self.rasterize(
+				&draw.texture,
+				&draw.shader,
+				&draw.verts,
+				&draw.indices,
+				draw.blend_mode,
+			);
+		}
+
+		self.transform = transform;
+		self.clip_stack = clip_stack;
+	}
+
+	fn rasterize(
+		&self,
+		texture: &Texture,
+		shader: &Shader,
+		verts: &[Vertex],
+		indices: &[u16],
+		blend_mode: BlendMode,
+	) {
+		// Draws to the mask only need the texture's alpha, so custom shaders aren't used.
+		let shader = if self.mask_state == MaskState::Write {
+			&self.renderer.standard_res().default_shader
+		} else {
+			shader
+		};
+
+		let rasterizer = Rasterizer {
+			transform: self.transform,
+			texture,
+			shader,
+			blend_mode,
+			clip: self.clip_stack.last().cloned(),
+			mask_state: self.mask_state,
+		};
+		self.renderer
+			.with_target(|target| rasterizer.draw(target, verts, indices));
+	}
+}
+
+impl RenderContext<Renderer> for SoftwareRenderContext {
+	fn set_transform(&mut self, transform: mint::ColumnMatrix4<f32>) -> Result<()> {
+		self.transform = transform.into();
+		Ok(())
+	}
+
+	fn clear(&mut self, color: Color<f32>) -> Result<()> {
+		self.draw_queued();
+		self.renderer.with_target(|target| target.image.fill(color));
+		Ok(())
+	}
+
+	fn push_clip_rect(&mut self, rect: &math::Rect<f32>) -> Result<()> {
+		let clip = nested_clip_rect(self.clip_stack.last(), rect);
+		self.clip_stack.push(clip);
+		Ok(())
+	}
+
+	fn pop_clip_rect(&mut self) -> Result<()> {
+		self.clip_stack.pop();
+		Ok(())
+	}
+
+	fn begin_mask(&mut self) -> Result<()> {
+		self.draw_queued();
+		self.renderer
+			.with_target(|target| target.mask.iter_mut().for_each(|m| *m = false));
+		self.mask_state = MaskState::Write;
+		Ok(())
+	}
+
+	fn set_mask_mode(&mut self, mode: MaskMode) -> Result<()> {
+		self.draw_queued();
+		self.mask_state = mode.into();
+		Ok(())
+	}
+
+	fn begin_sorted(&mut self) -> Result<()> {
+		self.draw_queue.begin();
+		Ok(())
+	}
+
+	fn set_draw_order(&mut self, layer: i32, sort_key: f32) -> Result<()> {
+		self.draw_queue.set_order(layer, sort_key);
+		Ok(())
+	}
+
+	fn end_sorted(&mut self) -> Result<()> {
+		self.draw_queued();
+		self.draw_queue.end();
+		Ok(())
+	}
+
+	fn draw(&mut self, renderable: &Renderable<'_, Renderer>) -> Result<()> {
+		self.render(
+			&renderable.texture,
+			&renderable.shader,
+			renderable.verts,
+			renderable.indices,
+			renderable.blend_mode,
+		);
+		Ok(())
+	}
+
+	fn fill_geometry(
+		&mut self,
+		verts: &[Vertex],
+		indices: &[u16],
+		blend_mode: BlendMode,
+	) -> Result<()> {
+		let renderer = self.renderer.clone();
+		let standard_res = renderer.standard_res();
+		self.render(
+			&standard_res.white_tex,
+			&standard_res.default_shader,
+			verts,
+			indices,
+			blend_mode,
+		);
+		Ok(())
+	}
+
+	fn present(mut self) -> Result<()> {
+		self.draw_queued();
+		Ok(())
+	}
+}
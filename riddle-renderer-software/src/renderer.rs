@@ -0,0 +1,126 @@
+use crate::*;
+
+use math::{SpacialNumericConversion, Vector2};
+use riddle_platform_common::WindowId;
+
+use std::sync::{Arc, Mutex};
+
+/// A CPU renderer which rasterizes in to an in memory [`image::Image`].
+///
+/// The renderer isn't associated with a window, and its contents can be retrieved at any
+/// time using [`Renderer::image`].
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_software::{common::Color, math::*, *};
+/// # fn main() -> Result<(), SoftwareRendererError> {
+/// let renderer = Renderer::new(vec2(100, 100));
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLUE)?;
+///     render_ctx.fill_circle(vec2(50.0, 50.0), 20.0, Color::GREEN)
+/// })?;
+///
+/// assert_eq!(Color::<u8>::GREEN, renderer.image().get_pixel([50, 50]));
+/// assert_eq!(Color::<u8>::BLUE, renderer.image().get_pixel([5, 5]));
+/// # Ok(()) }
+/// ```
+pub struct Renderer {
+	pub(crate) internal: Arc<RendererInternal>,
+}
+
+impl Renderer {
+	/// Create a new renderer, with a transparent black target image of the given dimensions.
+	pub fn new(dimensions: Vector2<u32>) -> Self {
+		let mut white_img = image::Image::new(1, 1);
+		white_img.set_pixel([0, 0], Color::from([0xFF; 4]));
+
+		let standard_res = StandardResources {
+			default_shader: Shader::default(),
+			white_tex: Texture::from_image(&white_img, &SpriteInitArgs::new()),
+		};
+
+		Self {
+			internal: Arc::new(RendererInternal {
+				target: Mutex::new(RenderTarget::new(dimensions)),
+				standard_res,
+			}),
+		}
+	}
+
+	/// The dimensions of the target image.
+	pub fn dimensions(&self) -> Vector2<f32> {
+		self.internal
+			.target
+			.lock()
+			.unwrap()
+			.image
+			.dimensions()
+			.convert()
+	}
+
+	/// Get a copy of the current contents of the target image.
+	pub fn image(&self) -> image::Image {
+		self.internal.target.lock().unwrap().image.clone()
+	}
+
+	pub(crate) fn standard_res(&self) -> &StandardResources {
+		&self.internal.standard_res
+	}
+
+	pub(crate) fn with_target<R, F: FnOnce(&mut RenderTarget) -> R>(&self, f: F) -> R {
+		let mut target = self.internal.target.lock().unwrap();
+		f(&mut target)
+	}
+}
+
+impl CommonRenderer for Renderer {
+	type RenderContext = SoftwareRenderContext;
+	type Sprite = Sprite;
+	type Texture = Texture;
+	type Shader = Shader;
+	type SpriteFont = SpriteFont<Self>;
+	type Error = SoftwareRendererError;
+
+	fn dimensions(&self) -> Vector2<f32> {
+		Renderer::dimensions(self)
+	}
+
+	/// Software renderers aren't associated with a window, so always report the same id.
+	fn window_id(&self) -> WindowId {
+		WindowId::new(u32::MAX)
+	}
+
+	fn render<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(&mut Self::RenderContext) -> Result<R>,
+	{
+		let mut ctx = SoftwareRenderContext::new(self);
+		let result = f(&mut ctx)?;
+		ctx.present()?;
+		Ok(result)
+	}
+
+	fn distance_field_shader(&self, params: &DistanceFieldParams) -> Result<Shader> {
+		Ok(Shader::distance_field(params))
+	}
+}
+
+impl Clone for Renderer {
+	fn clone(&self) -> Self {
+		Self {
+			internal: self.internal.clone(),
+		}
+	}
+}
+
+pub(crate) struct StandardResources {
+	pub default_shader: Shader,
+	pub white_tex: Texture,
+}
+
+pub(crate) struct RendererInternal {
+	target: Mutex<RenderTarget>,
+	standard_res: StandardResources,
+}
@@ -0,0 +1,184 @@
+use crate::*;
+
+use math::Vector2;
+
+use std::sync::Arc;
+
+/// The values available to a [`Shader`] when shading a single pixel.
+#[derive(Clone, Debug)]
+pub struct Fragment {
+	/// The center of the pixel being shaded, in target pixel coordinates.
+	pub position: Vector2<f32>,
+
+	/// The interpolated texture coordinate.
+	pub uv: Vector2<f32>,
+
+	/// The interpolated vertex color.
+	pub color: Color<f32>,
+
+	/// The texture color sampled at `uv` with the texture's filter mode.
+	pub texel: Color<f32>,
+}
+
+/// A CPU fragment shader, mapping a [`Fragment`] to the color which will be blended in to
+/// the target.
+///
+/// The default shader multiplies the texel by the vertex color, matching the default shader
+/// of `riddle-renderer-wgpu`.
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_software::{common::Color, *};
+/// // Render sprites as flat white silhouettes
+/// let flash = Shader::new(|fragment| Color::rgba(1.0, 1.0, 1.0, fragment.texel.a));
+/// ```
+pub struct Shader {
+	internal: Arc<ShaderFn>,
+}
+
+type ShaderFn = dyn Fn(&Fragment) -> Color<f32> + Send + Sync;
+
+impl Shader {
+	/// Build a shader from a function which computes the color of a fragment.
+	pub fn new<F>(f: F) -> Self
+	where
+		F: Fn(&Fragment) -> Color<f32> + Send + Sync + 'static,
+	{
+		Self {
+			internal: Arc::new(f),
+		}
+	}
+
+	/// A shader for textures whose alpha channel stores a distance field, which draws as
+	/// described by [`DistanceFieldParams`].
+	///
+	/// Fragments don't carry screen space derivatives, so the edges are anti aliased over
+	/// [`DistanceFieldParams::pixel_distance`], which is exact when the text is drawn at the
+	/// size it was generated at.
+	pub(crate) fn distance_field(params: &DistanceFieldParams) -> Self {
+		let params = params.clone();
+		Self::new(move |fragment| {
+			let field = fragment.texel.a;
+			let color = &fragment.color;
+			let aa = (params.pixel_distance * 0.5).max(0.0001);
+
+			let fill_alpha = smoothstep(0.5 - aa - params.softness, 0.5 + aa, field);
+			let outline_edge = 0.5 - params.outline_width;
+			let outline_alpha = smoothstep(
+				outline_edge - aa - params.softness,
+				outline_edge + aa,
+				field,
+			);
+			let glow_alpha = if params.glow_width > 0.0 {
+				smoothstep(outline_edge - params.glow_width, outline_edge, field)
+			} else {
+				0.0
+			};
+
+			let fill = premultiply(color, fill_alpha);
+			let outline = premultiply(&params.outline_color, outline_alpha * color.a);
+			let glow = premultiply(&params.glow_color, glow_alpha * color.a);
+			let result = over(fill, over(outline, glow));
+
+			if result.a <= 0.0 {
+				Color::rgba(0.0, 0.0, 0.0, 0.0)
+			} else {
+				Color::rgba(
+					result.r / result.a,
+					result.g / result.a,
+					result.b / result.a,
+					result.a,
+				)
+			}
+		})
+	}
+
+	pub(crate) fn same_shader(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.internal, &other.internal)
+	}
+
+	#[inline]
+	pub(crate) fn shade(&self, fragment: &Fragment) -> Color<f32> {
+		(self.internal)(fragment)
+	}
+}
+
+impl Default for Shader {
+	fn default() -> Self {
+		Self::new(|fragment| {
+			let Fragment { texel, color, .. } = fragment;
+			Color::rgba(
+				texel.r * color.r,
+				texel.g * color.g,
+				texel.b * color.b,
+				texel.a * color.a,
+			)
+		})
+	}
+}
+
+impl Clone for Shader {
+	fn clone(&self) -> Self {
+		Self {
+			internal: self.internal.clone(),
+		}
+	}
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * (3.0 - 2.0 * t)
+}
+
+fn premultiply(color: &Color<f32>, alpha: f32) -> Color<f32> {
+	let a = color.a * alpha;
+	Color::rgba(color.r * a, color.g * a, color.b * a, a)
+}
+
+/// Place the premultiplied color `a` over `b`.
+fn over(a: Color<f32>, b: Color<f32>) -> Color<f32> {
+	let inv = 1.0 - a.a;
+	Color::rgba(
+		a.r + b.r * inv,
+		a.g + b.g * inv,
+		a.b + b.b * inv,
+		a.a + b.a * inv,
+	)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn shade_distance(shader: &Shader, distance: f32) -> Color<f32> {
+		shader.shade(&Fragment {
+			position: Vector2::new(0.0, 0.0),
+			uv: Vector2::new(0.0, 0.0),
+			color: Color::rgba(1.0, 0.0, 0.0, 1.0),
+			texel: Color::rgba(1.0, 1.0, 1.0, distance),
+		})
+	}
+
+	#[test]
+	fn distance_field_layers_fill_over_outline() {
+		let shader = Shader::distance_field(&DistanceFieldParams {
+			outline_color: Color::rgba(0.0, 0.0, 1.0, 1.0),
+			outline_width: 0.2,
+			glow_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+			glow_width: 0.0,
+			softness: 0.0,
+			pixel_distance: 0.05,
+		});
+
+		assert_eq!(
+			Color::rgba(1.0, 0.0, 0.0, 1.0),
+			shade_distance(&shader, 0.9)
+		);
+		assert_eq!(
+			Color::rgba(0.0, 0.0, 1.0, 1.0),
+			shade_distance(&shader, 0.4)
+		);
+		assert_eq!(0.0, shade_distance(&shader, 0.1).a);
+	}
+}
@@ -0,0 +1,208 @@
+use math::{vec2, Rect, SpacialNumericConversion, Vector2};
+
+use crate::*;
+
+/// A renderable region of a texture.
+///
+/// Multiple sprites can share a single texture. Sprites store a reference to the
+/// [`Renderer`] which built them.
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_software::{common::Color, image::*, math::*, *};
+/// # fn main() -> Result<(), SoftwareRendererError> {
+/// let renderer = Renderer::new(vec2(8, 8));
+///
+/// let mut img = Image::new(2, 2);
+/// img.fill(Color::<u8>::GREEN);
+/// let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new())?;
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     sprite.render_at(render_ctx, vec2(2.0, 2.0))
+/// })?;
+///
+/// assert_eq!(Color::<u8>::GREEN, renderer.image().get_pixel([3, 3]));
+/// assert_eq!(Color::<u8>::BLACK, renderer.image().get_pixel([4, 4]));
+/// # Ok(()) }
+/// ```
+pub struct Sprite {
+	renderer: Renderer,
+	texture: Texture,
+	source_rect: Rect<f32>,
+}
+
+impl CommonSprite<Renderer> for Sprite {
+	fn new_from_image(
+		renderer: &Renderer,
+		img: &image::Image,
+		init_args: &SpriteInitArgs,
+	) -> Result<Self> {
+		let texture = Texture::from_image(img, init_args);
+		Ok(Sprite {
+			renderer: renderer.clone(),
+			source_rect: Rect::new(vec2(0.0, 0.0), texture.dimensions().convert()),
+			texture,
+		})
+	}
+
+	fn subsprite(&self, source_rect: &Rect<f32>) -> Self {
+		let mut translated_source = source_rect.clone();
+		translated_source.location += self.source_rect.location;
+
+		Sprite {
+			renderer: self.renderer.clone(),
+			texture: self.texture.clone(),
+			source_rect: self
+				.source_rect
+				.intersect(&translated_source)
+				.unwrap_or_else(|| Rect::new(self.source_rect.location, vec2(0.0, 0.0))),
+		}
+	}
+
+	fn dimensions(&self) -> Vector2<f32> {
+		self.source_rect.dimensions
+	}
+
+	fn render_regions<Ctx: RenderContext<Renderer> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		parts: &[(Rect<f32>, Vector2<f32>)],
+	) -> Result<()> {
+		self.render_regions_with_shader(
+			render_ctx,
+			args,
+			&self.renderer.standard_res().default_shader,
+			parts,
+		)
+	}
+
+	fn render_regions_with_shader<Ctx: RenderContext<Renderer> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		shader: &Shader,
+		parts: &[(Rect<f32>, Vector2<f32>)],
+	) -> Result<()> {
+		for parts in parts.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_regions_geometry(
+				self.texture.dimensions().convert(),
+				&self.source_rect,
+				args,
+				parts,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode: args.blend_mode,
+			};
+
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
+	}
+
+	fn render_stretched_regions<Ctx: RenderContext<Renderer> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		parts: &[(Rect<f32>, Rect<f32>)],
+	) -> Result<()> {
+		for parts in parts.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_stretched_regions_geometry(
+				self.texture.dimensions().convert(),
+				&self.source_rect,
+				args,
+				parts,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: self.renderer.standard_res().default_shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode: args.blend_mode,
+			};
+
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
+	}
+
+	fn render_quads<Ctx: RenderContext<Renderer> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		blend_mode: BlendMode,
+		quads: &[SpriteQuad],
+	) -> Result<()> {
+		for quads in quads.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_quads_geometry(
+				self.texture.dimensions().convert(),
+				&self.source_rect,
+				quads,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: self.renderer.standard_res().default_shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode,
+			};
+
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
+	}
+}
+
+impl Clone for Sprite {
+	fn clone(&self) -> Self {
+		Self {
+			renderer: self.renderer.clone(),
+			texture: self.texture.clone(),
+			source_rect: self.source_rect.clone(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn nine_slice_splits_large_tiled_center() {
+		let renderer = Renderer::new(vec2(150, 150));
+
+		let mut img = image::Image::new(3, 3);
+		img.fill(Color::<u8>::RED);
+		img.set_pixel([1, 1], Color::<u8>::GREEN);
+		let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new()).unwrap();
+
+		// The 1x1 texel center is tiled 148 * 148 times, more than fit in one renderable
+		let panel = NineSliceSprite::new(sprite, NineSliceInsets::uniform(1.0))
+			.with_center_mode(NineSliceMode::Tile);
+		assert!(148 * 148 > MAX_QUADS_PER_RENDERABLE);
+
+		renderer
+			.render(|render_ctx| {
+				render_ctx.clear(Color::BLACK)?;
+				panel.render_rect(render_ctx, &Rect::new(vec2(0.0, 0.0), vec2(150.0, 150.0)))
+			})
+			.unwrap();
+
+		let image = renderer.image();
+		for y in 1..149 {
+			for x in 1..149 {
+				assert_eq!(Color::<u8>::GREEN, image.get_pixel([x, y]));
+			}
+		}
+		assert_eq!(Color::<u8>::RED, image.get_pixel([0, 0]));
+		assert_eq!(Color::<u8>::RED, image.get_pixel([149, 149]));
+	}
+}
@@ -0,0 +1,142 @@
+use crate::*;
+
+use math::Vector2;
+
+use std::sync::Arc;
+
+/// An image which can be sampled by the software renderer.
+///
+/// Textures are immutable once created, and are cheap to clone.
+pub struct Texture {
+	pub(crate) internal: Arc<TextureInternal>,
+}
+
+impl Texture {
+	pub(crate) fn from_image(image: &image::Image, init_args: &SpriteInitArgs) -> Self {
+		let mut levels = vec![image.clone()];
+		if init_args.mipmaps {
+			levels.extend(image::filters::mipmap_chain(image));
+		}
+
+		Self {
+			internal: Arc::new(TextureInternal {
+				levels,
+				mag_filter: init_args.mag_filter,
+				min_filter: init_args.min_filter,
+				mipmap_filter: init_args.mipmap_filter,
+				address_mode_u: init_args.address_mode_u,
+				address_mode_v: init_args.address_mode_v,
+			}),
+		}
+	}
+
+	pub(crate) fn dimensions(&self) -> Vector2<u32> {
+		self.internal.levels[0].dimensions()
+	}
+
+	/// Sample the texture, resolving coordinates outside of the texture with its address modes.
+	///
+	/// The level of detail selects the mip level, where 0 is the full size image and each
+	/// following level is half the size. It is ignored if the texture has no mipmaps.
+	pub(crate) fn sample(&self, uv: Vector2<f32>, filter: FilterMode, lod: f32) -> Color<f32> {
+		let max_level = self.internal.levels.len() - 1;
+		let lod = lod.clamp(0.0, max_level as f32);
+		match self.internal.mipmap_filter {
+			FilterMode::Nearest => self.sample_level(lod.round() as usize, uv, filter),
+			FilterMode::Linear => {
+				let level = lod.floor() as usize;
+				let t = lod - level as f32;
+				let near = self.sample_level(level, uv, filter);
+				if t == 0.0 {
+					return near;
+				}
+				let far = self.sample_level((level + 1).min(max_level), uv, filter);
+				Color::rgba(
+					near.r + (far.r - near.r) * t,
+					near.g + (far.g - near.g) * t,
+					near.b + (far.b - near.b) * t,
+					near.a + (far.a - near.a) * t,
+				)
+			}
+		}
+	}
+
+	fn sample_level(&self, level: usize, uv: Vector2<f32>, filter: FilterMode) -> Color<f32> {
+		let img = &self.internal.levels[level];
+		let (width, height) = (img.width(), img.height());
+		if width == 0 || height == 0 {
+			return Color::ZERO;
+		}
+
+		let x = uv.x * width as f32;
+		let y = uv.y * height as f32;
+
+		match filter {
+			FilterMode::Nearest => self.texel(img, x.floor() as i64, y.floor() as i64),
+			FilterMode::Linear => {
+				let x = x - 0.5;
+				let y = y - 0.5;
+				let (x0, y0) = (x.floor(), y.floor());
+				let (fx, fy) = (x - x0, y - y0);
+				let (x0, y0) = (x0 as i64, y0 as i64);
+
+				let lerp = |a: Color<f32>, b: Color<f32>, t: f32| {
+					Color::rgba(
+						a.r + (b.r - a.r) * t,
+						a.g + (b.g - a.g) * t,
+						a.b + (b.b - a.b) * t,
+						a.a + (b.a - a.a) * t,
+					)
+				};
+
+				let top = lerp(self.texel(img, x0, y0), self.texel(img, x0 + 1, y0), fx);
+				let bottom = lerp(
+					self.texel(img, x0, y0 + 1),
+					self.texel(img, x0 + 1, y0 + 1),
+					fx,
+				);
+				lerp(top, bottom, fy)
+			}
+		}
+	}
+
+	fn texel(&self, img: &image::Image, x: i64, y: i64) -> Color<f32> {
+		let x = address(x, img.width() as i64, self.internal.address_mode_u) as u32;
+		let y = address(y, img.height() as i64, self.internal.address_mode_v) as u32;
+		img.get_pixel([x, y]).convert()
+	}
+}
+
+/// Map a texel coordinate on to the texture, which is `size` texels across.
+fn address(coord: i64, size: i64, mode: AddressMode) -> i64 {
+	match mode {
+		AddressMode::ClampToEdge => coord.clamp(0, size - 1),
+		AddressMode::Repeat => coord.rem_euclid(size),
+		AddressMode::MirrorRepeat => {
+			let coord = coord.rem_euclid(size * 2);
+			if coord < size {
+				coord
+			} else {
+				size * 2 - 1 - coord
+			}
+		}
+	}
+}
+
+impl Clone for Texture {
+	fn clone(&self) -> Self {
+		Self {
+			internal: self.internal.clone(),
+		}
+	}
+}
+
+pub(crate) struct TextureInternal {
+	/// The full size image, followed by any mip levels.
+	pub levels: Vec<image::Image>,
+	pub mag_filter: FilterMode,
+	pub min_filter: FilterMode,
+	pub mipmap_filter: FilterMode,
+	pub address_mode_u: AddressMode,
+	pub address_mode_v: AddressMode,
+}
@@ -2,13 +2,13 @@ use std::sync::Arc;
 
 use crate::*;
 
-use math::Rect;
-use wgpu::util::DeviceExt;
+use math::{Rect, Vector2};
 
 #[derive(Clone)]
 pub struct BufferedRenderArgs {
 	pub(crate) texture: Texture,
 	pub(crate) shader: Shader,
+	pub(crate) blend_mode: BlendMode,
 }
 
 impl BufferedRenderArgs {
@@ -16,6 +16,7 @@ impl BufferedRenderArgs {
 		Self {
 			texture: desc.texture.clone(),
 			shader: desc.shader.clone(),
+			blend_mode: desc.blend_mode,
 		}
 	}
 }
@@ -23,7 +24,8 @@ impl BufferedRenderArgs {
 impl PartialEq for BufferedRenderArgs {
 	fn eq(&self, other: &Self) -> bool {
 		Arc::ptr_eq(&self.texture.internal, &other.texture.internal)
-			&& Arc::ptr_eq(&self.shader.internal, &other.shader.internal)
+			&& self.shader.same_binding(&other.shader)
+			&& self.blend_mode == other.blend_mode
 	}
 }
 
@@ -38,10 +40,26 @@ where
 	current_args: Option<BufferedRenderArgs>,
 
 	verts: Vec<Vertex>,
-	indices: Vec<u16>,
+	indices: BatchIndices,
+	streams: StreamBuffers,
+
+	// Draws using the default shader are batched across multiple textures. If the current
+	// batch is one of these, these hold the batch's textures and each vertex's texture slot.
+	batch_textures: Vec<Texture>,
+	texture_slots: Vec<u32>,
 
 	pending_clear_color: Option<[f32; 4]>,
+
+	// If the target is sRGB, colors are converted to linear space before they reach the
+	// shaders, so the target's sRGB encoding gives back the colors which were asked for.
+	linear_colors: bool,
+
 	view_matrix: mint::ColumnMatrix4<f32>,
+	clip_stack: Vec<Rect<f32>>,
+	stencil_mode: StencilMode,
+	pending_mask_clear: bool,
+	draw_queue: DrawQueue<Texture, Shader>,
+	stats: RenderStats,
 
 	encoder: wgpu::CommandEncoder,
 
@@ -55,27 +73,38 @@ where
 {
 	pub fn new(target_desc: R, encoder: wgpu::CommandEncoder) -> Result<Self> {
 		target_desc.begin_render()?;
+		let streams = target_desc.renderer().take_stream_buffers()?;
 		let identity: mint::ColumnMatrix4<f32> = glam::Mat4::IDENTITY.into();
+		let linear_colors = is_srgb_format(target_desc.format());
 		Ok(Self {
 			target_desc,
 			current_args: None,
 			verts: vec![],
-			indices: vec![],
+			indices: BatchIndices::default(),
+			streams,
+			batch_textures: vec![],
+			texture_slots: vec![],
 			pending_clear_color: None,
+			linear_colors,
 			view_matrix: identity,
+			clip_stack: vec![],
+			stencil_mode: StencilMode::Disabled,
+			pending_mask_clear: false,
+			draw_queue: DrawQueue::new(),
+			stats: RenderStats::default(),
 			encoder,
 			device_marker: Default::default(),
 		})
 	}
 
 	fn clear_immediate(&mut self, color: Color<f32>) -> Result<()> {
-		self.flush()?;
+		self.flush_for(FlushReason::Clear)?;
 		let encoder = &mut self.encoder;
-		self.target_desc.with_view(|view| {
+		self.target_desc.with_views(|view, resolve_target, _| {
 			let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 				color_attachments: &[wgpu::RenderPassColorAttachment {
 					view,
-					resolve_target: None,
+					resolve_target,
 					ops: wgpu::Operations {
 						load: wgpu::LoadOp::Clear(wgpu::Color {
 							r: color.r as f64,
@@ -94,9 +123,22 @@ where
 	}
 
 	pub fn flush(&mut self) -> Result<()> {
+		self.flush_for(FlushReason::Explicit)
+	}
+
+	/// The work done by this render context so far.
+	pub fn stats(&self) -> RenderStats {
+		let mut stats = self.stats.clone();
+		stats.bytes_uploaded = self.streams.bytes_written();
+		stats
+	}
+
+	/// Draw the pending batch, if there is one, recording why it was drawn.
+	fn flush_for(&mut self, reason: FlushReason) -> Result<()> {
 		match &self.current_args {
 			Some(args) => {
 				let args = args.clone();
+				self.stats.record_flush(reason);
 				self.do_flush(&args)
 			}
 			_ => Ok(()),
@@ -104,34 +146,163 @@ where
 	}
 
 	fn do_flush(&mut self, args: &BufferedRenderArgs) -> Result<()> {
+		// Multi texture batches are drawn with the batch shader, which selects the texture to
+		// sample from using each vertex's texture slot.
+		let batch_textures = std::mem::take(&mut self.batch_textures);
+		let is_multi_texture = !batch_textures.is_empty();
+		let shader = if is_multi_texture {
+			self.target_desc.standard_resources().batch_shader.clone()
+		} else {
+			args.shader.clone()
+		};
+		let textures = if is_multi_texture {
+			batch_textures.iter().collect::<Vec<_>>()
+		} else {
+			vec![&args.texture]
+		};
+
 		let renderer = self.target_desc.renderer().clone();
+		let (vertex_slice, slot_slice, index_slice) =
+			renderer.wgpu_device().with_device_info(|info| {
+				let streams = &mut self.streams;
+				let vertex_slice = streams.vertex.write(
+					info.device,
+					info.queue,
+					bytemuck::cast_slice(&self.verts),
+				);
+				let slot_slice = if is_multi_texture {
+					Some(streams.vertex.write(
+						info.device,
+						info.queue,
+						bytemuck::cast_slice(&self.texture_slots),
+					))
+				} else {
+					None
+				};
+				let index_slice =
+					streams
+						.index
+						.write(info.device, info.queue, self.indices.as_bytes());
+				Ok((vertex_slice, slot_slice, index_slice))
+			})?;
 
-		let verts = std::mem::take(&mut self.verts);
-		let indices = std::mem::take(&mut self.indices);
+		let mut vertex_buffers = vec![vertex_slice.slice()];
+		if let Some(slot_slice) = &slot_slice {
+			vertex_buffers.push(slot_slice.slice());
+		}
+
+		self.encode_draw(
+			&shader,
+			&textures[..],
+			args.blend_mode,
+			&vertex_buffers[..],
+			index_slice.slice(),
+			self.indices.format(),
+			self.indices.len() as u32,
+		)?;
+		self.stats
+			.record_draw(self.verts.len() as u64, self.indices.len() as u64);
+
+		self.verts.clear();
+		self.indices.clear();
+		self.texture_slots.clear();
+		self.current_args = None;
+		Ok(())
+	}
+
+	/// Draw geometry which is held in buffers owned by the caller, rather than batched by the
+	/// renderer. Any pending batch is flushed first.
+	///
+	/// The geometry can't be queued while buffering sorted draws, so any buffered sorted draws
+	/// are drawn first, splitting the sorted sequence like [`RenderContext::clear`] does.
+	///
+	/// The vertices are in the [`Vertex`] layout, and are drawn with the default shader and
+	/// the current transform, clip rect and mask.
+	#[allow(clippy::too_many_arguments)]
+	pub(crate) fn draw_buffers(
+		&mut self,
+		texture: &Texture,
+		blend_mode: BlendMode,
+		vertex_buffer: wgpu::BufferSlice,
+		vertex_count: u32,
+		index_buffer: wgpu::BufferSlice,
+		index_format: wgpu::IndexFormat,
+		index_count: u32,
+	) -> Result<()> {
+		self.draw_queued()?;
+		self.flush_for(FlushReason::BufferDraw)?;
+
+		let standard_resources = self.target_desc.standard_resources();
+		let (shader, blend_mode) = if self.stencil_mode == StencilMode::Write {
+			(standard_resources.mask_shader.clone(), BlendMode::Replace)
+		} else {
+			(standard_resources.default_shader.clone(), blend_mode)
+		};
+
+		self.encode_draw(
+			&shader,
+			&[texture],
+			blend_mode,
+			&[vertex_buffer],
+			index_buffer,
+			index_format,
+			index_count,
+		)?;
+		self.stats
+			.record_draw(vertex_count as u64, index_count as u64);
+		Ok(())
+	}
+
+	/// Encode a render pass which draws indexed geometry with the current transform, clip rect
+	/// and stencil state, applying any pending clears.
+	#[allow(clippy::too_many_arguments)]
+	fn encode_draw(
+		&mut self,
+		shader: &Shader,
+		textures: &[&Texture],
+		blend_mode: BlendMode,
+		vertex_buffers: &[wgpu::BufferSlice],
+		index_buffer: wgpu::BufferSlice,
+		index_format: wgpu::IndexFormat,
+		index_count: u32,
+	) -> Result<()> {
+		let renderer = self.target_desc.renderer().clone();
 		let pending_clear = self.pending_clear_color.take();
+		let pending_mask_clear = std::mem::take(&mut self.pending_mask_clear);
+		let scissor_rect = self.clip_stack.last().map(|clip| self.scissor_rect(clip));
 
 		renderer.wgpu_device().with_device_info(|info| {
-			let vertex_buf = info
-				.device
-				.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-					label: None,
-					contents: bytemuck::cast_slice(&verts),
-					usage: wgpu::BufferUsage::VERTEX,
-				});
-
-			let index_buf = info
-				.device
-				.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-					label: None,
-					contents: bytemuck::cast_slice(&indices),
-					usage: wgpu::BufferUsage::INDEX,
-				});
-
-			let bind_group = args.shader.internal.bind_params(
+			let streams = &mut self.streams;
+
+			let camera_matrix = camera_matrix(self.target_desc.dimensions(), self.view_matrix);
+			let camera_slice = streams.uniform.write(
+				info.device,
+				info.queue,
+				bytemuck::cast_slice(camera_matrix.as_ref()),
+			);
+
+			let user_bind_group = shader.params.as_ref().and_then(|params| {
+				shader.internal.bind_user_params(
+					info.device,
+					info.queue,
+					&mut streams.uniform,
+					params,
+				)
+			});
+
+			let bind_group =
+				shader
+					.internal
+					.bind_params(info.device, camera_slice.binding(), textures);
+
+			let pipeline = shader.internal.pipeline(
 				info.device,
-				self.target_desc.dimensions(),
-				self.view_matrix,
-				&args.texture,
+				PipelineKey {
+					blend_mode,
+					stencil_mode: self.stencil_mode,
+					sample_count: self.target_desc.sample_count(),
+					format: self.target_desc.format(),
+				},
 			);
 
 			let load_op = match pending_clear {
@@ -143,29 +314,117 @@ where
 				}),
 				None => wgpu::LoadOp::Load,
 			};
+			let stencil_load_op = if pending_mask_clear {
+				wgpu::LoadOp::Clear(0)
+			} else {
+				wgpu::LoadOp::Load
+			};
 
-			// Scope render pass so that the render is encoded before the buffers are cleared.
 			let encoder = &mut self.encoder;
-			let indices_len = indices.len() as u32;
-			self.target_desc.with_view(|view| {
-				let mut rpass = args
-					.shader
-					.internal
-					.begin_render_pass(view, encoder, load_op);
-
-				rpass.set_bind_group(0, &bind_group, &[]);
+			self.target_desc
+				.with_views(|view, resolve_target, stencil_view| {
+					let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+						color_attachments: &[wgpu::RenderPassColorAttachment {
+							view,
+							resolve_target,
+							ops: wgpu::Operations {
+								load: load_op,
+								store: true,
+							},
+						}],
+						depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+							view: stencil_view,
+							depth_ops: None,
+							stencil_ops: Some(wgpu::Operations {
+								load: stencil_load_op,
+								store: true,
+							}),
+						}),
+						label: None,
+					});
+
+					rpass.set_pipeline(&pipeline);
+					rpass.set_stencil_reference(STENCIL_MASK_VALUE);
+					rpass.set_bind_group(0, &bind_group, &[]);
+					if let Some(user_bind_group) = &user_bind_group {
+						rpass.set_bind_group(1, user_bind_group, &[]);
+					}
+
+					if let Some((x, y, width, height)) = scissor_rect {
+						if width == 0 || height == 0 {
+							return Ok(());
+						}
+						rpass.set_scissor_rect(x, y, width, height);
+					}
+
+					for (slot, vertex_buffer) in vertex_buffers.iter().enumerate() {
+						rpass.set_vertex_buffer(slot as u32, *vertex_buffer);
+					}
+					rpass.set_index_buffer(index_buffer, index_format);
+					rpass.draw_indexed(0..index_count, 0, 0..1);
+					Ok(())
+				})
+		})
+	}
 
-				rpass.set_vertex_buffer(0, vertex_buf.slice(..));
-				rpass.set_index_buffer(index_buf.slice(..), wgpu::IndexFormat::Uint16);
-				rpass.draw_indexed(0..indices_len, 0, 0..1);
-				Ok(())
-			})?;
+	/// The region of the world which is visible in the target with the current transform.
+	pub(crate) fn visible_rect(&self) -> Rect<f32> {
+		let dimensions = self.target_desc.dimensions();
+		let inverse = glam::Mat4::from(self.view_matrix).inverse();
+		let corners = [
+			glam::vec3(0.0, 0.0, 0.0),
+			glam::vec3(dimensions.x, 0.0, 0.0),
+			glam::vec3(0.0, dimensions.y, 0.0),
+			glam::vec3(dimensions.x, dimensions.y, 0.0),
+		]
+		.iter()
+		.map(|corner| inverse.transform_point3(*corner).truncate())
+		.collect::<Vec<_>>();
+
+		let min = corners
+			.iter()
+			.fold(corners[0], |min, corner| min.min(*corner));
+		let max = corners
+			.iter()
+			.fold(corners[0], |max, corner| max.max(*corner));
+		Rect::new(
+			Vector2::new(min.x, min.y),
+			Vector2::new(max.x - min.x, max.y - min.y),
+		)
+	}
 
-			self.verts.clear();
-			self.indices.clear();
-			self.current_args = None;
-			Ok(())
-		})
+	/// Convert a clip rect in target coordinates to a scissor rect in physical pixels, clamped
+	/// to the target.
+	fn scissor_rect(&self, clip: &Rect<f32>) -> (u32, u32, u32, u32) {
+		let dimensions = self.target_desc.dimensions();
+		let physical_dimensions = self.target_desc.physical_dimensions();
+		let scale = Vector2::new(
+			physical_dimensions.x as f32 / dimensions.x,
+			physical_dimensions.y as f32 / dimensions.y,
+		);
+
+		let to_physical =
+			|value: f32, scale: f32, max: u32| ((value * scale).round().max(0.0) as u32).min(max);
+
+		let min_x = to_physical(clip.location.x, scale.x, physical_dimensions.x);
+		let min_y = to_physical(clip.location.y, scale.y, physical_dimensions.y);
+		let max_x = to_physical(
+			clip.location.x + clip.dimensions.x,
+			scale.x,
+			physical_dimensions.x,
+		);
+		let max_y = to_physical(
+			clip.location.y + clip.dimensions.y,
+			scale.y,
+			physical_dimensions.y,
+		);
+
+		(
+			min_x,
+			min_y,
+			max_x.saturating_sub(min_x),
+			max_y.saturating_sub(min_y),
+		)
 	}
 
 	pub fn buffered_render(
@@ -174,21 +433,172 @@ where
 		verts: &[Vertex],
 		indices: &[u16],
 	) -> Result<()> {
-		if Some(args) != self.current_args.as_ref() {
-			self.flush()?;
+		if args.shader.internal.user_bind_group_layout.is_some() && args.shader.params.is_none() {
+			return Err(WgpuRendererError::ShaderParams(
+				"Shader requires params to be bound before rendering",
+			));
+		}
+
+		if self.draw_queue.is_active() {
+			self.draw_queue.push(
+				args.texture.clone(),
+				args.shader.clone(),
+				args.blend_mode,
+				verts,
+				indices,
+				self.view_matrix.into(),
+				self.clip_stack.last().cloned(),
+				|a, b| {
+					Arc::ptr_eq(&a.texture.internal, &b.texture.internal)
+						&& a.shader.same_binding(&b.shader)
+						&& a.blend_mode == b.blend_mode
+				},
+			);
+			return Ok(());
+		}
+
+		self.batch_render(args, verts, indices)
+	}
+
+	/// Add a draw to the current batch, flushing the batch first if the draw can't be added
+	/// to it.
+	fn batch_render(
+		&mut self,
+		args: &BufferedRenderArgs,
+		verts: &[Vertex],
+		indices: &[u16],
+	) -> Result<()> {
+		// Draws to the mask only need the texture's alpha, so use the mask shader in place of
+		// the renderable's shader.
+		let mask_args;
+		let args = if self.stencil_mode == StencilMode::Write {
+			mask_args = BufferedRenderArgs {
+				texture: args.texture.clone(),
+				shader: self.target_desc.standard_resources().mask_shader.clone(),
+				blend_mode: BlendMode::Replace,
+			};
+			&mask_args
+		} else {
+			args
+		};
+
+		if indices.is_empty() {
+			return Ok(());
+		}
+
+		let is_default_shader = args
+			.shader
+			.same_binding(&self.target_desc.standard_resources().default_shader);
+
+		if is_default_shader {
+			let slot = self.batch_texture_slot(args)?;
+			self.texture_slots
+				.resize(self.texture_slots.len() + verts.len(), slot);
+		} else if Some(args) != self.current_args.as_ref() {
+			if let Some(current) = &self.current_args {
+				let reason = if !self.batch_textures.is_empty()
+					|| !args.shader.same_binding(&current.shader)
+				{
+					FlushReason::ShaderChange
+				} else if args.blend_mode != current.blend_mode {
+					FlushReason::BlendModeChange
+				} else {
+					FlushReason::TextureChange
+				};
+				self.flush_for(reason)?;
+			}
 			self.current_args = Some(args.clone());
 		}
 
-		let indices: Vec<u16> = indices
-			.iter()
-			.map(|idx| idx + self.verts.len() as u16)
-			.collect();
+		let base_vertex = self.verts.len();
+		if self.linear_colors {
+			self.verts.extend(verts.iter().map(|vert| Vertex {
+				color: Color::from(vert.color).to_linear().into(),
+				..*vert
+			}));
+		} else {
+			self.verts.extend_from_slice(verts);
+		}
+		self.indices.extend(base_vertex, indices, self.verts.len());
+
+		Ok(())
+	}
 
-		self.verts.extend_from_slice(verts);
-		self.indices.extend_from_slice(&indices[..]);
+	/// Draw any draws queued while in sorted mode, in sorted order.
+	///
+	/// The queued vertices have already been transformed, and each draw carries its own clip
+	/// rect, so the transform and clip stack are replaced while drawing them.
+	fn draw_queued(&mut self) -> Result<()> {
+		let draws = self.draw_queue.take_sorted();
+		if draws.is_empty() {
+			return Ok(());
+		}
 
+		self.flush_for(FlushReason::SortedDraws)?;
+		let view_matrix = std::mem::replace(&mut self.view_matrix, glam::Mat4::IDENTITY.into());
+		let clip_stack = std::mem::take(&mut self.clip_stack);
+
+		for draw in draws {
+			if self.clip_stack.last() != draw.clip.as_ref() {
+				self.flush_for(FlushReason::ClipChange)?;
+				self.clip_stack = draw.clip.into_iter().collect();
+			}
+
+			self.batch_render(
+				&BufferedRenderArgs {
+					texture: draw.texture,
+					shader: draw.shader,
+					blend_mode: draw.blend_mode,
+				},
+				&draw.verts,
+				&draw.indices,
+			)?;
+		}
+
+		self.flush_for(FlushReason::SortedDraws)?;
+		self.view_matrix = view_matrix;
+		self.clip_stack = clip_stack;
 		Ok(())
 	}
+
+	/// Find the slot of the args' texture in the current multi texture batch, adding it to the
+	/// batch if there is a free slot. If the args can't be added to the current batch, it is
+	/// flushed and a new multi texture batch is started.
+	fn batch_texture_slot(&mut self, args: &BufferedRenderArgs) -> Result<u32> {
+		let can_extend_batch = !self.batch_textures.is_empty()
+			&& self
+				.current_args
+				.as_ref()
+				.map(|current| current.blend_mode == args.blend_mode)
+				.unwrap_or(false);
+
+		if can_extend_batch {
+			if let Some(slot) = self
+				.batch_textures
+				.iter()
+				.position(|texture| Arc::ptr_eq(&texture.internal, &args.texture.internal))
+			{
+				return Ok(slot as u32);
+			}
+
+			if self.batch_textures.len() < BATCH_TEXTURE_SLOTS as usize {
+				self.batch_textures.push(args.texture.clone());
+				return Ok(self.batch_textures.len() as u32 - 1);
+			}
+		}
+
+		let reason = if can_extend_batch {
+			FlushReason::TextureChange
+		} else if self.batch_textures.is_empty() {
+			FlushReason::ShaderChange
+		} else {
+			FlushReason::BlendModeChange
+		};
+		self.flush_for(reason)?;
+		self.current_args = Some(args.clone());
+		self.batch_textures.push(args.texture.clone());
+		Ok(0)
+	}
 }
 
 impl<Device, R> RenderContext<Renderer<Device>> for BufferedRenderer<Device, R>
@@ -197,7 +607,7 @@ where
 	R: WgpuRenderTargetDesc<Device>,
 {
 	fn set_transform(&mut self, transform: mint::ColumnMatrix4<f32>) -> Result<()> {
-		self.flush()?;
+		self.flush_for(FlushReason::TransformChange)?;
 		self.view_matrix = transform;
 		Ok(())
 	}
@@ -206,43 +616,86 @@ where
 	/// will be performed when the next batched render happens, or when `present` is called,
 	/// whichever comes first.
 	fn clear(&mut self, color: Color<f32>) -> Result<()> {
-		self.flush()?;
+		self.draw_queued()?;
+		self.flush_for(FlushReason::Clear)?;
+		let color = if self.linear_colors {
+			color.to_linear()
+		} else {
+			color
+		};
 		self.pending_clear_color = Some(color.into());
 		Ok(())
 	}
 
-	fn fill_rect(&mut self, rect: &Rect<f32>, color: Color<f32>) -> Result<()> {
-		let pos_topleft = glam::Vec2::from(rect.location);
-		let pos_topright = pos_topleft + glam::vec2(rect.dimensions.x, 0.0);
-		let pos_bottomleft = pos_topleft + glam::vec2(0.0, rect.dimensions.y);
-		let pos_bottomright = pos_bottomleft + glam::vec2(rect.dimensions.x, 0.0);
+	fn push_clip_rect(&mut self, rect: &Rect<f32>) -> Result<()> {
+		self.flush_for(FlushReason::ClipChange)?;
+		let clip = nested_clip_rect(self.clip_stack.last(), rect);
+		self.clip_stack.push(clip);
+		Ok(())
+	}
 
-		let color_arr: [f32; 4] = color.into();
+	fn pop_clip_rect(&mut self) -> Result<()> {
+		self.flush_for(FlushReason::ClipChange)?;
+		self.clip_stack.pop();
+		Ok(())
+	}
+
+	fn begin_mask(&mut self) -> Result<()> {
+		self.draw_queued()?;
+		self.flush_for(FlushReason::MaskChange)?;
+		self.pending_mask_clear = true;
+		self.stencil_mode = StencilMode::Write;
+		Ok(())
+	}
+
+	fn set_mask_mode(&mut self, mode: MaskMode) -> Result<()> {
+		self.draw_queued()?;
+		self.flush_for(FlushReason::MaskChange)?;
+		self.stencil_mode = mode.into();
+		Ok(())
+	}
 
-		let vertex_data = [
-			Vertex::ptc(pos_topleft, [0.0, 0.0], &color_arr),
-			Vertex::ptc(pos_bottomleft, [0.0, 0.0], &color_arr),
-			Vertex::ptc(pos_bottomright, [0.0, 0.0], &color_arr),
-			Vertex::ptc(pos_topright, [0.0, 0.0], &color_arr),
-		];
-		let index_data: &[u16] = &[1, 2, 0, 2, 0, 3];
+	fn begin_sorted(&mut self) -> Result<()> {
+		self.draw_queue.begin();
+		Ok(())
+	}
+
+	fn set_draw_order(&mut self, layer: i32, sort_key: f32) -> Result<()> {
+		self.draw_queue.set_order(layer, sort_key);
+		Ok(())
+	}
+
+	fn end_sorted(&mut self) -> Result<()> {
+		self.draw_queued()?;
+		self.draw_queue.end();
+		Ok(())
+	}
 
+	fn fill_geometry(
+		&mut self,
+		verts: &[Vertex],
+		indices: &[u16],
+		blend_mode: BlendMode,
+	) -> Result<()> {
 		self.buffered_render(
 			&BufferedRenderArgs {
 				texture: self.target_desc.standard_resources().white_tex.clone(),
 				shader: self.target_desc.standard_resources().default_shader.clone(),
+				blend_mode,
 			},
-			&vertex_data[..],
-			index_data,
+			verts,
+			indices,
 		)
 	}
 
 	fn present(mut self) -> Result<()> {
-		self.flush()?;
+		self.draw_queued()?;
+		self.flush_for(FlushReason::Present)?;
 		if let Some(clear_color) = self.pending_clear_color {
 			self.clear_immediate(clear_color.into())?;
 		}
 
+		let stats = self.stats();
 		let cmd = self.encoder.finish();
 		self.target_desc
 			.renderer()
@@ -252,6 +705,14 @@ where
 				Ok(())
 			})?;
 
+		self.target_desc.renderer().record_stats(&stats);
+
+		// All writes to the stream buffers have been submitted, so they can be reused.
+		self.streams.reset();
+		self.target_desc
+			.renderer()
+			.return_stream_buffers(self.streams);
+
 		self.target_desc.end_render();
 
 		Ok(())
@@ -265,3 +726,96 @@ where
 		)
 	}
 }
+
+/// The indices of the current batch.
+///
+/// Indices are stored as `u16` until the batch references more vertices than a `u16` can
+/// address, at which point the batch switches to `u32` indices.
+#[derive(Default)]
+struct BatchIndices {
+	narrow: Vec<u16>,
+	wide: Vec<u32>,
+	is_wide: bool,
+}
+
+impl BatchIndices {
+	/// Append a renderable's indices, offset by the index of its first vertex in the batch.
+	fn extend(&mut self, base_vertex: usize, indices: &[u16], vertex_count: usize) {
+		if !self.is_wide && vertex_count > u16::MAX as usize + 1 {
+			self.wide.extend(self.narrow.drain(..).map(u32::from));
+			self.is_wide = true;
+		}
+
+		if self.is_wide {
+			let base_vertex = base_vertex as u32;
+			self.wide
+				.extend(indices.iter().map(|idx| *idx as u32 + base_vertex));
+		} else {
+			let base_vertex = base_vertex as u16;
+			self.narrow
+				.extend(indices.iter().map(|idx| idx + base_vertex));
+		}
+	}
+
+	fn len(&self) -> usize {
+		if self.is_wide {
+			self.wide.len()
+		} else {
+			self.narrow.len()
+		}
+	}
+
+	fn format(&self) -> wgpu::IndexFormat {
+		if self.is_wide {
+			wgpu::IndexFormat::Uint32
+		} else {
+			wgpu::IndexFormat::Uint16
+		}
+	}
+
+	fn as_bytes(&self) -> &[u8] {
+		if self.is_wide {
+			bytemuck::cast_slice(&self.wide)
+		} else {
+			bytemuck::cast_slice(&self.narrow)
+		}
+	}
+
+	fn clear(&mut self) {
+		self.narrow.clear();
+		self.wide.clear();
+		self.is_wide = false;
+	}
+}
+
+fn camera_matrix(camera_size: Vector2<f32>, view_matrix: mint::ColumnMatrix4<f32>) -> glam::Mat4 {
+	let ortho_matrix =
+		glam::Mat4::orthographic_lh(0.0, camera_size.x, camera_size.y, 0.0, 0.0, 1.0);
+	let view_matrix: glam::Mat4 = view_matrix.into();
+	ortho_matrix * view_matrix
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn batch_indices_widen_past_u16_vertices() {
+		let mut indices = BatchIndices::default();
+		indices.extend(0, &[0, 1, 2], 3);
+		indices.extend(3, &[0, 1, 2], 6);
+		assert_eq!(wgpu::IndexFormat::Uint16, indices.format());
+		assert_eq!(vec![0, 1, 2, 3, 4, 5], indices.narrow);
+
+		// The batch now references 65,538 vertices, more than u16 indices can address
+		indices.extend(65_535, &[0, 1, 2], 65_538);
+		assert_eq!(wgpu::IndexFormat::Uint32, indices.format());
+		assert_eq!(9, indices.len());
+		assert_eq!(vec![0, 1, 2, 3, 4, 5, 65_535, 65_536, 65_537], indices.wide);
+		assert_eq!(9 * 4, indices.as_bytes().len());
+
+		indices.clear();
+		assert_eq!(wgpu::IndexFormat::Uint16, indices.format());
+		assert_eq!(0, indices.len());
+	}
+}
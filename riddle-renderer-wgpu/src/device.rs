@@ -5,7 +5,8 @@ use crate::{math::*, *};
 /// A [`Renderer`] compatible WGPU device.
 ///
 /// A default implementation exists for `riddle_platform_winit::Window`
-/// in [`WindowWgpuDevice`].
+/// in [`WindowWgpuDevice`], and for offscreen rendering without a window in
+/// [`HeadlessWgpuDevice`].
 ///
 /// The application may implement this trait to layer the renderer on
 /// top of custom WGPU renderer.
@@ -35,7 +36,7 @@ use crate::{math::*, *};
 /// #   fn end_frame(&self) { todo!() }
 /// #   fn viewport_dimensions(&self) -> math::Vector2<f32>  { todo!() }
 /// #   fn with_device_info<R, F: FnOnce(&WgpuDeviceInfo) -> Result<R, WgpuRendererError>>(&self, f: F) -> Result<R, WgpuRendererError> { todo!() }
-/// #   fn with_frame<R, F: FnOnce(&wgpu::SwapChainFrame) -> Result<R, WgpuRendererError>>(&self, f: F) -> Result<R, WgpuRendererError> { todo!() }
+/// #   fn with_frame_view<R, F: FnOnce(&wgpu::TextureView) -> Result<R, WgpuRendererError>>(&self, f: F) -> Result<R, WgpuRendererError> { todo!() }
 /// #   fn window_id(&self) -> WindowId { todo!() }
 /// }
 ///
@@ -80,12 +81,31 @@ pub trait WgpuDevice {
 	/// This controls the projection matrix used by the sprite renderer.
 	fn viewport_dimensions(&self) -> Vector2<f32>;
 
+	/// The dimensions of the swapchain frame in physical pixels.
+	///
+	/// Defaults to the viewport dimensions. Devices whose frames are scaled relative to the
+	/// viewport, such as windows on high DPI displays, should override this.
+	fn viewport_physical_dimensions(&self) -> Vector2<u32> {
+		self.viewport_dimensions().convert()
+	}
+
+	/// The format of the swapchain frame's texture view. The renderer's pipelines and
+	/// multisampled attachments are built for this format.
+	///
+	/// If the format is an sRGB format, the renderer blends in linear space and converts the
+	/// colors it draws with from sRGB to linear, see [`RendererInitArgs::with_srgb`].
+	///
+	/// Defaults to `Bgra8Unorm`.
+	fn frame_format(&self) -> wgpu::TextureFormat {
+		wgpu::TextureFormat::Bgra8Unorm
+	}
+
 	/// Provides a reference to the set of wgpu device state for use by the renderer.
 	fn with_device_info<R, F: FnOnce(&WgpuDeviceInfo) -> Result<R>>(&self, f: F) -> Result<R>;
 
-	/// Provide a reference to the current swap chain frame for use by the
-	/// renderer.
-	fn with_frame<R, F: FnOnce(&wgpu::SwapChainFrame) -> Result<R>>(&self, f: F) -> Result<R>;
+	/// Provide a reference to the view of the current frame for use by the
+	/// renderer. For window devices this will be the current swap chain frame.
+	fn with_frame_view<R, F: FnOnce(&wgpu::TextureView) -> Result<R>>(&self, f: F) -> Result<R>;
 
 	fn window_id(&self) -> WindowId;
 }
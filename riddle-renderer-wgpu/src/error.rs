@@ -10,9 +10,27 @@ pub enum WgpuRendererError {
 	#[error("Error beginning render: {0}")]
 	BeginRender(&'static str),
 
+	#[error("Error reading back texture: {0}")]
+	TextureRead(&'static str),
+
+	#[error("Error writing to texture: {0}")]
+	TextureWrite(&'static str),
+
 	#[error("Shader loading error: {0}")]
 	ShaderLoad(&'static str),
 
+	#[error("Unsupported MSAA sample count: {0}")]
+	SampleCount(u32),
+
+	#[error("Invalid shader params: {0}")]
+	ShaderParams(&'static str),
+
+	#[error("Tile map loading error: {0}")]
+	TileMapLoad(&'static str),
+
+	#[error("Failed to parse tile map JSON: {0}")]
+	TileMapJson(#[from] serde_json::Error),
+
 	#[error(transparent)]
 	Image(#[from] image::ImageError),
 
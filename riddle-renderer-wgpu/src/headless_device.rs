@@ -0,0 +1,171 @@
+use math::{SpacialNumericConversion, Vector2};
+use platform::common::WindowId;
+
+use crate::*;
+
+/// A Renderer WGPU device which renders to an offscreen texture, without needing a window
+/// or surface.
+///
+/// Intended for offscreen rendering, and for rendering scenes in environments without a
+/// display, such as CI. The contents of the frame can be read back using
+/// [`HeadlessWgpuDevice::read_image`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// let renderer = Renderer::new_headless(vec2(64, 64))?;
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::RED)?;
+///     render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(10.0, 10.0)), Color::GREEN)
+/// })?;
+///
+/// let img = renderer.wgpu_device().read_image()?;
+/// assert_eq!(Color::<u8>::GREEN, img.get_pixel([0, 0]));
+/// # Ok(()) }
+/// ```
+pub struct HeadlessWgpuDevice {
+	device: wgpu::Device,
+	queue: wgpu::Queue,
+
+	frame_texture: wgpu::Texture,
+	frame_view: wgpu::TextureView,
+	frame_format: wgpu::TextureFormat,
+	dimensions: Vector2<u32>,
+}
+
+impl HeadlessWgpuDevice {
+	/// The format of the offscreen frame texture. If [`RendererInitArgs::srgb`] is set, the
+	/// sRGB variant of the format is used.
+	pub const FRAME_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8Unorm;
+
+	/// Headless devices aren't associated with a window, so all report this id.
+	pub const WINDOW_ID: u32 = u32::MAX;
+
+	/// Create a new device with an offscreen frame of the given dimensions, in pixels.
+	///
+	/// No surface compatibility is requested of the adapter, so software adapters such as
+	/// lavapipe can be used.
+	pub fn new(dimensions: Vector2<u32>) -> Result<Self> {
+		Self::new_with_args(dimensions, &RendererInitArgs::new())
+	}
+
+	/// Create a new device with an offscreen frame of the given dimensions, in pixels, using
+	/// an sRGB frame format if [`RendererInitArgs::srgb`] is set.
+	pub fn new_with_args(dimensions: Vector2<u32>, init_args: &RendererInitArgs) -> Result<Self> {
+		let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+
+		log::debug!("Initializing headless WGPU adapter...");
+		let adapter =
+			futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+				power_preference: wgpu::PowerPreference::LowPower,
+				compatible_surface: None,
+			}))
+			.ok_or(WgpuRendererError::ApiInit("Failed to get WGPU adapter"))?;
+
+		log::debug!("Initializing headless WGPU device...");
+		let (device, queue) = futures::executor::block_on(adapter.request_device(
+			&wgpu::DeviceDescriptor {
+				..Default::default()
+			},
+			None,
+		))
+		.map_err(|_| WgpuRendererError::ApiInit("Failed to create WGPU device"))?;
+
+		let frame_format = with_srgb_encoding(Self::FRAME_FORMAT, init_args.srgb);
+		let frame_texture = device.create_texture(&wgpu::TextureDescriptor {
+			size: wgpu::Extent3d {
+				width: dimensions.x,
+				height: dimensions.y,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: frame_format,
+			usage: wgpu::TextureUsage::RENDER_ATTACHMENT | wgpu::TextureUsage::COPY_SRC,
+			label: None,
+		});
+		let frame_view = frame_texture.create_view(&wgpu::TextureViewDescriptor {
+			..Default::default()
+		});
+
+		Ok(Self {
+			device,
+			queue,
+			frame_texture,
+			frame_view,
+			frame_format,
+			dimensions,
+		})
+	}
+
+	/// Copy the contents of the offscreen frame in to an image.
+	///
+	/// This blocks until the GPU has finished rendering the frame.
+	pub fn read_image(&self) -> Result<image::Image> {
+		futures::executor::block_on(self.read_frame_image(true))
+	}
+
+	/// Copy the contents of the offscreen frame in to an image.
+	///
+	/// The returned future doesn't poll the device itself, and only completes once the caller
+	/// drives device polling, for example by calling `wgpu::Device::poll` through
+	/// [`WgpuDevice::with_device_info`].
+	pub async fn read_image_async(&self) -> Result<image::Image> {
+		self.read_frame_image(false).await
+	}
+
+	async fn read_frame_image(&self, wait: bool) -> Result<image::Image> {
+		read_texture_image(
+			self,
+			&self.frame_texture,
+			self.frame_format,
+			self.dimensions,
+			wait,
+		)
+		.await
+	}
+}
+
+impl WgpuDevice for HeadlessWgpuDevice {
+	fn viewport_dimensions(&self) -> Vector2<f32> {
+		self.dimensions.convert()
+	}
+
+	fn frame_format(&self) -> wgpu::TextureFormat {
+		self.frame_format
+	}
+
+	fn begin_frame(&self) -> Result<()> {
+		Ok(())
+	}
+
+	fn end_frame(&self) {}
+
+	#[inline]
+	fn with_device_info<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(&WgpuDeviceInfo) -> Result<R>,
+	{
+		let info = WgpuDeviceInfo {
+			device: &self.device,
+			queue: &self.queue,
+		};
+		f(&info)
+	}
+
+	#[inline]
+	fn with_frame_view<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(&wgpu::TextureView) -> Result<R>,
+	{
+		f(&self.frame_view)
+	}
+
+	fn window_id(&self) -> WindowId {
+		WindowId::new(Self::WINDOW_ID)
+	}
+}
@@ -42,14 +42,20 @@
 mod buffered_renderer;
 mod device;
 mod error;
+mod headless_device;
+mod post_process;
+mod render_stats;
 mod renderer;
 mod shader;
 mod sprite;
 mod sprite_atlas;
 mod sprite_render_target;
+mod stream_buffer;
 mod swap_chain_target;
 mod target;
 mod texture;
+mod tile_map;
+mod virtual_resolution;
 mod window_device;
 
 use riddle_common::*;
@@ -62,14 +68,20 @@ type Result<R> = std::result::Result<R, WgpuRendererError>;
 use buffered_renderer::*;
 pub use device::*;
 pub use error::*;
+pub use headless_device::*;
+pub use post_process::*;
+pub use render_stats::*;
 pub use renderer::*;
-use shader::*;
+pub use shader::*;
 pub use sprite::*;
 pub use sprite_atlas::*;
 pub use sprite_render_target::*;
+use stream_buffer::*;
 use swap_chain_target::*;
 use target::*;
 use texture::*;
+pub use tile_map::*;
+pub use virtual_resolution::*;
 pub use window_device::*;
 
 pub use riddle_renderer_common::*;
@@ -77,3 +89,4 @@ pub use riddle_renderer_common::*;
 use riddle_renderer_common::vertex::*;
 
 pub type DefaultRenderer = Renderer<WindowWgpuDevice>;
+pub type HeadlessRenderer = Renderer<HeadlessWgpuDevice>;
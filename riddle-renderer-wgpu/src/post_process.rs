@@ -0,0 +1,408 @@
+use math::{vec2, Vector2};
+
+use crate::*;
+
+/// Renders a scene in to an offscreen target, then applies a chain of full screen passes to it
+/// while drawing it to another render context.
+///
+/// Built in passes are provided for common effects, see [`PostProcessPass`], and custom passes
+/// can be added using any [`Shader`]. Each pass reads the output of the previous pass. The
+/// intermediate targets passes render in to are managed by the chain, and are reused between
+/// passes, so long chains don't need a target per pass.
+///
+/// The final pass is drawn directly to the render context given to
+/// [`PostProcessChain::present`], with its top left corner at the origin and replacing the
+/// target's contents.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let mut chain = PostProcessChain::new(&renderer, vec2(800, 600))?
+///     .with_pass(PostProcessPass::Bloom { threshold: 0.8, intensity: 1.5, radius: 4.0 })
+///     .with_pass(PostProcessPass::Vignette { intensity: 0.6, radius: 0.75, softness: 0.4 });
+///
+/// // Once per frame
+/// chain.render_scene(|scene_ctx| {
+///     scene_ctx.clear(Color::BLACK)?;
+///     scene_ctx.fill_rect(&Rect::new(vec2(100.0, 100.0), vec2(50.0, 50.0)), Color::WHITE)
+/// })?;
+/// renderer.render(|render_ctx| chain.present(render_ctx))?;
+/// # Ok(()) }
+/// ```
+pub struct PostProcessChain<Device: WgpuDevice> {
+	renderer: Renderer<Device>,
+	shaders: PostProcessShaders,
+	dimensions: Vector2<u32>,
+	passes: Vec<PostProcessPass<Device>>,
+
+	scene: SpriteRenderTarget<Device>,
+
+	// The targets steps render in to, which never include the scene target so the scene can be
+	// presented again. Steps ping-pong between two of them. The third is needed when a pass
+	// which reads its input again in a later step, such as bloom, reads the output of a
+	// previous pass, as a step's output must avoid both its input and its pass's input.
+	intermediates: Vec<SpriteRenderTarget<Device>>,
+}
+
+impl<Device: WgpuDevice> PostProcessChain<Device> {
+	/// Build a chain with no passes, rendering the scene at the given dimensions.
+	pub fn new(renderer: &Renderer<Device>, dimensions: Vector2<u32>) -> Result<Self> {
+		let shaders = PostProcessShaders::new(renderer)?;
+		Ok(Self {
+			renderer: renderer.clone(),
+			shaders,
+			dimensions,
+			passes: vec![],
+			scene: SpriteRenderTarget::new(renderer, dimensions)?,
+			intermediates: Self::create_intermediates(renderer, dimensions)?,
+		})
+	}
+
+	/// Add a pass to the end of the chain.
+	pub fn with_pass(mut self, pass: PostProcessPass<Device>) -> Self {
+		self.add_pass(pass);
+		self
+	}
+
+	/// Add a pass to the end of the chain.
+	pub fn add_pass(&mut self, pass: PostProcessPass<Device>) {
+		self.passes.push(pass);
+	}
+
+	/// The passes in the chain, in the order they are applied.
+	pub fn passes(&self) -> &[PostProcessPass<Device>] {
+		&self.passes[..]
+	}
+
+	/// Access the passes, to reorder, remove or change the settings of passes.
+	pub fn passes_mut(&mut self) -> &mut Vec<PostProcessPass<Device>> {
+		&mut self.passes
+	}
+
+	/// The dimensions of the scene target and intermediate targets.
+	pub fn dimensions(&self) -> Vector2<u32> {
+		self.dimensions
+	}
+
+	/// Recreate the scene target and intermediate targets with new dimensions, for example when
+	/// the window is resized. The contents of the scene target are lost.
+	pub fn resize(&mut self, dimensions: Vector2<u32>) -> Result<()> {
+		self.scene = SpriteRenderTarget::new(&self.renderer, dimensions)?;
+		self.intermediates = Self::create_intermediates(&self.renderer, dimensions)?;
+		self.dimensions = dimensions;
+		Ok(())
+	}
+
+	/// The target the scene is rendered in to before the passes are applied.
+	pub fn scene(&self) -> &SpriteRenderTarget<Device> {
+		&self.scene
+	}
+
+	/// Render the scene, see [`SpriteRenderTarget::render`].
+	pub fn render_scene<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(&mut BufferedRenderer<Device, &SpriteRenderTarget<Device>>) -> Result<R>,
+	{
+		self.scene().render(f)
+	}
+
+	/// Apply the passes to the scene, and draw the result to the render context.
+	///
+	/// All but the last pass are rendered immediately. The last pass is drawn to the render
+	/// context like any other renderable. The scene target is left unchanged, so the scene can
+	/// be presented again without rendering it again.
+	pub fn present<Ctx>(&self, render_ctx: &mut Ctx) -> Result<()>
+	where
+		Ctx: RenderContext<Renderer<Device>> + ?Sized,
+	{
+		let steps = self.steps();
+		let args = SpriteRenderArgs {
+			blend_mode: BlendMode::Replace,
+			..Default::default()
+		};
+
+		if steps.is_empty() {
+			return self.scene.sprite().render(render_ctx, &args);
+		}
+
+		// Step inputs are indices in to the intermediate targets, or None for the scene
+		let mut input = None;
+		let mut pass_input = None;
+		for (i, step) in steps.iter().enumerate() {
+			if step.first_in_pass {
+				pass_input = input;
+			}
+			let shader = self.step_shader(step, pass_input)?;
+			let input_sprite = self.target(input).sprite();
+
+			if i == steps.len() - 1 {
+				input_sprite.render_with_shader(render_ctx, &args, &shader)?;
+			} else {
+				let output = (0..self.intermediates.len())
+					.find(|target| Some(*target) != input && Some(*target) != pass_input)
+					.unwrap();
+				self.intermediates[output].render(|target_ctx| {
+					input_sprite.render_with_shader(target_ctx, &args, &shader)
+				})?;
+				input = Some(output);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn create_intermediates(
+		renderer: &Renderer<Device>,
+		dimensions: Vector2<u32>,
+	) -> Result<Vec<SpriteRenderTarget<Device>>> {
+		(0..3)
+			.map(|_| SpriteRenderTarget::new(renderer, dimensions))
+			.collect()
+	}
+
+	/// The intermediate target with the given index, or the scene target for None.
+	fn target(&self, index: Option<usize>) -> &SpriteRenderTarget<Device> {
+		match index {
+			Some(index) => &self.intermediates[index],
+			None => &self.scene,
+		}
+	}
+
+	/// Break the passes down in to the individual full screen draws which apply them.
+	fn steps(&self) -> Vec<PostProcessStep<'_, Device>> {
+		let shaders = &self.shaders;
+		let mut steps = vec![];
+		for pass in &self.passes {
+			let first = steps.len();
+			match pass {
+				PostProcessPass::Blur { radius } => {
+					steps.push(PostProcessStep::new(
+						&shaders.blur,
+						[1.0, 0.0, *radius, 0.0],
+					));
+					steps.push(PostProcessStep::new(
+						&shaders.blur,
+						[0.0, 1.0, *radius, 0.0],
+					));
+				}
+				PostProcessPass::Bloom {
+					threshold,
+					intensity,
+					radius,
+				} => {
+					steps.push(PostProcessStep::new(
+						&shaders.bloom_extract,
+						[*threshold, 0.0, 0.0, 0.0],
+					));
+					steps.push(PostProcessStep::new(
+						&shaders.blur,
+						[1.0, 0.0, *radius, 0.0],
+					));
+					steps.push(PostProcessStep::new(
+						&shaders.blur,
+						[0.0, 1.0, *radius, 0.0],
+					));
+					steps.push(
+						PostProcessStep::new(&shaders.bloom_composite, [*intensity, 0.0, 0.0, 0.0])
+							.with_texture(StepTexture::PassInput),
+					);
+				}
+				PostProcessPass::Crt {
+					scanline_intensity,
+					curvature,
+				} => {
+					steps.push(PostProcessStep::new(
+						&shaders.crt,
+						[*scanline_intensity, *curvature, 0.0, 0.0],
+					));
+				}
+				PostProcessPass::Vignette {
+					intensity,
+					radius,
+					softness,
+				} => {
+					steps.push(PostProcessStep::new(
+						&shaders.vignette,
+						[*intensity, *radius, *softness, 0.0],
+					));
+				}
+				PostProcessPass::ColorGrade { lut, intensity } => {
+					steps.push(
+						PostProcessStep::new(
+							&shaders.color_grade,
+							[lut.dimensions().y, *intensity, 0.0, 0.0],
+						)
+						.with_texture(StepTexture::Sprite(lut)),
+					);
+				}
+				PostProcessPass::Custom(shader) => {
+					let mut step = PostProcessStep::new(shader, [0.0; 4]);
+					step.custom = true;
+					steps.push(step);
+				}
+			}
+
+			if let Some(step) = steps.get_mut(first) {
+				step.first_in_pass = true;
+			}
+		}
+		steps
+	}
+
+	/// Bind the params for a built in step. Custom shaders are used as they are.
+	fn step_shader(
+		&self,
+		step: &PostProcessStep<'_, Device>,
+		pass_input: Option<usize>,
+	) -> Result<Shader> {
+		if step.custom {
+			return Ok(step.shader.clone());
+		}
+
+		let texel_size = vec2(
+			1.0 / self.dimensions.x.max(1) as f32,
+			1.0 / self.dimensions.y.max(1) as f32,
+		);
+		let values = step.values;
+		let mut params = ShaderParams::new().with_uniforms(&[
+			texel_size.x,
+			texel_size.y,
+			0.0,
+			0.0,
+			values[0],
+			values[1],
+			values[2],
+			values[3],
+		]);
+
+		params = match step.texture {
+			StepTexture::None => params,
+			StepTexture::PassInput => params.with_texture(0, self.target(pass_input).sprite()),
+			StepTexture::Sprite(sprite) => params.with_texture(0, sprite),
+		};
+
+		step.shader.with_params(params)
+	}
+}
+
+/// A full screen effect applied by a [`PostProcessChain`].
+pub enum PostProcessPass<Device: WgpuDevice> {
+	/// A gaussian blur, with the given radius in texels.
+	Blur { radius: f32 },
+
+	/// Make areas brighter than the threshold glow. The bright areas are extracted, blurred
+	/// with the given radius in texels, and added back to the scene scaled by the intensity.
+	Bloom {
+		threshold: f32,
+		intensity: f32,
+		radius: f32,
+	},
+
+	/// Darken alternate rows of texels, and curve the image like an old CRT screen. A curvature
+	/// of 0 leaves the image flat.
+	Crt {
+		scanline_intensity: f32,
+		curvature: f32,
+	},
+
+	/// Darken the edges of the screen. The radius and softness are fractions of the screen,
+	/// with the darkening starting `radius - softness` from the center, and reaching full
+	/// intensity at `radius` from the center.
+	Vignette {
+		intensity: f32,
+		radius: f32,
+		softness: f32,
+	},
+
+	/// Remap colors using a lookup table, blended with the original colors by the intensity.
+	///
+	/// A LUT of size `n` is an `n * n` by `n` texel sprite, made of `n` square slices side by
+	/// side. Red increases from left to right within each slice, green increases from top to
+	/// bottom, and blue increases from the first slice to the last. The sprite should be
+	/// created with linear filtering.
+	ColorGrade { lut: Sprite<Device>, intensity: f32 },
+
+	/// A pass using a custom shader.
+	///
+	/// The previous pass's output is bound as the sprite texture, and is drawn as a single
+	/// quad covering the target with texture coordinates from 0 to 1. The shader's user params
+	/// are bound as set with [`Shader::with_params`].
+	Custom(Shader),
+}
+
+/// The shaders used by the built in passes.
+struct PostProcessShaders {
+	blur: Shader,
+	bloom_extract: Shader,
+	bloom_composite: Shader,
+	crt: Shader,
+	vignette: Shader,
+	color_grade: Shader,
+}
+
+/// The size of the `Params` uniform block shared by the built in pass shaders.
+const POST_PROCESS_UNIFORM_SIZE: u64 = 32;
+
+impl PostProcessShaders {
+	fn new<Device: WgpuDevice>(renderer: &Renderer<Device>) -> Result<Self> {
+		let load = |effect: &str, texture_count: u32| {
+			let wgsl = format!("{}\n{}", include_str!("shaders/post/common.wgsl"), effect);
+			Shader::load(
+				renderer,
+				wgsl.as_bytes(),
+				&ShaderInitArgs::new()
+					.with_uniforms(POST_PROCESS_UNIFORM_SIZE)
+					.with_textures(texture_count),
+			)
+		};
+
+		Ok(Self {
+			blur: load(include_str!("shaders/post/blur.wgsl"), 0)?,
+			bloom_extract: load(include_str!("shaders/post/bloom_extract.wgsl"), 0)?,
+			bloom_composite: load(include_str!("shaders/post/bloom_composite.wgsl"), 1)?,
+			crt: load(include_str!("shaders/post/crt.wgsl"), 0)?,
+			vignette: load(include_str!("shaders/post/vignette.wgsl"), 0)?,
+			color_grade: load(include_str!("shaders/post/color_grade.wgsl"), 1)?,
+		})
+	}
+}
+
+/// A single full screen draw, making up part of a pass.
+struct PostProcessStep<'a, Device: WgpuDevice> {
+	shader: &'a Shader,
+	values: [f32; 4],
+	texture: StepTexture<'a, Device>,
+	first_in_pass: bool,
+	custom: bool,
+}
+
+impl<'a, Device: WgpuDevice> PostProcessStep<'a, Device> {
+	fn new(shader: &'a Shader, values: [f32; 4]) -> Self {
+		Self {
+			shader,
+			values,
+			texture: StepTexture::None,
+			first_in_pass: false,
+			custom: false,
+		}
+	}
+
+	fn with_texture(mut self, texture: StepTexture<'a, Device>) -> Self {
+		self.texture = texture;
+		self
+	}
+}
+
+/// The user texture bound to a built in step.
+enum StepTexture<'a, Device: WgpuDevice> {
+	None,
+
+	/// The input to the pass the step is part of.
+	PassInput,
+	Sprite(&'a Sprite<Device>),
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+/// Counters describing the work done to render a frame, or the work done so far by a single
+/// render context.
+///
+/// [`Renderer::frame_stats`](crate::Renderer::frame_stats) reports the stats of the last
+/// presented frame. The frame's stats include every render context presented since the
+/// previous frame, such as renders to [`SpriteRenderTarget`](crate::SpriteRenderTarget)s.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+/// let sprite = Sprite::new_from_image(&renderer, &Image::new(32, 32), &SpriteInitArgs::new())?;
+///
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     for i in 0..100 {
+///         sprite.render_at(render_ctx, vec2(i as f32, 0.0))?;
+///     }
+///     Ok(())
+/// })?;
+///
+/// // Sprites sharing a texture are batched in to a single draw call
+/// let stats = renderer.frame_stats();
+/// assert_eq!(1, stats.draw_calls);
+/// assert_eq!(400, stats.vertices);
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderStats {
+	/// The number of indexed draw calls encoded.
+	pub draw_calls: u32,
+
+	/// The number of times a pending batch of geometry was drawn. Each flush is one draw
+	/// call, but draws of geometry held in caller owned buffers, such as tile map chunks, are
+	/// draw calls without a flush.
+	pub flushes: u32,
+
+	/// The number of flushes, by the reason the batch couldn't be extended.
+	pub flush_reasons: HashMap<FlushReason, u32>,
+
+	/// The number of vertices drawn.
+	pub vertices: u64,
+
+	/// The number of indices drawn.
+	pub indices: u64,
+
+	/// The number of bytes written to the renderer's streaming vertex, index and uniform
+	/// buffers. Texture uploads, and writes to buffers owned by other objects, aren't included.
+	pub bytes_uploaded: u64,
+}
+
+impl RenderStats {
+	/// The number of flushes caused by the given reason.
+	pub fn flushes_for(&self, reason: FlushReason) -> u32 {
+		self.flush_reasons.get(&reason).copied().unwrap_or(0)
+	}
+
+	pub(crate) fn record_flush(&mut self, reason: FlushReason) {
+		self.flushes += 1;
+		*self.flush_reasons.entry(reason).or_insert(0) += 1;
+	}
+
+	pub(crate) fn record_draw(&mut self, vertices: u64, indices: u64) {
+		self.draw_calls += 1;
+		self.vertices += vertices;
+		self.indices += indices;
+	}
+
+	pub(crate) fn add(&mut self, other: &RenderStats) {
+		self.draw_calls += other.draw_calls;
+		self.flushes += other.flushes;
+		for (reason, count) in other.flush_reasons.iter() {
+			*self.flush_reasons.entry(*reason).or_insert(0) += count;
+		}
+		self.vertices += other.vertices;
+		self.indices += other.indices;
+		self.bytes_uploaded += other.bytes_uploaded;
+	}
+}
+
+/// Why a render context drew its pending batch of geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FlushReason {
+	/// A draw used a texture which couldn't be added to the batch. For batches using the
+	/// default shader, this happens when all the batch's texture slots are in use.
+	TextureChange,
+
+	/// A draw used a different shader, or the same shader with different params.
+	ShaderChange,
+
+	/// A draw used a different blend mode.
+	BlendModeChange,
+
+	/// The transform was changed.
+	TransformChange,
+
+	/// The clip rect was pushed or popped.
+	ClipChange,
+
+	/// Drawing to or with the mask began or changed mode.
+	MaskChange,
+
+	/// The target was cleared.
+	Clear,
+
+	/// Sorted draws were drawn, at the end of sorted rendering or before a state change.
+	SortedDraws,
+
+	/// Geometry held in caller owned buffers was drawn.
+	BufferDraw,
+
+	/// The render context was presented.
+	Present,
+
+	/// The render context's `flush` was called.
+	Explicit,
+}
+
+#[cfg(test)]
+mod test {
+	use crate::*;
+
+	use math::vec2;
+
+	#[test]
+	fn interleaved_textures_are_drawn_in_one_draw_call() {
+		// Skip when no adapter is available, such as in CI without a GPU
+		let renderer = match Renderer::new_headless(vec2(64, 64)) {
+			Ok(renderer) => renderer,
+			Err(_) => return,
+		};
+
+		let sprite_a =
+			Sprite::new_from_image(&renderer, &image::Image::new(4, 4), &SpriteInitArgs::new())
+				.unwrap();
+		let sprite_b =
+			Sprite::new_from_image(&renderer, &image::Image::new(8, 8), &SpriteInitArgs::new())
+				.unwrap();
+
+		renderer
+			.render(|render_ctx| {
+				render_ctx.clear(Color::BLACK)?;
+				for i in 0..10 {
+					sprite_a.render_at(render_ctx, vec2(i as f32, 0.0))?;
+					sprite_b.render_at(render_ctx, vec2(i as f32, 10.0))?;
+				}
+				Ok(())
+			})
+			.unwrap();
+
+		let stats = renderer.frame_stats();
+		assert_eq!(1, stats.draw_calls);
+		assert_eq!(80, stats.vertices);
+		assert_eq!(120, stats.indices);
+		assert_eq!(1, stats.flushes);
+		assert_eq!(1, stats.flushes_for(FlushReason::Present));
+		assert_eq!(0, stats.flushes_for(FlushReason::TextureChange));
+	}
+}
@@ -3,6 +3,8 @@ use platform::{common::WindowId, Window};
 
 use crate::*;
 
+use std::sync::Mutex;
+
 /// A simple 2D sprite based renderer.
 ///
 /// A renderer can be created for a Window and holds a reference to the window, which will
@@ -47,8 +49,58 @@ impl Renderer<WindowWgpuDevice> {
 	/// # Ok(()) }
 	/// ```
 	pub fn new_from_window(window: &Window) -> Result<Self> {
-		let wgpu_device = WindowWgpuDevice::new(window)?;
-		Self::new_from_device(wgpu_device)
+		Self::new_from_window_with_args(window, &RendererInitArgs::new())
+	}
+
+	/// Initialize a new Renderer for the window, using the given options.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// let rdl =  RiddleLib::new()?;
+	/// let window = WindowBuilder::new().build(rdl.context())?;
+	///
+	/// // Render with 4x MSAA
+	/// let args = RendererInitArgs::new().with_sample_count(4);
+	/// let renderer = Renderer::new_from_window_with_args(&window, &args)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn new_from_window_with_args(
+		window: &Window,
+		init_args: &RendererInitArgs,
+	) -> Result<Self> {
+		let wgpu_device = WindowWgpuDevice::new_with_args(window, init_args)?;
+		Self::new_from_device_with_args(wgpu_device, init_args)
+	}
+}
+
+impl Renderer<HeadlessWgpuDevice> {
+	/// Initialize a new Renderer, creating a WGPU device which renders to an offscreen
+	/// frame of the given dimensions instead of a window.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// let renderer = Renderer::new_headless(vec2(320, 240))?;
+	///
+	/// assert_eq!(vec2(320.0, 240.0), renderer.dimensions());
+	/// # Ok(()) }
+	/// ```
+	pub fn new_headless(dimensions: Vector2<u32>) -> Result<Self> {
+		Self::new_headless_with_args(dimensions, &RendererInitArgs::new())
+	}
+
+	/// Initialize a new Renderer rendering to an offscreen frame, using the given options.
+	pub fn new_headless_with_args(
+		dimensions: Vector2<u32>,
+		init_args: &RendererInitArgs,
+	) -> Result<Self> {
+		let wgpu_device = HeadlessWgpuDevice::new_with_args(dimensions, init_args)?;
+		Self::new_from_device_with_args(wgpu_device, init_args)
 	}
 }
 
@@ -87,8 +139,27 @@ impl<Device: WgpuDevice> CommonRenderer for Renderer<Device> {
 
 		Ok(result)
 	}
+
+	fn distance_field_shader(&self, params: &DistanceFieldParams) -> Result<Shader> {
+		let outline_color: [f32; 4] = self.shader_color(&params.outline_color).into();
+		let glow_color: [f32; 4] = self.shader_color(&params.glow_color).into();
+		let widths = [
+			params.outline_width,
+			params.glow_width,
+			params.softness,
+			params.pixel_distance,
+		];
+		let uniforms: Vec<f32> = [outline_color, glow_color, widths].concat();
+
+		self.standard_res()
+			.distance_field_shader
+			.with_params(ShaderParams::new().with_uniforms(&uniforms[..]))
+	}
 }
 
+/// The size of the params uniform in `shaders/distance_field.wgsl`.
+const DISTANCE_FIELD_UNIFORM_SIZE: u64 = 48;
+
 impl<Device: WgpuDevice> Renderer<Device> {
 	/// Get the frame dimensions as reported by the [`WgpuDevice`].
 	///
@@ -116,6 +187,103 @@ impl<Device: WgpuDevice> Renderer<Device> {
 		&self.internal.standard_res
 	}
 
+	/// Take a set of stream buffers from the renderer's pool, creating a new set if none are
+	/// free. Each render context holds its own set, so nested renders don't share buffers.
+	pub(crate) fn take_stream_buffers(&self) -> Result<StreamBuffers> {
+		let pooled = self.internal.stream_buffers.lock().unwrap().pop();
+		match pooled {
+			Some(streams) => Ok(streams),
+			None => self
+				.internal
+				.wgpu_device
+				.with_device_info(|info| Ok(StreamBuffers::new(info.device))),
+		}
+	}
+
+	/// The number of samples per pixel used when rendering to the device's frames.
+	pub fn sample_count(&self) -> u32 {
+		self.internal.sample_count
+	}
+
+	/// Whether the renderer was created with [`RendererInitArgs::srgb`], rendering with sRGB
+	/// textures and render targets.
+	pub fn srgb(&self) -> bool {
+		self.internal.srgb
+	}
+
+	/// Whether textures created with the given args use an sRGB format.
+	pub(crate) fn srgb_texture(&self, init_args: &SpriteInitArgs) -> bool {
+		self.internal.srgb && init_args.color_space == ColorSpace::Srgb
+	}
+
+	/// Convert a color to the space the renderer's shaders output colors in.
+	pub(crate) fn shader_color(&self, color: &Color<f32>) -> Color<f32> {
+		if self.internal.srgb {
+			color.to_linear()
+		} else {
+			color.clone()
+		}
+	}
+
+	/// The work done to render the last presented frame, including any render targets
+	/// rendered since the frame before it. See [`RenderStats`].
+	///
+	/// Before the first frame has been presented, all the stats are zero.
+	pub fn frame_stats(&self) -> RenderStats {
+		self.internal.stats.lock().unwrap().frame.clone()
+	}
+
+	/// Add the stats of a presented render context to the stats of the frame being built.
+	pub(crate) fn record_stats(&self, stats: &RenderStats) {
+		self.internal.stats.lock().unwrap().pending.add(stats);
+	}
+
+	/// Called once a frame has been presented, making the stats built up for it available
+	/// from [`Renderer::frame_stats`].
+	pub(crate) fn end_frame_stats(&self) {
+		let mut stats = self.internal.stats.lock().unwrap();
+		stats.frame = std::mem::take(&mut stats.pending);
+	}
+
+	/// Provides the multisampled color attachment, if the renderer is multisampled, and the
+	/// stencil attachment for the device's frames, recreating them if the frame size has changed.
+	pub(crate) fn with_frame_attachments<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(Option<&wgpu::TextureView>, &wgpu::TextureView) -> Result<R>,
+	{
+		let dimensions = self.internal.wgpu_device.viewport_physical_dimensions();
+		let sample_count = self.internal.sample_count;
+		let mut attachments = self.internal.frame_attachments.lock().unwrap();
+		if attachments.as_ref().map(|a| a.stencil.dimensions) != Some(dimensions) {
+			*attachments = Some(self.internal.wgpu_device.with_device_info(|info| {
+				Ok(FrameAttachments {
+					color: if sample_count > 1 {
+						Some(MultisampleTexture::new(
+							info.device,
+							dimensions,
+							sample_count,
+							self.internal.wgpu_device.frame_format(),
+						))
+					} else {
+						None
+					},
+					stencil: StencilTexture::new(info.device, dimensions, sample_count),
+				})
+			})?);
+		}
+
+		let attachments = attachments.as_ref().unwrap();
+		f(
+			attachments.color.as_ref().map(|color| &color.view),
+			&attachments.stencil.view,
+		)
+	}
+
+	/// Return stream buffers to the pool, once all writes to them have been submitted.
+	pub(crate) fn return_stream_buffers(&self, streams: StreamBuffers) {
+		self.internal.stream_buffers.lock().unwrap().push(streams);
+	}
+
 	pub fn wgpu_device(&self) -> &Device {
 		&self.internal.wgpu_device
 	}
@@ -123,7 +291,19 @@ impl<Device: WgpuDevice> Renderer<Device> {
 	/// Or the renderer can be built on top of existing WGPU contexts, to allow the simple
 	/// renderer to be used on top of custom renderers.
 	pub fn new_from_device(wgpu_device: Device) -> Result<Self> {
-		let internal = RendererInternal::new(wgpu_device)?;
+		Self::new_from_device_with_args(wgpu_device, &RendererInitArgs::new())
+	}
+
+	/// Build a renderer on top of an existing WGPU device, using the given options.
+	///
+	/// If the renderer is multisampled, the renderer renders to its own multisampled
+	/// attachment, which is resolved in to the device's frame view.
+	pub fn new_from_device_with_args(
+		wgpu_device: Device,
+		init_args: &RendererInitArgs,
+	) -> Result<Self> {
+		validate_sample_count(init_args.sample_count)?;
+		let internal = RendererInternal::new(wgpu_device, init_args)?;
 		Ok(Self {
 			internal: internal.into(),
 		})
@@ -142,48 +322,195 @@ impl<D: WgpuDevice> Clone for Renderer<D> {
 #[derive(Clone)]
 pub struct StandardResources {
 	pub(super) default_shader: Shader,
+	pub(super) mask_shader: Shader,
+	pub(super) batch_shader: Shader,
+	pub(super) distance_field_shader: Shader,
 	pub(super) white_tex: Texture,
 }
 
+/// Options used when creating a [`Renderer`].
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_wgpu::*;
+/// let args = RendererInitArgs::new().with_sample_count(4).with_srgb(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendererInitArgs {
+	/// The number of samples per pixel when rendering to the device's frames. A count greater
+	/// than 1 enables MSAA. Only 1 and 4 are accepted, as they are the only counts supported by
+	/// all adapters.
+	pub sample_count: u32,
+
+	/// Render with sRGB frames, render targets and textures, so blending and filtering happen
+	/// in linear space. See [`RendererInitArgs::with_srgb`].
+	pub srgb: bool,
+}
+
+impl RendererInitArgs {
+	/// Create a new default init args, without multisampling or sRGB rendering.
+	pub fn new() -> Self {
+		Self {
+			sample_count: 1,
+			srgb: false,
+		}
+	}
+
+	/// Set the number of samples per pixel. Must be 1 or 4.
+	pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+		self.sample_count = sample_count;
+		self
+	}
+
+	/// Set whether the renderer renders in linear space, using sRGB formats for the frame,
+	/// render targets and sprites.
+	///
+	/// Without sRGB rendering colors are blended as they are stored, which darkens gradients
+	/// and the edges of blended and filtered sprites. With it, sprite textures are decoded to
+	/// linear space when sampled, the colors given to the renderer, such as clear, vertex and
+	/// distance field font colors, are converted from sRGB to linear, and results are encoded
+	/// back to sRGB when written to the frame. Colors are still given to and read back from the
+	/// renderer in sRGB.
+	///
+	/// Sprites holding data rather than colors should be created with [`ColorSpace::Linear`].
+	/// Uniforms passed to custom shaders aren't converted.
+	///
+	/// Devices created by the renderer use the sRGB variant of the surface's preferred format.
+	/// Custom devices should report an sRGB [`WgpuDevice::frame_format`].
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let args = RendererInitArgs::new().with_srgb(true);
+	/// let renderer = Renderer::new_from_window_with_args(&window, &args)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn with_srgb(mut self, srgb: bool) -> Self {
+		self.srgb = srgb;
+		self
+	}
+}
+
+impl Default for RendererInitArgs {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Check the sample count is one every adapter supports. wgpu can't be queried for which
+/// counts an adapter supports, and unsupported counts panic when attachments are created.
+pub(crate) fn validate_sample_count(sample_count: u32) -> Result<()> {
+	match sample_count {
+		1 | 4 => Ok(()),
+		_ => Err(WgpuRendererError::SampleCount(sample_count)),
+	}
+}
+
 pub(crate) struct RendererInternal<D: WgpuDevice> {
 	wgpu_device: D,
 	standard_res: StandardResources,
+	stream_buffers: Mutex<Vec<StreamBuffers>>,
+	sample_count: u32,
+	srgb: bool,
+	frame_attachments: Mutex<Option<FrameAttachments>>,
+	stats: Mutex<FrameStats>,
+}
+
+/// The stats of the last presented frame, and of the frame being built.
+#[derive(Default)]
+struct FrameStats {
+	frame: RenderStats,
+	pending: RenderStats,
+}
+
+/// The attachments used when rendering to the device's frames.
+struct FrameAttachments {
+	color: Option<MultisampleTexture>,
+	stencil: StencilTexture,
 }
 
 impl<D: WgpuDevice> RendererInternal<D> {
 	/// Or the renderer can be built on top of existing WGPU contexts, to allow the simple
 	/// renderer to be used on top of custom renderers.
-	fn new(wgpu_device: D) -> Result<Self> {
-		let (default_shader, white_tex) = wgpu_device.with_device_info(|info| {
-			let wgsl = include_bytes!("shaders/default.wgsl");
-			let sprite_shader = Shader::from_readers(
-				info.device,
-				std::io::Cursor::new(&wgsl[..]),
-				wgpu::PrimitiveTopology::TriangleList,
-			)?;
-
-			let mut white_img = image::Image::new(1, 1);
-			white_img.set_pixel([0, 0], Color::from([0xFF; 4]));
-			let white_tex = Texture::from_image(
-				info.device,
-				info.queue,
-				&white_img,
-				FilterMode::Nearest,
-				FilterMode::Nearest,
-				TextureType::Plain,
-			);
-
-			Ok((sprite_shader, white_tex))
-		})?;
+	fn new(wgpu_device: D, init_args: &RendererInitArgs) -> Result<Self> {
+		let (default_shader, mask_shader, batch_shader, distance_field_shader, white_tex) =
+			wgpu_device.with_device_info(|info| {
+				let wgsl = include_bytes!("shaders/default.wgsl");
+				let sprite_shader = Shader::from_readers(
+					info.device,
+					std::io::Cursor::new(&wgsl[..]),
+					wgpu::PrimitiveTopology::TriangleList,
+					&ShaderInitArgs::new(),
+					1,
+				)?;
+
+				let mask_wgsl = include_bytes!("shaders/mask.wgsl");
+				let mask_shader = Shader::from_readers(
+					info.device,
+					std::io::Cursor::new(&mask_wgsl[..]),
+					wgpu::PrimitiveTopology::TriangleList,
+					&ShaderInitArgs::new(),
+					1,
+				)?;
+
+				let batch_wgsl = include_bytes!("shaders/batch.wgsl");
+				let batch_shader = Shader::from_readers(
+					info.device,
+					std::io::Cursor::new(&batch_wgsl[..]),
+					wgpu::PrimitiveTopology::TriangleList,
+					&ShaderInitArgs::new(),
+					BATCH_TEXTURE_SLOTS,
+				)?;
+
+				let distance_field_wgsl = include_bytes!("shaders/distance_field.wgsl");
+				let distance_field_shader = Shader::from_readers(
+					info.device,
+					std::io::Cursor::new(&distance_field_wgsl[..]),
+					wgpu::PrimitiveTopology::TriangleList,
+					&ShaderInitArgs::new().with_uniforms(DISTANCE_FIELD_UNIFORM_SIZE),
+					1,
+				)?;
+
+				let mut white_img = image::Image::new(1, 1);
+				white_img.set_pixel([0, 0], Color::from([0xFF; 4]));
+				let white_tex = Texture::from_image(
+					info.device,
+					info.queue,
+					&white_img,
+					&SpriteInitArgs::new(),
+					TextureType::Plain,
+					false,
+				);
+
+				Ok((
+					sprite_shader,
+					mask_shader,
+					batch_shader,
+					distance_field_shader,
+					white_tex,
+				))
+			})?;
 
 		let standard_res = StandardResources {
 			default_shader,
+			mask_shader,
+			batch_shader,
+			distance_field_shader,
 			white_tex,
 		};
 
 		Ok(Self {
 			wgpu_device,
 			standard_res,
+			stream_buffers: Mutex::new(vec![]),
+			sample_count: init_args.sample_count,
+			srgb: init_args.srgb,
+			frame_attachments: Mutex::new(None),
+			stats: Mutex::new(FrameStats::default()),
 		})
 	}
 }
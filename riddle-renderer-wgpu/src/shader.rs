@@ -1,52 +1,314 @@
-use std::num::NonZeroU64;
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
 
 use crate::*;
 
-use math::Vector2;
-use wgpu::util::DeviceExt;
-use wgpu::{CommandEncoder, RenderPass, TextureView};
-
+/// A WGSL shader program which can be used to render sprites and other [`Renderable`]s.
+///
+/// Shaders are compiled against the standard [`Vertex`] layout. The module must provide
+/// `vs_main` and `fs_main` entry points, and the standard bind group must be declared as
+/// group 0:
+///
+/// * **binding 0** - `mat4x4<f32>` uniform containing the view projection transform.
+/// * **binding 1** - The sprite's `texture_2d<f32>`.
+/// * **binding 2** - The sprite's `sampler`.
+///
+/// If the shader was created with user uniforms or textures (see [`ShaderInitArgs`]), they are
+/// bound in group 1:
+///
+/// * **binding 0** - The user uniform buffer, if [`ShaderInitArgs::with_uniforms`] was specified.
+/// * **binding 1 + 2n** - User texture `n`.
+/// * **binding 2 + 2n** - The sampler for user texture `n`.
+///
+/// The values bound to group 1 are supplied through [`ShaderParams`] and [`Shader::with_params`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// // A shader taking a single vec4 uniform
+/// let wgsl = include_bytes!("../../example_assets/tint.wgsl");
+/// let shader = Shader::load(&renderer, &wgsl[..], &ShaderInitArgs::new().with_uniforms(16))?;
+///
+/// // Bind the uniform value to use when drawing with the shader
+/// let params = ShaderParams::new().with_uniforms(&[1.0f32, 0.0, 0.0, 1.0]);
+/// let tinted_shader = shader.with_params(params)?;
+///
+/// let sprite = Sprite::new_from_image(&renderer, &Image::new(32, 32), &SpriteInitArgs::new())?;
+/// renderer.render(|render_ctx| {
+///     sprite.render_with_shader(render_ctx, &SpriteRenderArgs::new([0.0, 0.0]), &tinted_shader)
+/// })?;
+/// # Ok(()) }
+/// ```
 pub struct Shader {
 	pub(crate) internal: std::sync::Arc<ShaderInternal>,
+	pub(crate) params: Option<std::sync::Arc<ShaderParams>>,
 }
 
 impl Shader {
+	/// Compile a WGSL shader read from the reader, using the bindings described by the init args.
+	pub fn load<Device, R>(
+		renderer: &Renderer<Device>,
+		r: R,
+		init_args: &ShaderInitArgs,
+	) -> Result<Self>
+	where
+		Device: WgpuDevice,
+		R: std::io::Read,
+	{
+		renderer.wgpu_device().with_device_info(|info| {
+			Self::from_readers(
+				info.device,
+				r,
+				wgpu::PrimitiveTopology::TriangleList,
+				init_args,
+				1,
+			)
+		})
+	}
+
+	/// Compile a WGSL shader from a byte buffer, using the bindings described by the init args.
+	pub fn from_bytes<Device: WgpuDevice>(
+		renderer: &Renderer<Device>,
+		bytes: &[u8],
+		init_args: &ShaderInitArgs,
+	) -> Result<Self> {
+		Self::load(renderer, bytes, init_args)
+	}
+
+	/// Build a shader which shares this shader's pipeline, but which binds the given user
+	/// uniforms and textures when rendering.
+	///
+	/// The params must match the [`ShaderInitArgs`] the shader was built with.
+	pub fn with_params(&self, params: ShaderParams) -> Result<Self> {
+		let init_args = &self.internal.init_args;
+		if params.uniforms.len() as u64 != init_args.uniform_size {
+			return Err(WgpuRendererError::ShaderParams(
+				"Uniform data size doesn't match shader uniform size",
+			));
+		}
+
+		if params.textures.len() != init_args.texture_count as usize
+			|| params.textures.iter().any(Option::is_none)
+		{
+			return Err(WgpuRendererError::ShaderParams(
+				"Texture count doesn't match shader texture count",
+			));
+		}
+
+		Ok(Self {
+			internal: self.internal.clone(),
+			params: Some(params.into()),
+		})
+	}
+
 	pub(crate) fn from_readers<SR>(
 		device: &wgpu::Device,
 		shader_reader: SR,
 		primitive_type: wgpu::PrimitiveTopology,
+		init_args: &ShaderInitArgs,
+		texture_slots: u32,
 	) -> Result<Self>
 	where
-		SR: std::io::Read + std::io::Seek,
+		SR: std::io::Read,
 	{
-		let internal = ShaderInternal::from_readers(device, shader_reader, primitive_type)?;
+		let internal = ShaderInternal::from_readers(
+			device,
+			shader_reader,
+			primitive_type,
+			init_args,
+			texture_slots,
+		)?;
 		Ok(Self {
 			internal: internal.into(),
+			params: None,
 		})
 	}
+
+	pub(crate) fn same_binding(&self, other: &Self) -> bool {
+		let same_params = match (&self.params, &other.params) {
+			(Some(a), Some(b)) => std::sync::Arc::ptr_eq(a, b),
+			(None, None) => true,
+			_ => false,
+		};
+		std::sync::Arc::ptr_eq(&self.internal, &other.internal) && same_params
+	}
 }
 
 impl Clone for Shader {
 	fn clone(&self) -> Self {
 		Self {
 			internal: self.internal.clone(),
+			params: self.params.clone(),
+		}
+	}
+}
+
+/// Describes the user bindings, beyond the standard sprite bindings, which a [`Shader`] uses.
+///
+/// # Example
+///
+/// ```
+/// # use riddle_renderer_wgpu::*;
+/// let args = ShaderInitArgs::new().with_uniforms(16).with_textures(1);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ShaderInitArgs {
+	pub uniform_size: u64,
+	pub texture_count: u32,
+}
+
+impl ShaderInitArgs {
+	/// Create a new init args, with no user uniforms or textures.
+	pub fn new() -> Self {
+		Self {
+			uniform_size: 0,
+			texture_count: 0,
 		}
 	}
+
+	/// Specify the size in bytes of the user uniform buffer. A size of 0 means the shader
+	/// has no user uniform buffer.
+	pub fn with_uniforms(mut self, uniform_size: u64) -> Self {
+		self.uniform_size = uniform_size;
+		self
+	}
+
+	/// Specify the number of user textures the shader samples.
+	pub fn with_textures(mut self, texture_count: u32) -> Self {
+		self.texture_count = texture_count;
+		self
+	}
+
+	fn has_user_bindings(&self) -> bool {
+		self.uniform_size > 0 || self.texture_count > 0
+	}
+}
+
+/// The user uniform and texture values bound to a [`Shader`] via [`Shader::with_params`].
+#[derive(Default, Clone)]
+pub struct ShaderParams {
+	uniforms: Vec<u8>,
+	textures: Vec<Option<Texture>>,
+}
+
+impl ShaderParams {
+	/// Create a new empty set of params.
+	pub fn new() -> Self {
+		Self {
+			uniforms: vec![],
+			textures: vec![],
+		}
+	}
+
+	/// Set the contents of the user uniform buffer.
+	pub fn with_uniforms<T: bytemuck::Pod>(mut self, uniforms: &[T]) -> Self {
+		self.uniforms = bytemuck::cast_slice(uniforms).to_vec();
+		self
+	}
+
+	/// Set the user texture at the given slot to the texture which the sprite references.
+	///
+	/// The entire texture is bound, regardless of the region of the texture the sprite
+	/// represents.
+	pub fn with_texture<Device: WgpuDevice>(mut self, slot: u32, sprite: &Sprite<Device>) -> Self {
+		let slot = slot as usize;
+		if self.textures.len() <= slot {
+			self.textures.resize(slot + 1, None);
+		}
+		self.textures[slot] = Some(sprite.texture().clone());
+		self
+	}
 }
 
 pub(crate) struct ShaderInternal {
 	pub bind_group_layout: wgpu::BindGroupLayout,
-	pipeline: wgpu::RenderPipeline,
+	pub user_bind_group_layout: Option<wgpu::BindGroupLayout>,
+	pub init_args: ShaderInitArgs,
+	pub texture_slots: u32,
+
+	module: wgpu::ShaderModule,
+	pipeline_layout: wgpu::PipelineLayout,
+	topology: wgpu::PrimitiveTopology,
+	pipelines: Mutex<HashMap<PipelineKey, Arc<wgpu::RenderPipeline>>>,
+}
+
+/// The render state which a shader's pipelines vary by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct PipelineKey {
+	pub blend_mode: BlendMode,
+	pub stencil_mode: StencilMode,
+	pub sample_count: u32,
+	pub format: wgpu::TextureFormat,
+}
+
+impl Default for PipelineKey {
+	fn default() -> Self {
+		Self {
+			blend_mode: BlendMode::default(),
+			stencil_mode: StencilMode::default(),
+			sample_count: 1,
+			format: wgpu::TextureFormat::Bgra8Unorm,
+		}
+	}
+}
+
+/// How a pipeline uses the stencil attachment, which holds the render context's mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) enum StencilMode {
+	/// The stencil is ignored.
+	#[default]
+	Disabled,
+
+	/// Fragments write to the stencil instead of the color target.
+	Write,
+
+	/// Fragments are only drawn where the stencil has been written to.
+	Inside,
+
+	/// Fragments are only drawn where the stencil hasn't been written to.
+	Outside,
+}
+
+impl From<MaskMode> for StencilMode {
+	fn from(mode: MaskMode) -> Self {
+		match mode {
+			MaskMode::Disabled => StencilMode::Disabled,
+			MaskMode::Inside => StencilMode::Inside,
+			MaskMode::Outside => StencilMode::Outside,
+		}
+	}
 }
 
+/// The format of the stencil attachments used by render targets.
+pub(crate) const STENCIL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
+
+/// The stencil value written by mask draws.
+pub(crate) const STENCIL_MASK_VALUE: u32 = 1;
+
+/// The number of textures the batch shader can sample from in a single draw call.
+pub(crate) const BATCH_TEXTURE_SLOTS: u32 = 8;
+
 impl ShaderInternal {
+	/// Compile a shader, with `texture_slots` textures and samplers in the standard bind group.
+	///
+	/// When there is more than one slot, the pipeline takes a second vertex buffer containing
+	/// a `u32` slot index per vertex.
 	pub(crate) fn from_readers<SR>(
 		device: &wgpu::Device,
 		mut shader_reader: SR,
 		primitive_type: wgpu::PrimitiveTopology,
+		init_args: &ShaderInitArgs,
+		texture_slots: u32,
 	) -> Result<Self>
 	where
-		SR: std::io::Read + std::io::Seek,
+		SR: std::io::Read,
 	{
 		let mut wgsl_buf = vec![];
 		shader_reader
@@ -60,22 +322,60 @@ impl ShaderInternal {
 			label: None,
 		});
 
-		let vertex_size = std::mem::size_of::<Vertex>();
+		let mut entries = vec![wgpu::BindGroupLayoutEntry {
+			binding: 0,
+			visibility: wgpu::ShaderStage::VERTEX,
+			ty: wgpu::BindingType::Buffer {
+				has_dynamic_offset: false,
+				ty: wgpu::BufferBindingType::Uniform,
+				min_binding_size: wgpu::BufferSize::new(64),
+			},
+			count: None,
+		}];
+		for i in 0..texture_slots {
+			entries.push(wgpu::BindGroupLayoutEntry {
+				binding: 1 + (i * 2),
+				visibility: wgpu::ShaderStage::FRAGMENT,
+				ty: wgpu::BindingType::Texture {
+					multisampled: false,
+					sample_type: wgpu::TextureSampleType::Float { filterable: true },
+					view_dimension: wgpu::TextureViewDimension::D2,
+				},
+				count: None,
+			});
+			entries.push(wgpu::BindGroupLayoutEntry {
+				binding: 2 + (i * 2),
+				visibility: wgpu::ShaderStage::FRAGMENT,
+				ty: wgpu::BindingType::Sampler {
+					filtering: true,
+					comparison: false,
+				},
+				count: None,
+			});
+		}
 
 		let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-			entries: &[
-				wgpu::BindGroupLayoutEntry {
+			entries: &entries[..],
+			label: None,
+		});
+
+		let user_bind_group_layout = if init_args.has_user_bindings() {
+			let mut entries = vec![];
+			if init_args.uniform_size > 0 {
+				entries.push(wgpu::BindGroupLayoutEntry {
 					binding: 0,
-					visibility: wgpu::ShaderStage::VERTEX,
+					visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
 					ty: wgpu::BindingType::Buffer {
 						has_dynamic_offset: false,
 						ty: wgpu::BufferBindingType::Uniform,
-						min_binding_size: wgpu::BufferSize::new(64),
+						min_binding_size: wgpu::BufferSize::new(init_args.uniform_size),
 					},
 					count: None,
-				},
-				wgpu::BindGroupLayoutEntry {
-					binding: 1,
+				});
+			}
+			for i in 0..init_args.texture_count {
+				entries.push(wgpu::BindGroupLayoutEntry {
+					binding: 1 + (i * 2),
 					visibility: wgpu::ShaderStage::FRAGMENT,
 					ty: wgpu::BindingType::Texture {
 						multisampled: false,
@@ -83,164 +383,331 @@ impl ShaderInternal {
 						view_dimension: wgpu::TextureViewDimension::D2,
 					},
 					count: None,
-				},
-				wgpu::BindGroupLayoutEntry {
-					binding: 2,
+				});
+				entries.push(wgpu::BindGroupLayoutEntry {
+					binding: 2 + (i * 2),
 					visibility: wgpu::ShaderStage::FRAGMENT,
 					ty: wgpu::BindingType::Sampler {
 						filtering: true,
 						comparison: false,
 					},
 					count: None,
-				},
-			],
-			label: None,
-		});
+				});
+			}
+
+			Some(
+				device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+					entries: &entries[..],
+					label: None,
+				}),
+			)
+		} else {
+			None
+		};
+
+		let bind_group_layouts: Vec<&wgpu::BindGroupLayout> = std::iter::once(&bind_group_layout)
+			.chain(user_bind_group_layout.iter())
+			.collect();
+
 		let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
 			label: None,
-			bind_group_layouts: &[&bind_group_layout],
+			bind_group_layouts: &bind_group_layouts[..],
 			push_constant_ranges: &[],
 		});
 
-		let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+		let internal = Self {
+			bind_group_layout,
+			user_bind_group_layout,
+			init_args: init_args.clone(),
+			texture_slots,
+			module: wgsl_module,
+			pipeline_layout,
+			topology: primitive_type,
+			pipelines: Mutex::new(HashMap::new()),
+		};
+
+		// Build the most common pipeline up front, so problems with the shader are reported
+		// when it is loaded.
+		internal.pipeline(device, PipelineKey::default());
+
+		Ok(internal)
+	}
+
+	/// Get the pipeline for the given render state, creating it if it hasn't been used before.
+	pub(crate) fn pipeline(
+		&self,
+		device: &wgpu::Device,
+		key: PipelineKey,
+	) -> Arc<wgpu::RenderPipeline> {
+		let mut pipelines = self.pipelines.lock().unwrap();
+		pipelines
+			.entry(key)
+			.or_insert_with(|| Arc::new(self.create_pipeline(device, key)))
+			.clone()
+	}
+
+	fn create_pipeline(&self, device: &wgpu::Device, key: PipelineKey) -> wgpu::RenderPipeline {
+		let vertex_size = std::mem::size_of::<Vertex>();
+		let vertex_attributes = [
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x2,
+				offset: 0,
+				shader_location: 0,
+			},
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x2,
+				offset: (std::mem::size_of::<f32>() * 2) as u64,
+				shader_location: 1,
+			},
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x4,
+				offset: (std::mem::size_of::<f32>() * 4) as u64,
+				shader_location: 2,
+			},
+		];
+		let slot_attributes = [wgpu::VertexAttribute {
+			format: wgpu::VertexFormat::Uint32,
+			offset: 0,
+			shader_location: 3,
+		}];
+
+		let mut vertex_buffers = vec![wgpu::VertexBufferLayout {
+			array_stride: vertex_size as wgpu::BufferAddress,
+			step_mode: wgpu::InputStepMode::Vertex,
+			attributes: &vertex_attributes,
+		}];
+		if self.texture_slots > 1 {
+			vertex_buffers.push(wgpu::VertexBufferLayout {
+				array_stride: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+				step_mode: wgpu::InputStepMode::Vertex,
+				attributes: &slot_attributes,
+			});
+		}
+
+		let write_mask = if key.stencil_mode == StencilMode::Write {
+			wgpu::ColorWrite::empty()
+		} else {
+			wgpu::ColorWrite::ALL
+		};
+
+		device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
 			label: None,
-			layout: Some(&pipeline_layout),
+			layout: Some(&self.pipeline_layout),
 			vertex: wgpu::VertexState {
-				module: &wgsl_module,
+				module: &self.module,
 				entry_point: "vs_main",
-				buffers: &[wgpu::VertexBufferLayout {
-					array_stride: vertex_size as wgpu::BufferAddress,
-					step_mode: wgpu::InputStepMode::Vertex,
-					attributes: &[
-						wgpu::VertexAttribute {
-							format: wgpu::VertexFormat::Float32x2,
-							offset: 0,
-							shader_location: 0,
-						},
-						wgpu::VertexAttribute {
-							format: wgpu::VertexFormat::Float32x2,
-							offset: (std::mem::size_of::<f32>() * 2) as u64,
-							shader_location: 1,
-						},
-						wgpu::VertexAttribute {
-							format: wgpu::VertexFormat::Float32x4,
-							offset: (std::mem::size_of::<f32>() * 4) as u64,
-							shader_location: 2,
-						},
-					],
-				}],
+				buffers: &vertex_buffers[..],
 			},
 			fragment: Some(wgpu::FragmentState {
-				module: &wgsl_module,
+				module: &self.module,
 				entry_point: "fs_main",
 				targets: &[wgpu::ColorTargetState {
-					format: wgpu::TextureFormat::Bgra8Unorm,
-					blend: Some(wgpu::BlendState {
-						color: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::SrcAlpha,
-							dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-							operation: wgpu::BlendOperation::Add,
-						},
-						alpha: wgpu::BlendComponent {
-							src_factor: wgpu::BlendFactor::One,
-							dst_factor: wgpu::BlendFactor::One,
-							operation: wgpu::BlendOperation::Add,
-						},
-					}),
-					write_mask: wgpu::ColorWrite::ALL,
+					format: key.format,
+					blend: blend_state(key.blend_mode),
+					write_mask,
 				}],
 			}),
 			primitive: wgpu::PrimitiveState {
-				topology: primitive_type,
+				topology: self.topology,
 				strip_index_format: None,
 				front_face: wgpu::FrontFace::Ccw,
 				cull_mode: None,
 				polygon_mode: wgpu::PolygonMode::Fill,
 				..Default::default()
 			},
-			depth_stencil: None,
+			depth_stencil: Some(depth_stencil_state(key.stencil_mode)),
 			multisample: wgpu::MultisampleState {
-				count: 1,
+				count: key.sample_count,
 				mask: !0,
 				alpha_to_coverage_enabled: false,
 			},
-		});
-
-		Ok(Self {
-			bind_group_layout,
-			pipeline: render_pipeline,
 		})
 	}
 
+	/// Bind the camera uniform and textures. If fewer textures are given than the shader has
+	/// slots, the remaining slots are bound to the first texture.
 	pub(crate) fn bind_params(
 		&self,
 		device: &wgpu::Device,
-		camera_size: Vector2<f32>,
-		view_matrix: mint::ColumnMatrix4<f32>,
-		texture: &Texture,
+		camera_uniform: wgpu::BufferBinding,
+		textures: &[&Texture],
 	) -> wgpu::BindGroup {
-		let ortho_matrix =
-			glam::Mat4::orthographic_lh(0.0, camera_size.x, camera_size.y, 0.0, 0.0, 1.0);
-		let view_matrix: glam::Mat4 = view_matrix.into();
-		let result_matrix = ortho_matrix * view_matrix;
-		let matrix_arr: &[f32; 16] = result_matrix.as_ref();
+		let textures: Vec<&Texture> = (0..self.texture_slots as usize)
+			.map(|i| textures.get(i).unwrap_or(&textures[0]))
+			.copied()
+			.collect();
 
-		let camera_uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-			label: None,
-			contents: bytemuck::cast_slice(matrix_arr),
-			usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
-		});
+		let views: Vec<wgpu::TextureView> = textures
+			.iter()
+			.map(|texture| {
+				texture
+					.internal
+					.texture
+					.create_view(&wgpu::TextureViewDescriptor {
+						..Default::default()
+					})
+			})
+			.collect();
+
+		let mut entries = vec![wgpu::BindGroupEntry {
+			binding: 0,
+			resource: wgpu::BindingResource::Buffer(camera_uniform),
+		}];
+		for (i, (view, texture)) in views.iter().zip(textures.iter()).enumerate() {
+			let i = i as u32;
+			entries.push(wgpu::BindGroupEntry {
+				binding: 1 + (i * 2),
+				resource: wgpu::BindingResource::TextureView(view),
+			});
+			entries.push(wgpu::BindGroupEntry {
+				binding: 2 + (i * 2),
+				resource: wgpu::BindingResource::Sampler(&texture.internal.sampler),
+			});
+		}
 
 		device.create_bind_group(&wgpu::BindGroupDescriptor {
 			layout: &self.bind_group_layout,
-			entries: &[
-				wgpu::BindGroupEntry {
-					binding: 0,
-					resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-						buffer: &camera_uniform,
-						offset: 0,
-						size: NonZeroU64::new(std::mem::size_of::<f32>() as u64 * 16),
-					}),
-				},
-				wgpu::BindGroupEntry {
-					binding: 1,
-					resource: wgpu::BindingResource::TextureView(
-						&texture
-							.internal
-							.texture
-							.create_view(&wgpu::TextureViewDescriptor {
-								..Default::default()
-							}),
-					),
-				},
-				wgpu::BindGroupEntry {
-					binding: 2,
-					resource: wgpu::BindingResource::Sampler(&texture.internal.sampler),
-				},
-			],
+			entries: &entries[..],
 			label: None,
 		})
 	}
 
-	pub(crate) fn begin_render_pass<'a>(
-		&'a self,
-		target: &'a TextureView,
-		encoder: &'a mut CommandEncoder,
-		load_op: wgpu::LoadOp<wgpu::Color>,
-	) -> RenderPass<'a> {
-		let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-			color_attachments: &[wgpu::RenderPassColorAttachment {
-				view: target,
-				resolve_target: None,
-				ops: wgpu::Operations {
-					load: load_op,
-					store: true,
-				},
-			}],
-			depth_stencil_attachment: None,
+	pub(crate) fn bind_user_params(
+		&self,
+		device: &wgpu::Device,
+		queue: &wgpu::Queue,
+		uniform_stream: &mut StreamBuffer,
+		params: &ShaderParams,
+	) -> Option<wgpu::BindGroup> {
+		let layout = self.user_bind_group_layout.as_ref()?;
+
+		let uniform_slice = if params.uniforms.is_empty() {
+			None
+		} else {
+			Some(uniform_stream.write(device, queue, &params.uniforms[..]))
+		};
+
+		let views: Vec<wgpu::TextureView> = params
+			.textures
+			.iter()
+			.flatten()
+			.map(|texture| {
+				texture
+					.internal
+					.texture
+					.create_view(&wgpu::TextureViewDescriptor {
+						..Default::default()
+					})
+			})
+			.collect();
+
+		let mut entries = vec![];
+		if let Some(uniform_slice) = &uniform_slice {
+			entries.push(wgpu::BindGroupEntry {
+				binding: 0,
+				resource: wgpu::BindingResource::Buffer(uniform_slice.binding()),
+			});
+		}
+		for (i, (view, texture)) in views
+			.iter()
+			.zip(params.textures.iter().flatten())
+			.enumerate()
+		{
+			let i = i as u32;
+			entries.push(wgpu::BindGroupEntry {
+				binding: 1 + (i * 2),
+				resource: wgpu::BindingResource::TextureView(view),
+			});
+			entries.push(wgpu::BindGroupEntry {
+				binding: 2 + (i * 2),
+				resource: wgpu::BindingResource::Sampler(&texture.internal.sampler),
+			});
+		}
+
+		Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout,
+			entries: &entries[..],
 			label: None,
-		});
-		rpass.set_pipeline(&self.pipeline);
-		rpass
+		}))
+	}
+}
+
+fn blend_state(blend_mode: BlendMode) -> Option<wgpu::BlendState> {
+	let component = |src_factor, dst_factor| wgpu::BlendComponent {
+		src_factor,
+		dst_factor,
+		operation: wgpu::BlendOperation::Add,
+	};
+
+	match blend_mode {
+		BlendMode::Alpha => Some(wgpu::BlendState {
+			color: component(
+				wgpu::BlendFactor::SrcAlpha,
+				wgpu::BlendFactor::OneMinusSrcAlpha,
+			),
+			alpha: component(wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+		}),
+		BlendMode::PremultipliedAlpha => Some(wgpu::BlendState {
+			color: component(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcAlpha),
+			alpha: component(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcAlpha),
+		}),
+		BlendMode::Additive => Some(wgpu::BlendState {
+			color: component(wgpu::BlendFactor::SrcAlpha, wgpu::BlendFactor::One),
+			alpha: component(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+		}),
+		BlendMode::Multiply => Some(wgpu::BlendState {
+			color: component(wgpu::BlendFactor::Dst, wgpu::BlendFactor::Zero),
+			alpha: component(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+		}),
+		BlendMode::Screen => Some(wgpu::BlendState {
+			color: component(wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrc),
+			alpha: component(wgpu::BlendFactor::Zero, wgpu::BlendFactor::One),
+		}),
+		BlendMode::Replace => None,
+	}
+}
+
+fn depth_stencil_state(stencil_mode: StencilMode) -> wgpu::DepthStencilState {
+	let (compare, pass_op, write_mask) = match stencil_mode {
+		StencilMode::Disabled => (
+			wgpu::CompareFunction::Always,
+			wgpu::StencilOperation::Keep,
+			0,
+		),
+		StencilMode::Write => (
+			wgpu::CompareFunction::Always,
+			wgpu::StencilOperation::Replace,
+			!0,
+		),
+		StencilMode::Inside => (
+			wgpu::CompareFunction::Equal,
+			wgpu::StencilOperation::Keep,
+			0,
+		),
+		StencilMode::Outside => (
+			wgpu::CompareFunction::NotEqual,
+			wgpu::StencilOperation::Keep,
+			0,
+		),
+	};
+
+	let face = wgpu::StencilFaceState {
+		compare,
+		fail_op: wgpu::StencilOperation::Keep,
+		depth_fail_op: wgpu::StencilOperation::Keep,
+		pass_op,
+	};
+
+	wgpu::DepthStencilState {
+		format: STENCIL_FORMAT,
+		depth_write_enabled: false,
+		depth_compare: wgpu::CompareFunction::Always,
+		stencil: wgpu::StencilState {
+			front: face,
+			back: face,
+			read_mask: !0,
+			write_mask,
+		},
+		bias: wgpu::DepthBiasState::default(),
 	}
 }
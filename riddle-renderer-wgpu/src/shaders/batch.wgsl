@@ -0,0 +1,91 @@
+// The default sprite shader, extended to sample from one of several textures selected by a
+// per vertex texture slot, so draws using different textures can be batched together.
+
+struct VertexOutput {
+    [[location(0)]] tex_coord: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+    [[location(2), interpolate(flat)]] slot: u32;
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[block]]
+struct Locals {
+    transform: mat4x4<f32>;
+};
+[[group(0), binding(0)]]
+var r_locals: Locals;
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] in_position: vec2<f32>,
+    [[location(1)]] in_tex_coord: vec2<f32>,
+    [[location(2)]] in_color: vec4<f32>,
+    [[location(3)]] in_slot: u32
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = in_tex_coord;
+    out.color = in_color;
+    out.slot = in_slot;
+    out.position = r_locals.transform * vec4<f32>(in_position.x, in_position.y, 0.0, 1.0);
+    return out;
+}
+
+[[group(0), binding(1)]]
+var r_color_0: texture_2d<f32>;
+[[group(0), binding(2)]]
+var r_sampler_0: sampler;
+[[group(0), binding(3)]]
+var r_color_1: texture_2d<f32>;
+[[group(0), binding(4)]]
+var r_sampler_1: sampler;
+[[group(0), binding(5)]]
+var r_color_2: texture_2d<f32>;
+[[group(0), binding(6)]]
+var r_sampler_2: sampler;
+[[group(0), binding(7)]]
+var r_color_3: texture_2d<f32>;
+[[group(0), binding(8)]]
+var r_sampler_3: sampler;
+[[group(0), binding(9)]]
+var r_color_4: texture_2d<f32>;
+[[group(0), binding(10)]]
+var r_sampler_4: sampler;
+[[group(0), binding(11)]]
+var r_color_5: texture_2d<f32>;
+[[group(0), binding(12)]]
+var r_sampler_5: sampler;
+[[group(0), binding(13)]]
+var r_color_6: texture_2d<f32>;
+[[group(0), binding(14)]]
+var r_sampler_6: sampler;
+[[group(0), binding(15)]]
+var r_color_7: texture_2d<f32>;
+[[group(0), binding(16)]]
+var r_sampler_7: sampler;
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    // Gradients are taken outside of the branches, as the slot may vary within a quad
+    var ddx: vec2<f32> = dpdx(in.tex_coord);
+    var ddy: vec2<f32> = dpdy(in.tex_coord);
+
+    var tex: vec4<f32>;
+    if (in.slot == 0u) {
+        tex = textureSampleGrad(r_color_0, r_sampler_0, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 1u) {
+        tex = textureSampleGrad(r_color_1, r_sampler_1, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 2u) {
+        tex = textureSampleGrad(r_color_2, r_sampler_2, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 3u) {
+        tex = textureSampleGrad(r_color_3, r_sampler_3, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 4u) {
+        tex = textureSampleGrad(r_color_4, r_sampler_4, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 5u) {
+        tex = textureSampleGrad(r_color_5, r_sampler_5, in.tex_coord, ddx, ddy);
+    } elseif (in.slot == 6u) {
+        tex = textureSampleGrad(r_color_6, r_sampler_6, in.tex_coord, ddx, ddy);
+    } else {
+        tex = textureSampleGrad(r_color_7, r_sampler_7, in.tex_coord, ddx, ddy);
+    }
+    return tex * in.color;
+}
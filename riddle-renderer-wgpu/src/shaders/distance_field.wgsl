@@ -0,0 +1,79 @@
+struct VertexOutput {
+    [[location(0)]] tex_coord: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[block]]
+struct Locals {
+    transform: mat4x4<f32>;
+};
+[[group(0), binding(0)]]
+var r_locals: Locals;
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] in_position: vec2<f32>,
+    [[location(1)]] in_tex_coord: vec2<f32>,
+    [[location(2)]] in_color: vec4<f32>
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = in_tex_coord;
+    out.color = in_color;
+    out.position = r_locals.transform * vec4<f32>(in_position.x, in_position.y, 0.0, 1.0);
+    return out;
+}
+
+[[group(0), binding(1)]]
+var r_color: texture_2d<f32>;
+[[group(0), binding(2)]]
+var r_sampler: sampler;
+
+// widths: outline width, glow width, softness, pixel distance
+[[block]]
+struct Params {
+    outline_color: vec4<f32>;
+    glow_color: vec4<f32>;
+    widths: vec4<f32>;
+};
+[[group(1), binding(0)]]
+var r_params: Params;
+
+// Place the premultiplied color a over b
+fn over(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {
+    return a + b * (1.0 - a.a);
+}
+
+fn premultiply(color: vec4<f32>, alpha: f32) -> vec4<f32> {
+    var a: f32 = color.a * alpha;
+    return vec4<f32>(color.rgb * a, a);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var field: f32 = textureSample(r_color, r_sampler, in.tex_coord).a;
+    var outline_width: f32 = r_params.widths.x;
+    var glow_width: f32 = r_params.widths.y;
+    var softness: f32 = r_params.widths.z;
+
+    // Anti alias over one target pixel, whatever the scale the text is drawn at
+    var aa: f32 = max(fwidth(field) * 0.5, 0.0001);
+
+    var fill_alpha: f32 = smoothStep(0.5 - aa - softness, 0.5 + aa, field);
+    var outline_edge: f32 = 0.5 - outline_width;
+    var outline_alpha: f32 = smoothStep(outline_edge - aa - softness, outline_edge + aa, field);
+    var glow_alpha: f32 = 0.0;
+    if (glow_width > 0.0) {
+        glow_alpha = smoothStep(outline_edge - glow_width, outline_edge, field);
+    }
+
+    var fill: vec4<f32> = premultiply(in.color, fill_alpha);
+    var outline: vec4<f32> = premultiply(r_params.outline_color, outline_alpha * in.color.a);
+    var glow: vec4<f32> = premultiply(r_params.glow_color, glow_alpha * in.color.a);
+    var result: vec4<f32> = over(fill, over(outline, glow));
+
+    if (result.a <= 0.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    return vec4<f32>(result.rgb / result.a, result.a);
+}
@@ -0,0 +1,14 @@
+// values: intensity
+// The blurred bright areas are the pass input, and the scene before bloom is user texture 0.
+
+[[group(1), binding(1)]]
+var r_scene: texture_2d<f32>;
+[[group(1), binding(2)]]
+var r_scene_sampler: sampler;
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var bloom: vec4<f32> = textureSample(r_color, r_sampler, in.tex_coord);
+    var scene: vec4<f32> = textureSample(r_scene, r_scene_sampler, in.tex_coord);
+    return vec4<f32>(scene.rgb + (bloom.rgb * r_params.values.x), scene.a);
+}
@@ -0,0 +1,9 @@
+// values: brightness threshold
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var color: vec4<f32> = textureSample(r_color, r_sampler, in.tex_coord);
+    var brightness: f32 = max(color.r, max(color.g, color.b));
+    var contribution: f32 = max(brightness - r_params.values.x, 0.0) / max(brightness, 0.0001);
+    return vec4<f32>(color.rgb * contribution, 1.0);
+}
@@ -0,0 +1,16 @@
+// values: direction x, direction y, radius in texels
+
+fn sample_offset(uv: vec2<f32>, offset: vec2<f32>) -> vec4<f32> {
+    return textureSample(r_color, r_sampler, uv + offset) + textureSample(r_color, r_sampler, uv - offset);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var tap: vec2<f32> = r_params.texel_size * vec2<f32>(r_params.values.x, r_params.values.y) * (r_params.values.z / 4.0);
+    var sum: vec4<f32> = textureSample(r_color, r_sampler, in.tex_coord) * 0.2270270270;
+    sum = sum + sample_offset(in.tex_coord, tap * 1.0) * 0.1945945946;
+    sum = sum + sample_offset(in.tex_coord, tap * 2.0) * 0.1216216216;
+    sum = sum + sample_offset(in.tex_coord, tap * 3.0) * 0.0540540541;
+    sum = sum + sample_offset(in.tex_coord, tap * 4.0) * 0.0162162162;
+    return sum;
+}
@@ -0,0 +1,26 @@
+// values: LUT size, intensity
+// The LUT is user texture 0, laid out as `size` slices of `size` x `size` texels side by side,
+// with red increasing along x, green along y and blue across the slices.
+
+[[group(1), binding(1)]]
+var r_lut: texture_2d<f32>;
+[[group(1), binding(2)]]
+var r_lut_sampler: sampler;
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var color: vec4<f32> = textureSample(r_color, r_sampler, in.tex_coord);
+    var size: f32 = r_params.values.x;
+    var rgb: vec3<f32> = clamp(color.rgb, vec3<f32>(0.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 1.0));
+
+    var blue: f32 = rgb.b * (size - 1.0);
+    var slice_low: f32 = floor(blue);
+    var slice_high: f32 = min(slice_low + 1.0, size - 1.0);
+    var x: f32 = ((rgb.r * (size - 1.0)) + 0.5) / (size * size);
+    var y: f32 = ((rgb.g * (size - 1.0)) + 0.5) / size;
+
+    var low: vec4<f32> = textureSample(r_lut, r_lut_sampler, vec2<f32>(x + (slice_low / size), y));
+    var high: vec4<f32> = textureSample(r_lut, r_lut_sampler, vec2<f32>(x + (slice_high / size), y));
+    var graded: vec3<f32> = mix(low.rgb, high.rgb, vec3<f32>(blue - slice_low, blue - slice_low, blue - slice_low));
+    return vec4<f32>(mix(rgb, graded, vec3<f32>(r_params.values.y, r_params.values.y, r_params.values.y)), color.a);
+}
@@ -0,0 +1,14 @@
+// values: scanline intensity, curvature
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var centered: vec2<f32> = in.tex_coord - vec2<f32>(0.5, 0.5);
+    var uv: vec2<f32> = in.tex_coord + (centered * dot(centered, centered) * r_params.values.y);
+
+    var inside: f32 = step(0.0, uv.x) * step(uv.x, 1.0) * step(0.0, uv.y) * step(uv.y, 1.0);
+    var color: vec4<f32> = textureSample(r_color, r_sampler, uv);
+
+    var scanline: f32 = 0.5 + (0.5 * sin((uv.y / r_params.texel_size.y) * 3.14159265));
+    var brightness: f32 = 1.0 - (r_params.values.x * (1.0 - scanline));
+    return vec4<f32>(color.rgb * brightness * inside, 1.0);
+}
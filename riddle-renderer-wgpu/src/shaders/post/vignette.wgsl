@@ -0,0 +1,10 @@
+// values: intensity, radius, softness
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var color: vec4<f32> = textureSample(r_color, r_sampler, in.tex_coord);
+    var dist: f32 = distance(in.tex_coord, vec2<f32>(0.5, 0.5));
+    var falloff: f32 = 1.0 - smoothStep(r_params.values.y - r_params.values.z, r_params.values.y, dist);
+    var shade: f32 = mix(1.0, falloff, r_params.values.x);
+    return vec4<f32>(color.rgb * shade, color.a);
+}
@@ -70,6 +70,59 @@ impl<Device: WgpuDevice> Sprite<Device> {
 			source_rect,
 		})
 	}
+
+	/// Copy an image in to the sprite's texture, with the image's top left corner at the given
+	/// location relative to the top left of the sprite.
+	///
+	/// The texture is updated in place, so every sprite sharing the texture, such as subsprites
+	/// and other sprites in the same atlas, sees the change. The image must fit within the
+	/// sprite.
+	///
+	/// The update is applied before any rendering which hasn't been presented yet, including
+	/// draws already made in a render context which is still open.
+	///
+	/// Sprites created with mipmaps can't be updated, and return
+	/// [`WgpuRendererError::TextureWrite`].
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+	/// let renderer = Renderer::new_from_window(&window)?;
+	///
+	/// let canvas = Image::new(64, 64);
+	/// let sprite = Sprite::new_from_image(&renderer, &canvas, &SpriteInitArgs::new())?;
+	///
+	/// // Draw on the CPU, then upload just the changed region
+	/// let mut brush = Image::new(4, 4);
+	/// brush.fill(Color::<u8>::RED);
+	/// sprite.update_region(&brush, vec2(10, 10))?;
+	/// # Ok(()) }
+	/// ```
+	pub fn update_region(&self, image: &image::Image, location: Vector2<u32>) -> Result<()> {
+		let source_location: Vector2<u32> = self.source_rect.location.convert();
+		let source_dimensions: Vector2<u32> = self.source_rect.dimensions.convert();
+		if !region_fits(location, image.dimensions(), source_dimensions) {
+			return Err(WgpuRendererError::TextureWrite(
+				"Region is outside of the sprite",
+			));
+		}
+
+		self.renderer.wgpu_device().with_device_info(|info| {
+			self.texture
+				.write_image(info.queue, image, source_location + location)
+		})
+	}
+
+	pub(crate) fn texture(&self) -> &Texture {
+		&self.texture
+	}
+
+	pub(crate) fn source_rect(&self) -> &Rect<f32> {
+		&self.source_rect
+	}
 }
 
 impl<Device: WgpuDevice> CommonSprite<Renderer<Device>> for Sprite<Device> {
@@ -83,9 +136,9 @@ impl<Device: WgpuDevice> CommonSprite<Renderer<Device>> for Sprite<Device> {
 				info.device,
 				info.queue,
 				&img,
-				init_args.mag_filter,
-				init_args.min_filter,
+				init_args,
 				TextureType::Plain,
+				renderer.srgb_texture(init_args),
 			))
 		})?;
 		Self::from_texture(renderer, &texture)
@@ -115,81 +168,93 @@ impl<Device: WgpuDevice> CommonSprite<Renderer<Device>> for Sprite<Device> {
 		args: &SpriteRenderArgs,
 		parts: &[(Rect<f32>, Vector2<f32>)],
 	) -> Result<()> {
-		let rot: glam::Mat2 = glam::Mat2::from_angle(args.angle);
-		let scale: glam::Mat2 = glam::Mat2::from_diagonal(args.scale.into());
-		let origin: glam::Vec2 = args.location.into();
-		let pivot: glam::Vec2 = args.pivot.into();
-
-		let Vector2::<f32> {
-			x: tex_width,
-			y: tex_height,
-		} = self.texture.internal.dimensions.convert();
-
-		let vertex_data: Vec<Vertex> = parts
-			.iter()
-			.flat_map(|(src_rect, location)| {
-				let location = glam::Vec2::from(*location);
-				let src_rect = Rect::new(
-					self.source_rect.location + src_rect.location,
-					src_rect.dimensions,
-				);
-
-				let pos_topleft: glam::Vec2 = location - pivot;
-				let pos_topright: glam::Vec2 = pos_topleft + glam::vec2(src_rect.dimensions.x, 0.0);
-				let pos_bottomleft: glam::Vec2 =
-					pos_topleft + glam::vec2(0.0, src_rect.dimensions.y);
-				let pos_bottomright: glam::Vec2 =
-					pos_bottomleft + glam::vec2(src_rect.dimensions.x, 0.0);
-
-				let uv_top = src_rect.location.y / (tex_height as f32);
-				let uv_left = src_rect.location.x / (tex_width as f32);
-				let uv_bottom = uv_top + (src_rect.dimensions.y / (tex_height as f32));
-				let uv_right = uv_left + (src_rect.dimensions.x / (tex_width as f32));
-
-				let color_arr: [f32; 4] = args.diffuse_color.clone().into();
-
-				vec![
-					Vertex::ptc(
-						origin + (rot * (scale * pos_topleft)),
-						[uv_left, uv_top],
-						&color_arr,
-					),
-					Vertex::ptc(
-						origin + (rot * (scale * pos_bottomleft)),
-						[uv_left, uv_bottom],
-						&color_arr,
-					),
-					Vertex::ptc(
-						origin + (rot * (scale * pos_bottomright)),
-						[uv_right, uv_bottom],
-						&color_arr,
-					),
-					Vertex::ptc(
-						origin + (rot * (scale * pos_topright)),
-						[uv_right, uv_top],
-						&color_arr,
-					),
-				]
-			})
-			.collect();
-
-		let index_data: Vec<u16> = parts
-			.iter()
-			.enumerate()
-			.flat_map(|(i, _)| {
-				let base = (i as u16) * 4;
-				vec![1 + base, 2 + base, base, 2 + base, base, 3 + base]
-			})
-			.collect();
-
-		let renderable = Renderable {
-			texture: self.texture.clone(),
-			shader: self.renderer.standard_res().default_shader.clone(),
-			verts: &vertex_data[..],
-			indices: &index_data[..],
-		};
+		self.render_regions_with_shader(
+			render_ctx,
+			args,
+			&self.renderer.standard_res().default_shader,
+			parts,
+		)
+	}
+
+	fn render_regions_with_shader<Ctx: RenderContext<Renderer<Device>> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		shader: &Shader,
+		parts: &[(Rect<f32>, Vector2<f32>)],
+	) -> Result<()> {
+		for parts in parts.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_regions_geometry(
+				self.texture.internal.dimensions.convert(),
+				&self.source_rect,
+				args,
+				parts,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode: args.blend_mode,
+			};
 
-		render_ctx.draw(&renderable)
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
+	}
+
+	fn render_stretched_regions<Ctx: RenderContext<Renderer<Device>> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		args: &SpriteRenderArgs,
+		parts: &[(Rect<f32>, Rect<f32>)],
+	) -> Result<()> {
+		for parts in parts.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_stretched_regions_geometry(
+				self.texture.internal.dimensions.convert(),
+				&self.source_rect,
+				args,
+				parts,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: self.renderer.standard_res().default_shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode: args.blend_mode,
+			};
+
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
+	}
+
+	fn render_quads<Ctx: RenderContext<Renderer<Device>> + ?Sized>(
+		&self,
+		render_ctx: &mut Ctx,
+		blend_mode: BlendMode,
+		quads: &[SpriteQuad],
+	) -> Result<()> {
+		for quads in quads.chunks(MAX_QUADS_PER_RENDERABLE) {
+			let (vertex_data, index_data) = sprite_quads_geometry(
+				self.texture.internal.dimensions.convert(),
+				&self.source_rect,
+				quads,
+			);
+
+			let renderable = Renderable {
+				texture: self.texture.clone(),
+				shader: self.renderer.standard_res().default_shader.clone(),
+				verts: &vertex_data[..],
+				indices: &index_data[..],
+				blend_mode,
+			};
+
+			render_ctx.draw(&renderable)?;
+		}
+		Ok(())
 	}
 }
 
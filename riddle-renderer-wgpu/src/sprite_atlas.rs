@@ -74,14 +74,15 @@ where
 		let packing_images: Vec<&image::Image> = images.iter().map(|(img, _)| img).collect();
 		let packed = image::ImagePacker::new().pack(&packing_images[..])?;
 
+		let init_args = SpriteInitArgs::new().with_filter_modes(mag_filter, min_filter);
 		let texture = renderer.wgpu_device().with_device_info(|info| {
 			Ok(Texture::from_image(
 				info.device,
 				info.queue,
 				packed.image(),
-				mag_filter,
-				min_filter,
+				&init_args,
 				TextureType::Plain,
+				renderer.srgb_texture(&init_args),
 			))
 		})?;
 
@@ -29,6 +29,9 @@ pub struct SpriteRenderTarget<Device: WgpuDevice> {
 	renderer: Renderer<Device>,
 
 	texture: Texture,
+	multisample: Option<MultisampleTexture>,
+	stencil: StencilTexture,
+	sample_count: u32,
 	sprite: Sprite<Device>,
 }
 
@@ -38,14 +41,67 @@ where
 {
 	/// Create a new render target with the specified dimensions
 	pub fn new(renderer: &Renderer<Device>, dimensions: Vector2<u32>) -> Result<Self> {
-		let texture = renderer.wgpu_device().with_device_info(|info| {
-			Ok(Texture::new(
+		Self::new_multisampled(renderer, dimensions, 1)
+	}
+
+	/// Create a new render target with the specified dimensions, which renders with the given
+	/// number of samples per pixel. A count greater than 1 enables MSAA, with the samples
+	/// resolved in to the target's sprite at the end of each render pass. The count must be 1
+	/// or 4.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?;
+	/// # let window = WindowBuilder::new().build(rdl.context())?;
+	/// # let renderer = Renderer::new_from_window(&window)?;
+	/// let target = SpriteRenderTarget::new_multisampled(&renderer, vec2(100, 100), 4)?;
+	/// # Ok(()) }
+	/// ```
+	pub fn new_multisampled(
+		renderer: &Renderer<Device>,
+		dimensions: Vector2<u32>,
+		sample_count: u32,
+	) -> Result<Self> {
+		Self::new_with_init_args(
+			renderer,
+			dimensions,
+			sample_count,
+			&SpriteInitArgs::new().with_filter_modes(FilterMode::Linear, FilterMode::Linear),
+		)
+	}
+
+	/// Create a new render target, with its sprite sampled as described by the init args.
+	pub(crate) fn new_with_init_args(
+		renderer: &Renderer<Device>,
+		dimensions: Vector2<u32>,
+		sample_count: u32,
+		init_args: &SpriteInitArgs,
+	) -> Result<Self> {
+		validate_sample_count(sample_count)?;
+
+		let (texture, multisample, stencil) = renderer.wgpu_device().with_device_info(|info| {
+			let texture = Texture::new(
 				info.device,
-				FilterMode::Linear,
-				FilterMode::Linear,
+				init_args,
 				TextureType::RenderTarget,
 				dimensions,
-			))
+				renderer.srgb_texture(init_args),
+			);
+			let multisample = if sample_count > 1 {
+				Some(MultisampleTexture::new(
+					info.device,
+					dimensions,
+					sample_count,
+					texture.internal.format,
+				))
+			} else {
+				None
+			};
+			let stencil = StencilTexture::new(info.device, dimensions, sample_count);
+			Ok((texture, multisample, stencil))
 		})?;
 
 		let sprite = Sprite::from_texture(renderer, &texture)?;
@@ -54,6 +110,9 @@ where
 			renderer: renderer.clone(),
 
 			texture,
+			multisample,
+			stencil,
+			sample_count,
 			sprite,
 		})
 	}
@@ -96,6 +155,52 @@ where
 	pub fn sprite(&self) -> &Sprite<Device> {
 		&self.sprite
 	}
+
+	/// Copy the contents of the render target back in to an image.
+	///
+	/// This blocks until the GPU has finished rendering to the target.
+	///
+	/// # Example
+	///
+	/// ```no_run
+	/// # use riddle::{common::Color, platform::*, renderer::*, math::*, *};
+	/// # fn main() -> Result<(), RiddleError> {
+	/// # let rdl =  RiddleLib::new()?;
+	/// # let window = WindowBuilder::new().build(rdl.context())?;
+	/// # let renderer = Renderer::new_from_window(&window)?;
+	/// let target = SpriteRenderTarget::new(&renderer, vec2(100, 100))?;
+	///
+	/// target.render(|target_ctx| {
+	///     target_ctx.clear(Color::RED)
+	/// })?;
+	///
+	/// let img = target.read_image()?;
+	/// assert_eq!(Color::<u8>::RED, img.get_pixel([50, 50]));
+	/// # Ok(()) }
+	/// ```
+	pub fn read_image(&self) -> Result<image::Image> {
+		futures::executor::block_on(self.read_texture_image(true))
+	}
+
+	/// Copy the contents of the render target back in to an image.
+	///
+	/// The returned future doesn't poll the device itself, and only completes once the caller
+	/// drives device polling, for example by calling `wgpu::Device::poll` through
+	/// [`WgpuDevice::with_device_info`], or by continuing to render frames.
+	pub async fn read_image_async(&self) -> Result<image::Image> {
+		self.read_texture_image(false).await
+	}
+
+	async fn read_texture_image(&self, wait: bool) -> Result<image::Image> {
+		read_texture_image(
+			self.renderer.wgpu_device(),
+			&self.texture.internal.texture,
+			self.texture.internal.format,
+			self.texture.internal.dimensions,
+			wait,
+		)
+		.await
+	}
 }
 
 impl<Device> WgpuRenderTargetDesc<Device> for &SpriteRenderTarget<Device>
@@ -106,19 +211,39 @@ where
 		self.sprite.dimensions()
 	}
 
+	fn physical_dimensions(&self) -> Vector2<u32> {
+		self.texture.internal.dimensions
+	}
+
+	#[inline]
+	fn sample_count(&self) -> u32 {
+		self.sample_count
+	}
+
+	#[inline]
+	fn format(&self) -> wgpu::TextureFormat {
+		self.texture.internal.format
+	}
+
 	#[inline]
-	fn with_view<F: FnOnce(&wgpu::TextureView) -> Result<()>>(&self, f: F) -> Result<()> {
+	fn with_views<F>(&self, f: F) -> Result<()>
+	where
+		F: FnOnce(&wgpu::TextureView, Option<&wgpu::TextureView>, &wgpu::TextureView) -> Result<()>,
+	{
 		let view = self
 			.texture
 			.internal
 			.texture
 			.create_view(&wgpu::TextureViewDescriptor {
-				format: Some(wgpu::TextureFormat::Bgra8Unorm),
+				format: Some(self.texture.internal.format),
 				dimension: Some(wgpu::TextureViewDimension::D2),
 				aspect: wgpu::TextureAspect::All,
 				..Default::default()
 			});
-		f(&view)
+		match &self.multisample {
+			Some(multisample) => f(&multisample.view, Some(&view), &self.stencil.view),
+			None => f(&view, None, &self.stencil.view),
+		}
 	}
 
 	fn renderer(&self) -> &Renderer<Device> {
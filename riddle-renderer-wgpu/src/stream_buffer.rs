@@ -0,0 +1,169 @@
+use std::{ops::Range, sync::Arc};
+
+/// A GPU buffer which data is streamed in to, reused across flushes and frames.
+///
+/// Each write is appended after the previous one, since all writes for a frame are staged
+/// before the frame's command buffer is submitted. Once the frame has been submitted the
+/// buffer can be rewound and reused, as queue writes are ordered after earlier submissions.
+///
+/// If a write doesn't fit in the remaining space, the current buffer is retired until the
+/// end of the frame and a larger one is allocated.
+pub(crate) struct StreamBuffer {
+	buffer: Arc<wgpu::Buffer>,
+	capacity: u64,
+	cursor: u64,
+	usage: wgpu::BufferUsage,
+	alignment: u64,
+	retired: Vec<Arc<wgpu::Buffer>>,
+	bytes_written: u64,
+}
+
+/// A region of a [`StreamBuffer`] which has been written to.
+pub(crate) struct StreamSlice {
+	pub buffer: Arc<wgpu::Buffer>,
+	pub range: Range<u64>,
+}
+
+impl StreamSlice {
+	pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+		self.buffer.slice(self.range.clone())
+	}
+
+	pub fn binding(&self) -> wgpu::BufferBinding<'_> {
+		wgpu::BufferBinding {
+			buffer: &self.buffer,
+			offset: self.range.start,
+			size: wgpu::BufferSize::new(self.range.end - self.range.start),
+		}
+	}
+}
+
+impl StreamBuffer {
+	pub fn new(
+		device: &wgpu::Device,
+		usage: wgpu::BufferUsage,
+		capacity: u64,
+		alignment: u64,
+	) -> Self {
+		let usage = usage | wgpu::BufferUsage::COPY_DST;
+		Self {
+			buffer: Self::create_buffer(device, usage, capacity),
+			capacity,
+			cursor: 0,
+			usage,
+			alignment,
+			retired: vec![],
+			bytes_written: 0,
+		}
+	}
+
+	/// Write data to the buffer, returning the region of the buffer which holds the data.
+	///
+	/// The data is padded to a multiple of [`wgpu::COPY_BUFFER_ALIGNMENT`].
+	pub fn write(
+		&mut self,
+		device: &wgpu::Device,
+		queue: &wgpu::Queue,
+		data: &[u8],
+	) -> StreamSlice {
+		let len = data.len() as u64;
+		let padded_len = align_to(len, wgpu::COPY_BUFFER_ALIGNMENT);
+		let offset = align_to(self.cursor, self.alignment);
+
+		if offset + padded_len > self.capacity {
+			let capacity = (self.capacity * 2).max(padded_len.next_power_of_two());
+			let buffer = Self::create_buffer(device, self.usage, capacity);
+			self.retired
+				.push(std::mem::replace(&mut self.buffer, buffer));
+			self.capacity = capacity;
+			self.cursor = 0;
+			return self.write(device, queue, data);
+		}
+
+		if padded_len == len {
+			queue.write_buffer(&self.buffer, offset, data);
+		} else {
+			let mut padded = data.to_vec();
+			padded.resize(padded_len as usize, 0);
+			queue.write_buffer(&self.buffer, offset, &padded);
+		}
+
+		self.cursor = offset + padded_len;
+		self.bytes_written += padded_len;
+		StreamSlice {
+			buffer: self.buffer.clone(),
+			range: offset..offset + len,
+		}
+	}
+
+	/// Rewind the buffer once all writes have been submitted, releasing any retired buffers.
+	pub fn reset(&mut self) {
+		self.cursor = 0;
+		self.retired.clear();
+		self.bytes_written = 0;
+	}
+
+	fn create_buffer(
+		device: &wgpu::Device,
+		usage: wgpu::BufferUsage,
+		size: u64,
+	) -> Arc<wgpu::Buffer> {
+		Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size,
+			usage,
+			mapped_at_creation: false,
+		}))
+	}
+}
+
+/// The set of stream buffers used by a single [`BufferedRenderer`](crate::BufferedRenderer).
+pub(crate) struct StreamBuffers {
+	pub vertex: StreamBuffer,
+	pub index: StreamBuffer,
+	pub uniform: StreamBuffer,
+}
+
+impl StreamBuffers {
+	const INITIAL_VERTEX_CAPACITY: u64 = 1 << 18;
+	const INITIAL_INDEX_CAPACITY: u64 = 1 << 16;
+	const INITIAL_UNIFORM_CAPACITY: u64 = 1 << 14;
+
+	pub fn new(device: &wgpu::Device) -> Self {
+		Self {
+			vertex: StreamBuffer::new(
+				device,
+				wgpu::BufferUsage::VERTEX,
+				Self::INITIAL_VERTEX_CAPACITY,
+				wgpu::COPY_BUFFER_ALIGNMENT,
+			),
+			index: StreamBuffer::new(
+				device,
+				wgpu::BufferUsage::INDEX,
+				Self::INITIAL_INDEX_CAPACITY,
+				wgpu::COPY_BUFFER_ALIGNMENT,
+			),
+			uniform: StreamBuffer::new(
+				device,
+				wgpu::BufferUsage::UNIFORM,
+				Self::INITIAL_UNIFORM_CAPACITY,
+				wgpu::BIND_BUFFER_ALIGNMENT,
+			),
+		}
+	}
+
+	pub fn reset(&mut self) {
+		self.vertex.reset();
+		self.index.reset();
+		self.uniform.reset();
+	}
+
+	/// The number of bytes written to all the buffers since they were last reset.
+	pub fn bytes_written(&self) -> u64 {
+		self.vertex.bytes_written + self.index.bytes_written + self.uniform.bytes_written
+	}
+}
+
+fn align_to(value: u64, alignment: u64) -> u64 {
+	value.div_ceil(alignment) * alignment
+}
@@ -21,13 +21,34 @@ impl<Device: WgpuDevice> WgpuRenderTargetDesc<Device> for SwapChainFrameTarget<D
 	}
 
 	#[inline]
-	fn with_view<F>(&self, f: F) -> Result<()>
+	fn physical_dimensions(&self) -> Vector2<u32> {
+		self.renderer.wgpu_device().viewport_physical_dimensions()
+	}
+
+	#[inline]
+	fn sample_count(&self) -> u32 {
+		self.renderer.sample_count()
+	}
+
+	#[inline]
+	fn format(&self) -> wgpu::TextureFormat {
+		self.renderer.wgpu_device().frame_format()
+	}
+
+	#[inline]
+	fn with_views<F>(&self, f: F) -> Result<()>
 	where
-		F: FnOnce(&wgpu::TextureView) -> Result<()>,
+		F: FnOnce(&wgpu::TextureView, Option<&wgpu::TextureView>, &wgpu::TextureView) -> Result<()>,
 	{
 		self.renderer
-			.wgpu_device()
-			.with_frame(|frame| f(&frame.output.view))
+			.with_frame_attachments(|multisample_view, stencil_view| {
+				self.renderer
+					.wgpu_device()
+					.with_frame_view(|view| match multisample_view {
+						Some(multisample_view) => f(multisample_view, Some(view), stencil_view),
+						None => f(view, None, stencil_view),
+					})
+			})
 	}
 
 	fn renderer(&self) -> &Renderer<Device> {
@@ -43,6 +64,7 @@ impl<Device: WgpuDevice> WgpuRenderTargetDesc<Device> for SwapChainFrameTarget<D
 	}
 
 	fn end_render(&self) {
-		self.renderer().wgpu_device().end_frame()
+		self.renderer().wgpu_device().end_frame();
+		self.renderer().end_frame_stats();
 	}
 }
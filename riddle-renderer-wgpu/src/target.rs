@@ -8,6 +8,18 @@ pub trait WgpuRenderTargetDesc<Device: WgpuDevice> {
 	fn end_render(&self);
 	fn renderer(&self) -> &Renderer<Device>;
 	fn dimensions(&self) -> Vector2<f32>;
+	fn physical_dimensions(&self) -> Vector2<u32>;
 	fn standard_resources(&self) -> &StandardResources;
-	fn with_view<F: FnOnce(&wgpu::TextureView) -> Result<()>>(&self, f: F) -> Result<()>;
+
+	/// The number of samples per pixel of the target's attachments.
+	fn sample_count(&self) -> u32;
+
+	/// The format of the target's color attachment.
+	fn format(&self) -> wgpu::TextureFormat;
+
+	/// Provides the color attachment, resolve target and stencil attachment views for the
+	/// target. The resolve target is only provided if the target is multisampled.
+	fn with_views<F>(&self, f: F) -> Result<()>
+	where
+		F: FnOnce(&wgpu::TextureView, Option<&wgpu::TextureView>, &wgpu::TextureView) -> Result<()>;
 }
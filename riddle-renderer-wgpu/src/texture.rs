@@ -9,48 +9,139 @@ pub struct Texture {
 }
 
 impl Texture {
+	/// Create a texture holding the image. If `srgb` is set, the texture uses an sRGB format,
+	/// so its colors are decoded to linear space when sampled.
 	pub(crate) fn from_image(
 		device: &wgpu::Device,
 		queue: &wgpu::Queue,
 		image: &image::Image,
-		mag_filter: FilterMode,
-		min_filter: FilterMode,
+		init_args: &SpriteInitArgs,
 		tex_type: TextureType,
+		srgb: bool,
 	) -> Self {
-		let texture = Texture::new(device, mag_filter, min_filter, tex_type, image.dimensions());
+		let mip_levels = match tex_type {
+			TextureType::Plain if init_args.mipmaps => image::filters::mipmap_chain(image),
+			_ => vec![],
+		};
 
-		let texture_extent = wgpu::Extent3d {
-			width: image.width(),
-			height: image.height(),
-			depth_or_array_layers: 1,
+		let internal = TextureInternal::new(
+			device,
+			init_args,
+			tex_type,
+			image.dimensions(),
+			mip_levels.len() as u32 + 1,
+			srgb,
+		);
+		let texture = Self {
+			internal: internal.into(),
+		};
+
+		for (mip_level, level_image) in std::iter::once(image).chain(mip_levels.iter()).enumerate()
+		{
+			queue.write_texture(
+				wgpu::ImageCopyTexture {
+					texture: &texture.internal.texture,
+					mip_level: mip_level as u32,
+					origin: wgpu::Origin3d::ZERO,
+				},
+				level_image.as_rgba8(),
+				wgpu::ImageDataLayout {
+					offset: 0,
+					bytes_per_row: NonZeroU32::new(level_image.width() * 4),
+					rows_per_image: NonZeroU32::new(level_image.height()),
+				},
+				wgpu::Extent3d {
+					width: level_image.width(),
+					height: level_image.height(),
+					depth_or_array_layers: 1,
+				},
+			);
+		}
+
+		texture
+	}
+
+	/// Copy an image in to a region of the texture, with the image's top left corner at the
+	/// given location in texels.
+	///
+	/// The image is converted to the texture's format. The region must be within the texture,
+	/// and textures with more than one mip level can't be written to, since the smaller levels
+	/// would keep showing the old contents.
+	pub(crate) fn write_image(
+		&self,
+		queue: &wgpu::Queue,
+		image: &image::Image,
+		location: Vector2<u32>,
+	) -> Result<()> {
+		let dimensions = self.internal.dimensions;
+		if !region_fits(location, image.dimensions(), dimensions) {
+			return Err(WgpuRendererError::TextureWrite(
+				"Region is outside of the texture",
+			));
+		}
+		if self.internal.mip_level_count > 1 {
+			return Err(WgpuRendererError::TextureWrite(
+				"Textures with mipmaps can't be updated",
+			));
+		}
+		if image.width() == 0 || image.height() == 0 {
+			return Ok(());
+		}
+
+		let swizzled;
+		let data = match self.internal.format {
+			wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+				image.as_rgba8()
+			}
+			wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+				swizzled = image
+					.as_rgba8()
+					.chunks_exact(4)
+					.flat_map(|px| [px[2], px[1], px[0], px[3]])
+					.collect::<Vec<u8>>();
+				&swizzled[..]
+			}
+			_ => {
+				return Err(WgpuRendererError::TextureWrite(
+					"Unsupported texture format",
+				))
+			}
 		};
 
 		queue.write_texture(
 			wgpu::ImageCopyTexture {
-				texture: &texture.internal.texture,
+				texture: &self.internal.texture,
 				mip_level: 0,
-				origin: wgpu::Origin3d::ZERO,
+				origin: wgpu::Origin3d {
+					x: location.x,
+					y: location.y,
+					z: 0,
+				},
 			},
-			image.as_rgba8(),
+			data,
 			wgpu::ImageDataLayout {
 				offset: 0,
 				bytes_per_row: NonZeroU32::new(image.width() * 4),
 				rows_per_image: NonZeroU32::new(image.height()),
 			},
-			texture_extent,
+			wgpu::Extent3d {
+				width: image.width(),
+				height: image.height(),
+				depth_or_array_layers: 1,
+			},
 		);
 
-		texture
+		Ok(())
 	}
 
 	pub(crate) fn new(
 		device: &wgpu::Device,
-		mag_filter: FilterMode,
-		min_filter: FilterMode,
+		init_args: &SpriteInitArgs,
 		tex_type: TextureType,
 		dimensions: Vector2<u32>,
+		srgb: bool,
 	) -> Texture {
-		let internal = TextureInternal::new(device, mag_filter, min_filter, tex_type, dimensions);
+		let internal = TextureInternal::new(device, init_args, tex_type, dimensions, 1, srgb);
 		Self {
 			internal: internal.into(),
 		}
@@ -72,24 +163,54 @@ fn filter_to_wgpu(filter: FilterMode) -> wgpu::FilterMode {
 	}
 }
 
+fn address_mode_to_wgpu(address_mode: AddressMode) -> wgpu::AddressMode {
+	match address_mode {
+		AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+		AddressMode::Repeat => wgpu::AddressMode::Repeat,
+		AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+	}
+}
+
 pub(crate) enum TextureType {
 	Plain,
 	RenderTarget,
 }
 
+/// Whether colors written to and sampled from textures of the format are encoded as sRGB.
+pub(crate) fn is_srgb_format(format: wgpu::TextureFormat) -> bool {
+	matches!(
+		format,
+		wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Bgra8UnormSrgb
+	)
+}
+
+/// The sRGB or linear variant of an 8 bit color format. Other formats are returned unchanged.
+pub(crate) fn with_srgb_encoding(format: wgpu::TextureFormat, srgb: bool) -> wgpu::TextureFormat {
+	match (format, srgb) {
+		(wgpu::TextureFormat::Rgba8Unorm, true) => wgpu::TextureFormat::Rgba8UnormSrgb,
+		(wgpu::TextureFormat::Bgra8Unorm, true) => wgpu::TextureFormat::Bgra8UnormSrgb,
+		(wgpu::TextureFormat::Rgba8UnormSrgb, false) => wgpu::TextureFormat::Rgba8Unorm,
+		(wgpu::TextureFormat::Bgra8UnormSrgb, false) => wgpu::TextureFormat::Bgra8Unorm,
+		_ => format,
+	}
+}
+
 pub(crate) struct TextureInternal {
 	pub texture: wgpu::Texture,
 	pub sampler: wgpu::Sampler,
 	pub dimensions: Vector2<u32>,
+	pub format: wgpu::TextureFormat,
+	pub mip_level_count: u32,
 }
 
 impl TextureInternal {
 	pub(crate) fn new(
 		device: &wgpu::Device,
-		mag_filter: FilterMode,
-		min_filter: FilterMode,
+		init_args: &SpriteInitArgs,
 		tex_type: TextureType,
 		dimensions: Vector2<u32>,
+		mip_level_count: u32,
+		srgb: bool,
 	) -> Self {
 		let texture_extent = wgpu::Extent3d {
 			width: dimensions.x,
@@ -102,6 +223,7 @@ impl TextureInternal {
 			TextureType::RenderTarget => {
 				wgpu::TextureUsage::SAMPLED
 					| wgpu::TextureUsage::COPY_DST
+					| wgpu::TextureUsage::COPY_SRC
 					| wgpu::TextureUsage::RENDER_ATTACHMENT
 			}
 		};
@@ -110,10 +232,11 @@ impl TextureInternal {
 			TextureType::Plain => wgpu::TextureFormat::Rgba8Unorm,
 			TextureType::RenderTarget => wgpu::TextureFormat::Bgra8Unorm,
 		};
+		let format = with_srgb_encoding(format, srgb);
 
 		let texture = device.create_texture(&wgpu::TextureDescriptor {
 			size: texture_extent,
-			mip_level_count: 1,
+			mip_level_count,
 			sample_count: 1,
 			dimension: wgpu::TextureDimension::D2,
 			format,
@@ -122,11 +245,12 @@ impl TextureInternal {
 		});
 
 		let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-			address_mode_u: wgpu::AddressMode::ClampToEdge,
-			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_u: address_mode_to_wgpu(init_args.address_mode_u),
+			address_mode_v: address_mode_to_wgpu(init_args.address_mode_v),
 			address_mode_w: wgpu::AddressMode::ClampToEdge,
-			mag_filter: filter_to_wgpu(mag_filter),
-			min_filter: filter_to_wgpu(min_filter),
+			mag_filter: filter_to_wgpu(init_args.mag_filter),
+			min_filter: filter_to_wgpu(init_args.min_filter),
+			mipmap_filter: filter_to_wgpu(init_args.mipmap_filter),
 			..Default::default()
 		});
 
@@ -134,6 +258,211 @@ impl TextureInternal {
 			texture,
 			sampler,
 			dimensions,
+			format,
+			mip_level_count,
+		}
+	}
+}
+
+/// A stencil attachment, which holds the mask for a render target.
+pub(crate) struct StencilTexture {
+	_texture: wgpu::Texture,
+	pub view: wgpu::TextureView,
+	pub dimensions: Vector2<u32>,
+}
+
+impl StencilTexture {
+	pub fn new(device: &wgpu::Device, dimensions: Vector2<u32>, sample_count: u32) -> Self {
+		let (texture, view) = create_attachment(device, dimensions, sample_count, STENCIL_FORMAT);
+		Self {
+			_texture: texture,
+			view,
+			dimensions,
+		}
+	}
+}
+
+/// A multisampled color attachment, which is resolved in to a render target's texture or
+/// frame at the end of each render pass.
+pub(crate) struct MultisampleTexture {
+	_texture: wgpu::Texture,
+	pub view: wgpu::TextureView,
+}
+
+impl MultisampleTexture {
+	pub fn new(
+		device: &wgpu::Device,
+		dimensions: Vector2<u32>,
+		sample_count: u32,
+		format: wgpu::TextureFormat,
+	) -> Self {
+		let (texture, view) = create_attachment(device, dimensions, sample_count, format);
+		Self {
+			_texture: texture,
+			view,
 		}
 	}
 }
+
+fn create_attachment(
+	device: &wgpu::Device,
+	dimensions: Vector2<u32>,
+	sample_count: u32,
+	format: wgpu::TextureFormat,
+) -> (wgpu::Texture, wgpu::TextureView) {
+	let texture = device.create_texture(&wgpu::TextureDescriptor {
+		size: wgpu::Extent3d {
+			width: dimensions.x.max(1),
+			height: dimensions.y.max(1),
+			depth_or_array_layers: 1,
+		},
+		mip_level_count: 1,
+		sample_count,
+		dimension: wgpu::TextureDimension::D2,
+		format,
+		usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+		label: None,
+	});
+	let view = texture.create_view(&wgpu::TextureViewDescriptor {
+		..Default::default()
+	});
+	(texture, view)
+}
+
+/// Copy the contents of a texture in to an image, converting from the texture's format to
+/// RGBA8.
+///
+/// Only `Rgba8Unorm` and `Bgra8Unorm` textures, and their sRGB variants, are supported. sRGB
+/// textures are read back without decoding, so the image holds sRGB colors.
+///
+/// The copy only completes once the device has been polled. If `wait` is set the device is
+/// polled until the GPU has finished, blocking the calling thread, so the returned future is
+/// ready immediately. Otherwise the future stays pending until something else polls the device.
+pub(crate) async fn read_texture_image<Device: WgpuDevice>(
+	wgpu_device: &Device,
+	texture: &wgpu::Texture,
+	format: wgpu::TextureFormat,
+	dimensions: Vector2<u32>,
+	wait: bool,
+) -> Result<image::Image> {
+	let mut img = image::Image::new(dimensions.x, dimensions.y);
+	if dimensions.x == 0 || dimensions.y == 0 {
+		return Ok(img);
+	}
+
+	// Rows copied in to buffers must be padded to the wgpu alignment
+	let unpadded_bytes_per_row = dimensions.x * 4;
+	let padded_bytes_per_row = {
+		let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+		unpadded_bytes_per_row.div_ceil(align) * align
+	};
+
+	let (buffer, map_future) = wgpu_device.with_device_info(|info| {
+		let buffer = info.device.create_buffer(&wgpu::BufferDescriptor {
+			label: None,
+			size: (padded_bytes_per_row * dimensions.y) as wgpu::BufferAddress,
+			usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+			mapped_at_creation: false,
+		});
+
+		let mut encoder = info
+			.device
+			.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+		encoder.copy_texture_to_buffer(
+			wgpu::ImageCopyTexture {
+				texture,
+				mip_level: 0,
+				origin: wgpu::Origin3d::ZERO,
+			},
+			wgpu::ImageCopyBuffer {
+				buffer: &buffer,
+				layout: wgpu::ImageDataLayout {
+					offset: 0,
+					bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+					rows_per_image: NonZeroU32::new(dimensions.y),
+				},
+			},
+			wgpu::Extent3d {
+				width: dimensions.x,
+				height: dimensions.y,
+				depth_or_array_layers: 1,
+			},
+		);
+		info.queue.submit(Some(encoder.finish()));
+
+		let map_future = buffer.slice(..).map_async(wgpu::MapMode::Read);
+		Ok((buffer, map_future))
+	})?;
+
+	if wait {
+		wgpu_device.with_device_info(|info| {
+			info.device.poll(wgpu::Maintain::Wait);
+			Ok(())
+		})?;
+	}
+	map_future
+		.await
+		.map_err(|_| WgpuRendererError::TextureRead("Failed to map buffer"))?;
+
+	{
+		let mapped = buffer.slice(..).get_mapped_range();
+		let swap_red_blue = match format {
+			wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+			wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+			_ => return Err(WgpuRendererError::TextureRead("Unsupported texture format")),
+		};
+
+		let img_bytes = img.as_rgba8_mut();
+		for (src_row, dst_row) in mapped
+			.chunks(padded_bytes_per_row as usize)
+			.zip(img_bytes.chunks_mut(unpadded_bytes_per_row as usize))
+		{
+			dst_row.copy_from_slice(&src_row[..unpadded_bytes_per_row as usize]);
+			if swap_red_blue {
+				for pixel in dst_row.chunks_mut(4) {
+					pixel.swap(0, 2);
+				}
+			}
+		}
+	}
+	buffer.unmap();
+
+	Ok(img)
+}
+
+/// Whether a region with the given location and dimensions lies within bounds of the given
+/// dimensions, without overflowing.
+pub(crate) fn region_fits(
+	location: Vector2<u32>,
+	dimensions: Vector2<u32>,
+	bounds: Vector2<u32>,
+) -> bool {
+	let fits = |offset: u32, len: u32, bound: u32| match offset.checked_add(len) {
+		Some(end) => end <= bound,
+		None => false,
+	};
+	fits(location.x, dimensions.x, bounds.x) && fits(location.y, dimensions.y, bounds.y)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn region_fits_rejects_overflow() {
+		let bounds = Vector2::new(16, 8);
+		assert!(region_fits(Vector2::new(0, 0), Vector2::new(16, 8), bounds));
+		assert!(region_fits(Vector2::new(12, 4), Vector2::new(4, 4), bounds));
+		assert!(!region_fits(
+			Vector2::new(13, 0),
+			Vector2::new(4, 4),
+			bounds
+		));
+		assert!(!region_fits(Vector2::new(0, 5), Vector2::new(4, 4), bounds));
+		assert!(!region_fits(
+			Vector2::new(u32::MAX, 0),
+			Vector2::new(2, 1),
+			bounds
+		));
+	}
+}
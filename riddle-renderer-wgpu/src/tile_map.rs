@@ -0,0 +1,726 @@
+use std::{collections::HashMap, time::Duration};
+
+use math::{vec2, Rect, SpacialNumericConversion, Vector2};
+use serde::Deserialize;
+
+use crate::*;
+
+/// The number of tiles along each side of a chunk.
+const CHUNK_SIZE: u32 = 32;
+
+/// A grid of equally sized tiles within a sprite, used by a [`TileMap`].
+///
+/// Tiles are numbered from 0 in row major order.
+pub struct TileSet<Device: WgpuDevice> {
+	sprite: Sprite<Device>,
+	tile_dimensions: Vector2<f32>,
+	margin: f32,
+	spacing: f32,
+}
+
+impl<Device: WgpuDevice> TileSet<Device> {
+	/// A tile set with tiles packed edge to edge, starting at the top left of the sprite.
+	pub fn new(sprite: &Sprite<Device>, tile_dimensions: Vector2<f32>) -> Self {
+		Self {
+			sprite: sprite.clone(),
+			tile_dimensions,
+			margin: 0.0,
+			spacing: 0.0,
+		}
+	}
+
+	/// Set the gap around the edge of the sprite, and the gap between tiles, in texels.
+	pub fn with_margin_and_spacing(mut self, margin: f32, spacing: f32) -> Self {
+		self.margin = margin;
+		self.spacing = spacing;
+		self
+	}
+
+	pub fn tile_dimensions(&self) -> Vector2<f32> {
+		self.tile_dimensions
+	}
+
+	fn columns(&self) -> u32 {
+		let available = self.sprite.dimensions().x - (self.margin * 2.0) + self.spacing;
+		(available / (self.tile_dimensions.x + self.spacing))
+			.floor()
+			.max(1.0) as u32
+	}
+
+	/// The region of the tile set's sprite which a tile is drawn from.
+	fn tile_rect(&self, tile: u32) -> Rect<f32> {
+		let columns = self.columns();
+		let column = (tile % columns) as f32;
+		let row = (tile / columns) as f32;
+		Rect::new(
+			vec2(
+				self.margin + (column * (self.tile_dimensions.x + self.spacing)),
+				self.margin + (row * (self.tile_dimensions.y + self.spacing)),
+			),
+			self.tile_dimensions,
+		)
+	}
+}
+
+impl<Device: WgpuDevice> Clone for TileSet<Device> {
+	fn clone(&self) -> Self {
+		Self {
+			sprite: self.sprite.clone(),
+			tile_dimensions: self.tile_dimensions,
+			margin: self.margin,
+			spacing: self.spacing,
+		}
+	}
+}
+
+/// A single frame of an animated tile, see [`TileMap::set_tile_animation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileAnimationFrame {
+	/// The tile displayed during the frame.
+	pub tile: u32,
+
+	/// How long the frame is displayed for.
+	pub duration: Duration,
+}
+
+/// A multi layer grid of tiles, rendered from persistent GPU buffers.
+///
+/// Each layer is split in to square chunks of tiles. A chunk's geometry is uploaded to the GPU
+/// when it is first rendered, and is only rebuilt when one of its tiles changes, or when an
+/// animated tile in it changes frame. Chunks outside of the area visible with the render
+/// context's current transform aren't drawn.
+///
+/// The map is drawn with its top left corner at the world origin, and each tile occupies the
+/// tile set's tile dimensions in world units. Use [`RenderContext::set_transform`] or
+/// [`RenderContext::set_camera`] to position it.
+///
+/// Tile maps are always drawn immediately, even between [`RenderContext::begin_sorted`] and
+/// [`RenderContext::end_sorted`]. Rendering a tile map in sorted mode draws the sorted draws
+/// buffered so far first, so they are drawn under the map whatever their layer and sort key,
+/// and draws submitted afterwards are sorted separately and drawn over it.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, image::*, platform::*, renderer::*, math::*, *};
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let img = Image::new(64, 64);
+/// let tiles = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new())?;
+/// let tile_set = TileSet::new(&tiles, vec2(16.0, 16.0));
+///
+/// // A 256x256 map with a ground layer and a decoration layer
+/// let mut map = TileMap::new(&renderer, &tile_set, vec2(256, 256), 2);
+/// for y in 0..256 {
+///     for x in 0..256 {
+///         map.set_tile(0, vec2(x, y), Some(0));
+///     }
+/// }
+///
+/// // Water cycles between tiles 4 and 5
+/// map.set_tile(1, vec2(10, 10), Some(4));
+/// map.set_tile_animation(4, vec![
+///     TileAnimationFrame { tile: 4, duration: Duration::from_millis(250) },
+///     TileAnimationFrame { tile: 5, duration: Duration::from_millis(250) },
+/// ]);
+///
+/// // Once per frame
+/// map.update(Duration::from_secs_f32(rdl.state().time().delta_secs()));
+/// renderer.render(|render_ctx| {
+///     render_ctx.clear(Color::BLACK)?;
+///     map.render(render_ctx)
+/// })?;
+/// # Ok(()) }
+/// ```
+pub struct TileMap<Device: WgpuDevice> {
+	renderer: Renderer<Device>,
+	tile_set: TileSet<Device>,
+	dimensions: Vector2<u32>,
+	layers: Vec<TileLayer>,
+	animations: HashMap<u32, Vec<TileAnimationFrame>>,
+	elapsed: Duration,
+}
+
+impl<Device: WgpuDevice> TileMap<Device> {
+	/// Build an empty map with the given dimensions in tiles, and number of layers.
+	pub fn new(
+		renderer: &Renderer<Device>,
+		tile_set: &TileSet<Device>,
+		dimensions: Vector2<u32>,
+		layer_count: usize,
+	) -> Self {
+		let mut map = Self {
+			renderer: renderer.clone(),
+			tile_set: tile_set.clone(),
+			dimensions,
+			layers: vec![],
+			animations: HashMap::new(),
+			elapsed: Duration::from_secs(0),
+		};
+		for _ in 0..layer_count {
+			map.add_layer();
+		}
+		map
+	}
+
+	/// Load a map exported from the Tiled map editor in its JSON format.
+	///
+	/// The map's tile layers are loaded in order, along with tile animations. The map must
+	/// use a single embedded tile set, whose image is provided as `tile_sprite`. Layer data
+	/// must be stored as an uncompressed array, and infinite maps aren't supported. Flipped
+	/// tiles are drawn unflipped.
+	pub fn load_tiled_json<R: std::io::Read>(
+		renderer: &Renderer<Device>,
+		reader: R,
+		tile_sprite: &Sprite<Device>,
+	) -> Result<Self> {
+		let json: TiledMap = serde_json::from_reader(reader)?;
+
+		let tiled_set = match &json.tilesets[..] {
+			[tile_set] => tile_set,
+			_ => {
+				return Err(WgpuRendererError::TileMapLoad(
+					"Map must have exactly one tile set",
+				))
+			}
+		};
+		if tiled_set.source.is_some() {
+			return Err(WgpuRendererError::TileMapLoad(
+				"External tile sets are not supported",
+			));
+		}
+
+		let tile_set = TileSet::new(
+			tile_sprite,
+			vec2(tiled_set.tilewidth as f32, tiled_set.tileheight as f32),
+		)
+		.with_margin_and_spacing(tiled_set.margin as f32, tiled_set.spacing as f32);
+
+		let tile_layers: Vec<&TiledLayer> = json
+			.layers
+			.iter()
+			.filter(|layer| layer.layer_type == "tilelayer")
+			.collect();
+		let mut map = Self::new(
+			renderer,
+			&tile_set,
+			vec2(json.width, json.height),
+			tile_layers.len(),
+		);
+
+		for (layer_index, layer) in tile_layers.iter().enumerate() {
+			let data = layer.data.as_ref().ok_or(WgpuRendererError::TileMapLoad(
+				"Tile layer data must be an uncompressed array",
+			))?;
+			if data.len() != (json.width * json.height) as usize {
+				return Err(WgpuRendererError::TileMapLoad(
+					"Tile layer data doesn't match the map dimensions",
+				));
+			}
+
+			for (i, gid) in data.iter().enumerate() {
+				// The top bits of a gid hold the tile's flip flags
+				let gid = gid & 0x1FFF_FFFF;
+				if gid >= tiled_set.firstgid {
+					let location = vec2(i as u32 % json.width, i as u32 / json.width);
+					map.set_tile(layer_index, location, Some(gid - tiled_set.firstgid));
+				}
+			}
+			map.set_layer_visible(layer_index, layer.visible);
+		}
+
+		for tile in &tiled_set.tiles {
+			if let Some(animation) = &tile.animation {
+				let frames = animation
+					.iter()
+					.map(|frame| TileAnimationFrame {
+						tile: frame.tileid,
+						duration: Duration::from_millis(frame.duration),
+					})
+					.collect();
+				map.set_tile_animation(tile.id, frames);
+			}
+		}
+
+		Ok(map)
+	}
+
+	/// The dimensions of the map, in tiles.
+	pub fn dimensions(&self) -> Vector2<u32> {
+		self.dimensions
+	}
+
+	pub fn layer_count(&self) -> usize {
+		self.layers.len()
+	}
+
+	/// Add an empty layer above the existing layers, returning its index.
+	pub fn add_layer(&mut self) -> usize {
+		let chunks = self.chunk_columns() * self.chunk_rows();
+		self.layers.push(TileLayer {
+			tiles: vec![None; (self.dimensions.x * self.dimensions.y) as usize],
+			chunks: (0..chunks).map(|_| TileChunk::new()).collect(),
+			visible: true,
+		});
+		self.layers.len() - 1
+	}
+
+	/// Get the tile at a location in a layer.
+	pub fn tile(&self, layer: usize, location: Vector2<u32>) -> Option<u32> {
+		let index = self.tile_index(location)?;
+		self.layers.get(layer)?.tiles[index]
+	}
+
+	/// Set or clear the tile at a location in a layer. Locations outside of the map are ignored.
+	pub fn set_tile(&mut self, layer: usize, location: Vector2<u32>, tile: Option<u32>) {
+		let index = match self.tile_index(location) {
+			Some(index) => index,
+			None => return,
+		};
+		let chunk_index = self.chunk_index(location);
+
+		if let Some(layer) = self.layers.get_mut(layer) {
+			if layer.tiles[index] != tile {
+				layer.tiles[index] = tile;
+				layer.chunks[chunk_index].dirty = true;
+			}
+		}
+	}
+
+	/// Show or hide a layer.
+	pub fn set_layer_visible(&mut self, layer: usize, visible: bool) {
+		if let Some(layer) = self.layers.get_mut(layer) {
+			layer.visible = visible;
+		}
+	}
+
+	/// Animate every instance of a tile, cycling through the given frames.
+	///
+	/// All animations share the map's clock, which is advanced by [`TileMap::update`].
+	pub fn set_tile_animation(&mut self, tile: u32, frames: Vec<TileAnimationFrame>) {
+		self.animations.insert(tile, frames);
+		self.mark_tile_dirty(tile);
+	}
+
+	/// Stop animating a tile.
+	pub fn clear_tile_animation(&mut self, tile: u32) {
+		if self.animations.remove(&tile).is_some() {
+			self.mark_tile_dirty(tile);
+		}
+	}
+
+	/// Advance the animated tiles by the given time.
+	pub fn update(&mut self, delta: Duration) {
+		let before = self.elapsed;
+		self.elapsed += delta;
+
+		let changed: Vec<u32> = self
+			.animations
+			.iter()
+			.filter(|(_, frames)| {
+				animation_frame(frames, before) != animation_frame(frames, self.elapsed)
+			})
+			.map(|(tile, _)| *tile)
+			.collect();
+		for tile in changed {
+			self.mark_animated_tile_dirty(tile);
+		}
+	}
+
+	/// Render all visible layers, in order.
+	pub fn render<R>(&mut self, render_ctx: &mut BufferedRenderer<Device, R>) -> Result<()>
+	where
+		R: WgpuRenderTargetDesc<Device>,
+	{
+		for layer in 0..self.layers.len() {
+			if self.layers[layer].visible {
+				self.render_layer(render_ctx, layer)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Render a single layer, whether or not it is visible. This allows other things to be
+	/// drawn between layers.
+	pub fn render_layer<R>(
+		&mut self,
+		render_ctx: &mut BufferedRenderer<Device, R>,
+		layer: usize,
+	) -> Result<()>
+	where
+		R: WgpuRenderTargetDesc<Device>,
+	{
+		if layer >= self.layers.len() {
+			return Ok(());
+		}
+
+		let visible_rect = render_ctx.visible_rect();
+		for chunk_index in 0..self.layers[layer].chunks.len() {
+			let chunk_rect = self.chunk_rect(chunk_index);
+			if visible_rect.intersect(&chunk_rect).is_none() {
+				continue;
+			}
+
+			if self.layers[layer].chunks[chunk_index].dirty {
+				self.rebuild_chunk(layer, chunk_index)?;
+			}
+
+			let chunk = &self.layers[layer].chunks[chunk_index];
+			if let Some(geometry) = &chunk.geometry {
+				render_ctx.draw_buffers(
+					self.tile_set.sprite.texture(),
+					BlendMode::Alpha,
+					geometry.vertex_buffer.slice(..geometry.vertex_len),
+					(geometry.vertex_len / std::mem::size_of::<Vertex>() as u64) as u32,
+					geometry.index_buffer.slice(..geometry.index_len),
+					wgpu::IndexFormat::Uint16,
+					geometry.index_count,
+				)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn rebuild_chunk(&mut self, layer: usize, chunk_index: usize) -> Result<()> {
+		let chunk_columns = self.chunk_columns() as u32;
+		let chunk_x = (chunk_index as u32 % chunk_columns) * CHUNK_SIZE;
+		let chunk_y = (chunk_index as u32 / chunk_columns) * CHUNK_SIZE;
+		let tile_dimensions = self.tile_set.tile_dimensions;
+
+		let mut parts = vec![];
+		let mut animated_tiles = vec![];
+		for y in chunk_y..(chunk_y + CHUNK_SIZE).min(self.dimensions.y) {
+			for x in chunk_x..(chunk_x + CHUNK_SIZE).min(self.dimensions.x) {
+				let index = (y * self.dimensions.x + x) as usize;
+				if let Some(tile) = self.layers[layer].tiles[index] {
+					let displayed_tile = match self.animations.get(&tile) {
+						Some(frames) => {
+							animated_tiles.push(tile);
+							animation_frame(frames, self.elapsed)
+								.map(|frame| frames[frame].tile)
+								.unwrap_or(tile)
+						}
+						None => tile,
+					};
+
+					parts.push((
+						self.tile_set.tile_rect(displayed_tile),
+						Rect::new(
+							vec2(x as f32 * tile_dimensions.x, y as f32 * tile_dimensions.y),
+							tile_dimensions,
+						),
+					));
+				}
+			}
+		}
+		animated_tiles.sort_unstable();
+		animated_tiles.dedup();
+
+		let sprite = &self.tile_set.sprite;
+		let (verts, indices) = sprite_stretched_regions_geometry(
+			sprite.texture().internal.dimensions.convert(),
+			sprite.source_rect(),
+			&SpriteRenderArgs::default(),
+			&parts[..],
+		);
+
+		let chunk = &mut self.layers[layer].chunks[chunk_index];
+		chunk.dirty = false;
+		chunk.animated_tiles = animated_tiles;
+
+		if indices.is_empty() {
+			chunk.geometry = None;
+			return Ok(());
+		}
+
+		let geometry = chunk.geometry.take();
+		chunk.geometry = Some(self.renderer.wgpu_device().with_device_info(|info| {
+			Ok(ChunkGeometry::upload(
+				info.device,
+				info.queue,
+				geometry,
+				bytemuck::cast_slice(&verts),
+				bytemuck::cast_slice(&indices),
+				indices.len() as u32,
+			))
+		})?);
+		Ok(())
+	}
+
+	/// Mark every chunk containing a tile as needing to be rebuilt.
+	fn mark_tile_dirty(&mut self, tile: u32) {
+		let width = self.dimensions.x;
+		let chunk_columns = self.chunk_columns();
+		for layer in &mut self.layers {
+			for (index, _) in layer
+				.tiles
+				.iter()
+				.enumerate()
+				.filter(|(_, t)| **t == Some(tile))
+			{
+				let column = (index as u32 % width / CHUNK_SIZE) as usize;
+				let row = (index as u32 / width / CHUNK_SIZE) as usize;
+				layer.chunks[row * chunk_columns + column].dirty = true;
+			}
+		}
+	}
+
+	/// Mark the chunks which were built with an animated tile as needing to be rebuilt.
+	fn mark_animated_tile_dirty(&mut self, tile: u32) {
+		for layer in &mut self.layers {
+			for chunk in &mut layer.chunks {
+				if chunk.animated_tiles.contains(&tile) {
+					chunk.dirty = true;
+				}
+			}
+		}
+	}
+
+	fn tile_index(&self, location: Vector2<u32>) -> Option<usize> {
+		if location.x < self.dimensions.x && location.y < self.dimensions.y {
+			Some((location.y * self.dimensions.x + location.x) as usize)
+		} else {
+			None
+		}
+	}
+
+	fn chunk_columns(&self) -> usize {
+		self.dimensions.x.div_ceil(CHUNK_SIZE) as usize
+	}
+
+	fn chunk_rows(&self) -> usize {
+		self.dimensions.y.div_ceil(CHUNK_SIZE) as usize
+	}
+
+	fn chunk_index(&self, location: Vector2<u32>) -> usize {
+		let column = (location.x / CHUNK_SIZE) as usize;
+		let row = (location.y / CHUNK_SIZE) as usize;
+		row * self.chunk_columns() + column
+	}
+
+	/// The world space bounds of a chunk.
+	fn chunk_rect(&self, chunk_index: usize) -> Rect<f32> {
+		let chunk_columns = self.chunk_columns();
+		let column = (chunk_index % chunk_columns) as f32;
+		let row = (chunk_index / chunk_columns) as f32;
+		let chunk_dimensions = self.tile_set.tile_dimensions * CHUNK_SIZE as f32;
+		Rect::new(
+			vec2(column * chunk_dimensions.x, row * chunk_dimensions.y),
+			chunk_dimensions,
+		)
+	}
+}
+
+/// The index of the frame displayed after the given time, or `None` if the animation has no
+/// length.
+fn animation_frame(frames: &[TileAnimationFrame], elapsed: Duration) -> Option<usize> {
+	let total: Duration = frames.iter().map(|frame| frame.duration).sum();
+	if total == Duration::from_secs(0) {
+		return None;
+	}
+
+	let mut offset = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+	for (index, frame) in frames.iter().enumerate() {
+		if offset < frame.duration {
+			return Some(index);
+		}
+		offset -= frame.duration;
+	}
+	None
+}
+
+struct TileLayer {
+	tiles: Vec<Option<u32>>,
+	chunks: Vec<TileChunk>,
+	visible: bool,
+}
+
+struct TileChunk {
+	dirty: bool,
+	geometry: Option<ChunkGeometry>,
+
+	/// The animated tiles which appear in the chunk, which cause it to be rebuilt when their
+	/// animation changes frame.
+	animated_tiles: Vec<u32>,
+}
+
+impl TileChunk {
+	fn new() -> Self {
+		Self {
+			dirty: true,
+			geometry: None,
+			animated_tiles: vec![],
+		}
+	}
+}
+
+/// The GPU buffers holding a chunk's geometry. The buffers are reused when the chunk is
+/// rebuilt, unless the new geometry doesn't fit.
+struct ChunkGeometry {
+	vertex_buffer: wgpu::Buffer,
+	index_buffer: wgpu::Buffer,
+	vertex_capacity: u64,
+	index_capacity: u64,
+	vertex_len: u64,
+	index_len: u64,
+	index_count: u32,
+}
+
+impl ChunkGeometry {
+	fn upload(
+		device: &wgpu::Device,
+		queue: &wgpu::Queue,
+		existing: Option<ChunkGeometry>,
+		vertex_data: &[u8],
+		index_data: &[u8],
+		index_count: u32,
+	) -> Self {
+		let vertex_len = vertex_data.len() as u64;
+		let index_len = align_to_copy(index_data.len() as u64);
+
+		let (vertex_buffer, index_buffer, vertex_capacity, index_capacity) = match existing {
+			Some(existing)
+				if existing.vertex_capacity >= vertex_len
+					&& existing.index_capacity >= index_len =>
+			{
+				(
+					existing.vertex_buffer,
+					existing.index_buffer,
+					existing.vertex_capacity,
+					existing.index_capacity,
+				)
+			}
+			_ => (
+				create_buffer(device, wgpu::BufferUsage::VERTEX, vertex_len),
+				create_buffer(device, wgpu::BufferUsage::INDEX, index_len),
+				vertex_len,
+				index_len,
+			),
+		};
+
+		queue.write_buffer(&vertex_buffer, 0, vertex_data);
+		let mut padded_indices = index_data.to_vec();
+		padded_indices.resize(index_len as usize, 0);
+		queue.write_buffer(&index_buffer, 0, &padded_indices);
+
+		Self {
+			vertex_buffer,
+			index_buffer,
+			vertex_capacity,
+			index_capacity,
+			vertex_len,
+			index_len,
+			index_count,
+		}
+	}
+}
+
+fn create_buffer(device: &wgpu::Device, usage: wgpu::BufferUsage, size: u64) -> wgpu::Buffer {
+	device.create_buffer(&wgpu::BufferDescriptor {
+		label: None,
+		size,
+		usage: usage | wgpu::BufferUsage::COPY_DST,
+		mapped_at_creation: false,
+	})
+}
+
+fn align_to_copy(len: u64) -> u64 {
+	len.div_ceil(wgpu::COPY_BUFFER_ALIGNMENT) * wgpu::COPY_BUFFER_ALIGNMENT
+}
+
+#[derive(Deserialize)]
+struct TiledMap {
+	width: u32,
+	height: u32,
+	layers: Vec<TiledLayer>,
+	tilesets: Vec<TiledTileSet>,
+}
+
+#[derive(Deserialize)]
+struct TiledLayer {
+	#[serde(rename = "type")]
+	layer_type: String,
+	#[serde(default)]
+	data: Option<Vec<u32>>,
+	#[serde(default = "default_visible")]
+	visible: bool,
+}
+
+fn default_visible() -> bool {
+	true
+}
+
+#[derive(Deserialize)]
+struct TiledTileSet {
+	firstgid: u32,
+	#[serde(default)]
+	source: Option<String>,
+	#[serde(default)]
+	tilewidth: u32,
+	#[serde(default)]
+	tileheight: u32,
+	#[serde(default)]
+	margin: u32,
+	#[serde(default)]
+	spacing: u32,
+	#[serde(default)]
+	tiles: Vec<TiledTile>,
+}
+
+#[derive(Deserialize)]
+struct TiledTile {
+	id: u32,
+	#[serde(default)]
+	animation: Option<Vec<TiledAnimationFrame>>,
+}
+
+#[derive(Deserialize)]
+struct TiledAnimationFrame {
+	tileid: u32,
+	duration: u64,
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn sorted_draws_before_tile_map_are_drawn_under_it() {
+		// Skip when no adapter is available, such as in CI without a GPU
+		let renderer = match Renderer::new_headless(vec2(8, 4)) {
+			Ok(renderer) => renderer,
+			Err(_) => return,
+		};
+
+		let mut img = image::Image::new(1, 1);
+		img.fill(Color::<u8>::GREEN);
+		let sprite = Sprite::new_from_image(&renderer, &img, &SpriteInitArgs::new()).unwrap();
+		let tile_set = TileSet::new(&sprite, vec2(1.0, 1.0));
+
+		// The map covers the left half of the target
+		let mut map = TileMap::new(&renderer, &tile_set, vec2(4, 4), 1);
+		for y in 0..4 {
+			for x in 0..4 {
+				map.set_tile(0, vec2(x, y), Some(0));
+			}
+		}
+
+		renderer
+			.render(|render_ctx| {
+				render_ctx.clear(Color::BLACK)?;
+				render_ctx.begin_sorted()?;
+				render_ctx.set_draw_order(1, 0.0)?;
+				render_ctx.fill_rect(&Rect::new(vec2(0.0, 0.0), vec2(8.0, 4.0)), Color::RED)?;
+				map.render(render_ctx)?;
+				render_ctx.end_sorted()
+			})
+			.unwrap();
+
+		let img = renderer.wgpu_device().read_image().unwrap();
+		assert_eq!(Color::<u8>::GREEN, img.get_pixel([1, 1]));
+		assert_eq!(Color::<u8>::RED, img.get_pixel([6, 1]));
+	}
+}
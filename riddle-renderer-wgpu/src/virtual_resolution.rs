@@ -0,0 +1,200 @@
+use math::{Rect, SpacialNumericConversion, Vector2};
+
+use crate::*;
+
+/// Renders a scene at a fixed virtual resolution, then draws it scaled to fill the renderer's
+/// window.
+///
+/// The scene is rendered in to a [`SpriteRenderTarget`] with the virtual dimensions, which is
+/// sampled with [`FilterMode::Nearest`] so pixel art stays sharp. How it is scaled to fit the
+/// window is set by the [`ScalingMode`], and any part of the window not covered by the scene is
+/// filled with the letterbox color.
+///
+/// The scale and position of the scene are computed in the window's physical pixels, so with
+/// [`ScalingMode::Integer`] every virtual pixel covers the same number of display pixels even
+/// when the window has a fractional scale factor. The viewport and the window locations taken
+/// and returned by [`VirtualResolutionTarget::window_to_virtual`] and
+/// [`VirtualResolutionTarget::virtual_to_window`] are converted back to the same logical units
+/// as [`Renderer::dimensions`] and mouse positions.
+///
+/// # Example
+///
+/// ```no_run
+/// # use riddle::{common::Color, input::*, platform::*, renderer::*, math::*, *};
+/// # fn main() -> Result<(), RiddleError> {
+/// # let rdl =  RiddleLib::new()?; let window = WindowBuilder::new().build(rdl.context())?;
+/// # let input_system = rdl.state().input();
+/// let renderer = Renderer::new_from_window(&window)?;
+///
+/// let screen = VirtualResolutionTarget::new(&renderer, vec2(320, 180), ScalingMode::Integer)?;
+///
+/// // Once per frame
+/// screen.render(|scene_ctx| {
+///     scene_ctx.clear(Color::BLACK)?;
+///     scene_ctx.fill_rect(&Rect::new(vec2(150.0, 80.0), vec2(20.0, 20.0)), Color::WHITE)
+/// })?;
+/// renderer.render(|render_ctx| screen.present(render_ctx))?;
+///
+/// // Find which virtual pixel the mouse is over
+/// let mouse_pos = screen.window_to_virtual(input_system.mouse_pos(window.id()).into());
+/// # Ok(()) }
+/// ```
+pub struct VirtualResolutionTarget<Device: WgpuDevice> {
+	renderer: Renderer<Device>,
+	resolution: VirtualResolution,
+	letterbox_color: Color<f32>,
+	target: SpriteRenderTarget<Device>,
+}
+
+impl<Device: WgpuDevice> VirtualResolutionTarget<Device> {
+	/// Build a target rendering the scene at the given virtual dimensions. The letterbox color
+	/// defaults to black.
+	pub fn new(
+		renderer: &Renderer<Device>,
+		dimensions: Vector2<u32>,
+		mode: ScalingMode,
+	) -> Result<Self> {
+		let target = Self::create_target(renderer, dimensions)?;
+		Ok(Self {
+			renderer: renderer.clone(),
+			resolution: VirtualResolution::new(dimensions, mode),
+			letterbox_color: Color::BLACK,
+			target,
+		})
+	}
+
+	/// The virtual dimensions and scaling mode.
+	pub fn resolution(&self) -> &VirtualResolution {
+		&self.resolution
+	}
+
+	/// The virtual dimensions of the scene.
+	pub fn dimensions(&self) -> Vector2<u32> {
+		self.resolution.dimensions
+	}
+
+	/// Recreate the scene target with new virtual dimensions. The contents of the scene target
+	/// are lost.
+	pub fn resize(&mut self, dimensions: Vector2<u32>) -> Result<()> {
+		self.target = Self::create_target(&self.renderer, dimensions)?;
+		self.resolution.dimensions = dimensions;
+		Ok(())
+	}
+
+	pub fn scaling_mode(&self) -> ScalingMode {
+		self.resolution.mode
+	}
+
+	pub fn set_scaling_mode(&mut self, mode: ScalingMode) {
+		self.resolution.mode = mode;
+	}
+
+	pub fn letterbox_color(&self) -> Color<f32> {
+		self.letterbox_color.clone()
+	}
+
+	/// Set the color the parts of the window not covered by the scene are filled with.
+	pub fn set_letterbox_color(&mut self, color: Color<f32>) {
+		self.letterbox_color = color;
+	}
+
+	/// The target the scene is rendered in to.
+	pub fn scene(&self) -> &SpriteRenderTarget<Device> {
+		&self.target
+	}
+
+	/// Render the scene, see [`SpriteRenderTarget::render`]. The render context's dimensions are
+	/// the virtual dimensions.
+	pub fn render<R, F>(&self, f: F) -> Result<R>
+	where
+		F: FnOnce(&mut BufferedRenderer<Device, &SpriteRenderTarget<Device>>) -> Result<R>,
+	{
+		self.target.render(f)
+	}
+
+	/// Clear the render context with the letterbox color, and draw the scene scaled to fit the
+	/// renderer's window.
+	pub fn present<Ctx>(&self, render_ctx: &mut Ctx) -> Result<()>
+	where
+		Ctx: RenderContext<Renderer<Device>> + ?Sized,
+	{
+		let source = Rect::new(Vector2::new(0.0, 0.0), self.target.sprite().dimensions());
+		render_ctx.clear(self.letterbox_color.clone())?;
+		self.target.sprite().render_stretched_regions(
+			render_ctx,
+			&SpriteRenderArgs {
+				blend_mode: BlendMode::Replace,
+				..Default::default()
+			},
+			&[(source, self.viewport())],
+		)
+	}
+
+	/// The rect in the window the scene is drawn in to, in logical units.
+	pub fn viewport(&self) -> Rect<f32> {
+		let (physical, scale_factor) = self.physical_output();
+		let viewport = self.resolution.viewport(physical);
+		Rect::new(
+			to_logical(viewport.location, scale_factor),
+			to_logical(viewport.dimensions, scale_factor),
+		)
+	}
+
+	/// Map a location in the window, such as the mouse position, to a location in the scene.
+	/// Locations in the letterbox bars map to locations outside of the scene.
+	pub fn window_to_virtual(&self, point: Vector2<f32>) -> Vector2<f32> {
+		let (physical, scale_factor) = self.physical_output();
+		let point = Vector2::new(point.x * scale_factor.x, point.y * scale_factor.y);
+		self.resolution.output_to_virtual(physical, point)
+	}
+
+	/// Map a location in the scene to a location in the window.
+	pub fn virtual_to_window(&self, point: Vector2<f32>) -> Vector2<f32> {
+		let (physical, scale_factor) = self.physical_output();
+		to_logical(
+			self.resolution.virtual_to_output(physical, point),
+			scale_factor,
+		)
+	}
+
+	/// The window's dimensions in physical pixels, and the number of physical pixels per
+	/// logical unit along each axis.
+	fn physical_output(&self) -> (Vector2<f32>, Vector2<f32>) {
+		let physical: Vector2<f32> = self
+			.renderer
+			.wgpu_device()
+			.viewport_physical_dimensions()
+			.convert();
+		let logical = self.renderer.dimensions();
+		let scale_factor = |physical: f32, logical: f32| {
+			if logical > 0.0 && physical > 0.0 {
+				physical / logical
+			} else {
+				1.0
+			}
+		};
+		(
+			physical,
+			Vector2::new(
+				scale_factor(physical.x, logical.x),
+				scale_factor(physical.y, logical.y),
+			),
+		)
+	}
+
+	fn create_target(
+		renderer: &Renderer<Device>,
+		dimensions: Vector2<u32>,
+	) -> Result<SpriteRenderTarget<Device>> {
+		SpriteRenderTarget::new_with_init_args(
+			renderer,
+			dimensions,
+			1,
+			&SpriteInitArgs::new().with_filter_modes(FilterMode::Nearest, FilterMode::Nearest),
+		)
+	}
+}
+
+fn to_logical(physical: Vector2<f32>, scale_factor: Vector2<f32>) -> Vector2<f32> {
+	Vector2::new(physical.x / scale_factor.x, physical.y / scale_factor.y)
+}
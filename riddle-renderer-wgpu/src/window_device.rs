@@ -21,12 +21,21 @@ pub struct WindowWgpuDevice {
 	surface: wgpu::Surface,
 	queue: wgpu::Queue,
 
+	format: wgpu::TextureFormat,
 	swap_chain: Mutex<wgpu::SwapChain>,
 	current_frame: Mutex<Option<wgpu::SwapChainFrame>>,
 }
 
 impl WindowWgpuDevice {
 	pub fn new(window: &Window) -> Result<Self> {
+		Self::new_with_args(window, &RendererInitArgs::new())
+	}
+
+	/// Create a device for the window, with a swap chain in the surface's preferred format.
+	///
+	/// The sRGB or linear variant of the preferred format is used, depending on whether
+	/// [`RendererInitArgs::srgb`] is set.
+	pub fn new_with_args(window: &Window, init_args: &RendererInitArgs) -> Result<Self> {
 		let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
 		let surface = unsafe { instance.create_surface(window) };
 
@@ -47,16 +56,15 @@ impl WindowWgpuDevice {
 		))
 		.map_err(|_| WgpuRendererError::ApiInit("Failed to create WGPU device"))?;
 
-		let (width, height) = window.physical_size();
-		let sc_desc = wgpu::SwapChainDescriptor {
-			usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-			format: wgpu::TextureFormat::Bgra8Unorm,
-			width,
-			height,
-			present_mode: wgpu::PresentMode::Mailbox,
-		};
+		let preferred_format = adapter
+			.get_swap_chain_preferred_format(&surface)
+			.unwrap_or(wgpu::TextureFormat::Bgra8Unorm);
+		let format = with_srgb_encoding(preferred_format, init_args.srgb);
+		log::debug!("Using swap chain format {:?}", format);
 
-		let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+		let (width, height) = window.physical_size();
+		let swap_chain =
+			device.create_swap_chain(&surface, &Self::swap_chain_desc(format, width, height));
 
 		let window_event_sub = EventSub::new();
 		window.subscribe_to_events(&window_event_sub);
@@ -67,6 +75,7 @@ impl WindowWgpuDevice {
 			device,
 			surface,
 			queue,
+			format,
 			swap_chain: Mutex::new(swap_chain),
 			current_frame: Mutex::new(None),
 		})
@@ -82,19 +91,28 @@ impl WindowWgpuDevice {
 
 		if dirty_swap_chain {
 			let (width, height) = self.window.physical_size();
-			let sc_desc = wgpu::SwapChainDescriptor {
-				usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
-				format: wgpu::TextureFormat::Bgra8Unorm,
-				width,
-				height,
-				present_mode: wgpu::PresentMode::Mailbox,
-			};
-
-			let swap_chain = self.device.create_swap_chain(&self.surface, &sc_desc);
+			let swap_chain = self.device.create_swap_chain(
+				&self.surface,
+				&Self::swap_chain_desc(self.format, width, height),
+			);
 			*self.swap_chain.lock().unwrap() = swap_chain;
 		}
 	}
 
+	fn swap_chain_desc(
+		format: wgpu::TextureFormat,
+		width: u32,
+		height: u32,
+	) -> wgpu::SwapChainDescriptor {
+		wgpu::SwapChainDescriptor {
+			usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+			format,
+			width,
+			height,
+			present_mode: wgpu::PresentMode::Mailbox,
+		}
+	}
+
 	fn ensure_current_frame(&self) -> Result<()> {
 		let swap_chain = self.swap_chain.lock().unwrap();
 		let mut frame = self.current_frame.lock().unwrap();
@@ -119,6 +137,15 @@ impl WgpuDevice for WindowWgpuDevice {
 		self.window.logical_size().into()
 	}
 
+	fn viewport_physical_dimensions(&self) -> Vector2<u32> {
+		let (width, height) = self.window.physical_size();
+		Vector2::new(width, height)
+	}
+
+	fn frame_format(&self) -> wgpu::TextureFormat {
+		self.format
+	}
+
 	fn begin_frame(&self) -> Result<()> {
 		self.handle_window_events();
 		self.ensure_current_frame()
@@ -141,12 +168,12 @@ impl WgpuDevice for WindowWgpuDevice {
 	}
 
 	#[inline]
-	fn with_frame<R, F>(&self, f: F) -> Result<R>
+	fn with_frame_view<R, F>(&self, f: F) -> Result<R>
 	where
-		F: FnOnce(&wgpu::SwapChainFrame) -> Result<R>,
+		F: FnOnce(&wgpu::TextureView) -> Result<R>,
 	{
 		let frame = self.current_frame.lock().unwrap();
-		f(frame.as_ref().unwrap())
+		f(&frame.as_ref().unwrap().output.view)
 	}
 
 	fn window_id(&self) -> WindowId {
@@ -148,12 +148,12 @@ impl WgpuDevice for CustomRendererHandle {
 		f(&info)
 	}
 
-	fn with_frame<R, F>(&self, f: F) -> Result<R, WgpuRendererError>
+	fn with_frame_view<R, F>(&self, f: F) -> Result<R, WgpuRendererError>
 	where
-		F: FnOnce(&wgpu::SwapChainFrame) -> Result<R, WgpuRendererError>,
+		F: FnOnce(&wgpu::TextureView) -> Result<R, WgpuRendererError>,
 	{
 		let renderer = self.renderer.borrow();
-		f(renderer.current_frame.as_ref().unwrap())
+		f(&renderer.current_frame.as_ref().unwrap().output.view)
 	}
 
 	fn window_id(&self) -> WindowId {
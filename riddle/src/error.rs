@@ -22,6 +22,10 @@ pub enum RiddleError {
 	#[error(transparent)]
 	WgpuRenderer(#[from] renderer::WgpuRendererError),
 
+	#[cfg(feature = "riddle-renderer-wgpu")]
+	#[error(transparent)]
+	SpriteSheet(#[from] renderer::SpriteSheetError),
+
 	#[error(transparent)]
 	Window(#[from] platform::PlatformError),
 }